@@ -1,36 +1,16 @@
 -- Initial schema for portfolio tracker
+-- Extends 001_initial_schema with volume data and the default assets
 
--- Assets table
-CREATE TABLE IF NOT EXISTS assets (
-    symbol TEXT PRIMARY KEY,
-    name TEXT NOT NULL,
-    asset_type TEXT NOT NULL CHECK(asset_type IN ('STOCK', 'CRYPTO', 'COMMODITY')),
-    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-);
-
--- Price points table
-CREATE TABLE IF NOT EXISTS price_points (
-    id INTEGER PRIMARY KEY AUTOINCREMENT,
-    asset_symbol TEXT NOT NULL,
-    timestamp DATETIME NOT NULL,
-    price REAL NOT NULL,
-    volume REAL,
-    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-    FOREIGN KEY (asset_symbol) REFERENCES assets(symbol),
-    UNIQUE(asset_symbol, timestamp)
-);
+ALTER TABLE price_points ADD COLUMN volume REAL;
 
 -- Create indices for performance
-CREATE INDEX IF NOT EXISTS idx_price_points_symbol_timestamp 
-ON price_points(asset_symbol, timestamp);
-
 CREATE INDEX IF NOT EXISTS idx_price_points_timestamp 
 ON price_points(timestamp);
 
 -- Insert default assets
-INSERT OR IGNORE INTO assets (symbol, name, asset_type) VALUES
-    ('QQQ', 'Invesco QQQ Trust', 'STOCK'),
-    ('SPY', 'S&P 500 ETF', 'STOCK'),
-    ('^IXIC', 'NASDAQ Composite', 'STOCK'),
-    ('BTC', 'Bitcoin', 'CRYPTO'),
-    ('XAU', 'Gold', 'COMMODITY');
+INSERT OR IGNORE INTO assets (id, symbol, name, asset_type) VALUES
+    ('QQQ', 'QQQ', 'Invesco QQQ Trust', 'STOCK'),
+    ('SPY', 'SPY', 'S&P 500 ETF', 'STOCK'),
+    ('IXIC', '^IXIC', 'NASDAQ Composite', 'STOCK'),
+    ('BTC', 'BTC', 'Bitcoin', 'CRYPTO'),
+    ('XAU', 'XAU', 'Gold', 'COMMODITY');
@@ -0,0 +1,16 @@
+-- Splits and cash dividends used to build split-adjusted and total-return series
+
+CREATE TABLE IF NOT EXISTS corporate_actions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    asset_id TEXT NOT NULL,
+    ex_date TIMESTAMP NOT NULL,
+    action_type TEXT NOT NULL CHECK(action_type IN ('SPLIT', 'DIVIDEND')),
+    -- Split ratio (new shares per old share) or dividend amount per share
+    value REAL NOT NULL CHECK(value > 0),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (asset_id) REFERENCES assets(id),
+    UNIQUE(asset_id, ex_date, action_type)
+);
+
+CREATE INDEX IF NOT EXISTS idx_corporate_actions_asset_ex_date
+ON corporate_actions(asset_id, ex_date);
@@ -0,0 +1,20 @@
+-- OHLCV bars per asset, timestamp and interval
+
+CREATE TABLE IF NOT EXISTS bars (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    asset_id TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    interval TEXT NOT NULL CHECK(interval IN ('1h', '1d', '1w', '1mo')),
+    open REAL NOT NULL,
+    high REAL NOT NULL,
+    low REAL NOT NULL,
+    close REAL NOT NULL,
+    adjusted_close REAL,
+    volume REAL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (asset_id) REFERENCES assets(id),
+    UNIQUE(asset_id, interval, timestamp)
+);
+
+CREATE INDEX IF NOT EXISTS idx_bars_asset_interval_timestamp
+ON bars(asset_id, interval, timestamp);
@@ -0,0 +1,38 @@
+-- Market events shown as markers and shaded spans on charts
+
+CREATE TABLE IF NOT EXISTS events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    category TEXT NOT NULL CHECK(category IN ('HALVING', 'FED_DECISION', 'RECESSION', 'NOTE')),
+    title TEXT NOT NULL,
+    description TEXT,
+    -- NULL for events that concern every asset
+    asset_id TEXT,
+    start_date TIMESTAMP NOT NULL,
+    -- Set for events spanning a period, NULL for single-day events
+    end_date TIMESTAMP CHECK(end_date IS NULL OR end_date >= start_date),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (asset_id) REFERENCES assets(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_events_start_date
+ON events(start_date);
+
+-- Bitcoin halvings
+INSERT INTO events (category, title, asset_id, start_date) VALUES
+    ('HALVING', 'Bitcoin halving', 'BTC', '2012-11-28T00:00:00+00:00'),
+    ('HALVING', 'Bitcoin halving', 'BTC', '2016-07-09T00:00:00+00:00'),
+    ('HALVING', 'Bitcoin halving', 'BTC', '2020-05-11T00:00:00+00:00'),
+    ('HALVING', 'Bitcoin halving', 'BTC', '2024-04-20T00:00:00+00:00');
+
+-- Turning points in the federal funds rate
+INSERT INTO events (category, title, description, start_date) VALUES
+    ('FED_DECISION', 'Fed cuts to zero', 'Emergency cut to 0-0.25%', '2020-03-15T00:00:00+00:00'),
+    ('FED_DECISION', 'Fed starts hiking', 'First hike since 2018, to 0.25-0.50%', '2022-03-16T00:00:00+00:00'),
+    ('FED_DECISION', 'Fed 75bp hike', 'Largest hike since 1994', '2022-06-15T00:00:00+00:00'),
+    ('FED_DECISION', 'Fed final hike', 'Target range 5.25-5.50%', '2023-07-26T00:00:00+00:00'),
+    ('FED_DECISION', 'Fed starts cutting', '50bp cut to 4.75-5.00%', '2024-09-18T00:00:00+00:00');
+
+-- NBER-dated US recessions
+INSERT INTO events (category, title, start_date, end_date) VALUES
+    ('RECESSION', 'Great Recession', '2007-12-01T00:00:00+00:00', '2009-06-30T00:00:00+00:00'),
+    ('RECESSION', 'COVID-19 recession', '2020-02-01T00:00:00+00:00', '2020-04-30T00:00:00+00:00');
@@ -0,0 +1,13 @@
+-- One row per successful price refresh, recording which provider the data
+-- came from
+CREATE TABLE IF NOT EXISTS price_refreshes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    asset_id TEXT NOT NULL,
+    source TEXT NOT NULL,
+    points INTEGER NOT NULL,
+    refreshed_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (asset_id) REFERENCES assets(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_refreshes_asset_refreshed_at
+ON price_refreshes(asset_id, refreshed_at);
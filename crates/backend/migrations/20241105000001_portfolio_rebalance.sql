@@ -0,0 +1,3 @@
+-- How often a saved portfolio is traded back to its target weights
+ALTER TABLE portfolios ADD COLUMN rebalance TEXT NOT NULL DEFAULT 'NEVER'
+    CHECK(rebalance IN ('NEVER', 'MONTHLY', 'QUARTERLY', 'YEARLY'));
@@ -1,7 +1,9 @@
-use super::PriceDataClient;
-use shared::PricePoint;
-use chrono::{DateTime, Utc};
+use super::{BarDataClient, PriceDataClient};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
 use reqwest::Client;
+use serde_json::Value;
+use shared::{Asset, AssetType, Bar, BarInterval, PricePoint};
 
 pub struct AlphaVantageClient {
     client: Client,
@@ -10,6 +12,9 @@ pub struct AlphaVantageClient {
 }
 
 impl AlphaVantageClient {
+    /// Provider name recorded with each refresh
+    pub const SOURCE: &'static str = "Alpha Vantage";
+
     pub fn new(api_key: String) -> Self {
         Self {
             client: Client::new(),
@@ -17,22 +22,244 @@ impl AlphaVantageClient {
             base_url: "https://www.alphavantage.co/query".to_string(),
         }
     }
+
+    /// Client configured from `ALPHA_VANTAGE_API_KEY`, if a key is set
+    pub fn from_env() -> Option<Self> {
+        std::env::var("ALPHA_VANTAGE_API_KEY")
+            .ok()
+            .filter(|key| !key.is_empty() && key != "your_api_key_here")
+            .map(Self::new)
+    }
+
+    async fn query(&self, params: &[(&str, &str)]) -> anyhow::Result<Value> {
+        let body: Value = self
+            .client
+            .get(&self.base_url)
+            .query(params)
+            .query(&[("apikey", self.api_key.as_str())])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        // Errors and rate limits come back as 200 with a message instead of data
+        for key in ["Error Message", "Note", "Information"] {
+            if let Some(message) = body.get(key).and_then(Value::as_str) {
+                anyhow::bail!("Alpha Vantage: {}", message);
+            }
+        }
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl PriceDataClient for AlphaVantageClient {
+    async fn fetch_historical(
+        &self,
+        symbol: &str,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<PricePoint>> {
+        let body = self
+            .query(&[
+                ("function", "TIME_SERIES_DAILY"),
+                ("symbol", symbol),
+                ("outputsize", "full"),
+            ])
+            .await?;
+
+        Ok(parse_daily_series(symbol, &body, start_date, end_date)?
+            .iter()
+            .map(|bar| bar.to_price_point(Default::default()))
+            .collect())
+    }
+
+    async fn fetch_latest(&self, symbol: &str) -> anyhow::Result<PricePoint> {
+        let body = self
+            .query(&[("function", "GLOBAL_QUOTE"), ("symbol", symbol)])
+            .await?;
+        let quote = body
+            .get("Global Quote")
+            .ok_or_else(|| anyhow::anyhow!("Alpha Vantage: missing quote for {}", symbol))?;
+
+        let field = |name: &str| {
+            quote
+                .get(name)
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow::anyhow!("Alpha Vantage: quote is missing {}", name))
+        };
+        let day: NaiveDate = field("07. latest trading day")?.parse()?;
+
+        Ok(PricePoint {
+            asset_id: symbol.to_string(),
+            timestamp: day.and_time(Default::default()).and_utc(),
+            price: field("05. price")?.parse()?,
+        })
+    }
 }
 
-// #[async_trait]
-// impl PriceDataClient for AlphaVantageClient {
-//     async fn fetch_historical(
-//         &self,
-//         symbol: &str,
-//         start_date: DateTime<Utc>,
-//         end_date: DateTime<Utc>,
-//     ) -> anyhow::Result<Vec<PricePoint>> {
-//         // TODO: Implement Alpha Vantage API call
-//         todo!("Implement Alpha Vantage historical data fetch")
-//     }
-
-//     async fn fetch_latest(&self, symbol: &str) -> anyhow::Result<PricePoint> {
-//         // TODO: Implement Alpha Vantage API call
-//         todo!("Implement Alpha Vantage latest price fetch")
-//     }
-// }
+#[async_trait]
+impl BarDataClient for AlphaVantageClient {
+    async fn fetch_daily_bars(
+        &self,
+        asset: &Asset,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<Bar>> {
+        let body = match asset.asset_type {
+            AssetType::Stock => {
+                self.query(&[
+                    ("function", "TIME_SERIES_DAILY_ADJUSTED"),
+                    ("symbol", &asset.symbol),
+                    ("outputsize", "full"),
+                ])
+                .await?
+            }
+            AssetType::Crypto => {
+                self.query(&[
+                    ("function", "DIGITAL_CURRENCY_DAILY"),
+                    ("symbol", &asset.symbol),
+                    ("market", "USD"),
+                ])
+                .await?
+            }
+            AssetType::Commodity => {
+                anyhow::bail!("Alpha Vantage has no daily bars for {}", asset.symbol)
+            }
+        };
+
+        parse_daily_series(&asset.id, &body, start_date, end_date)
+    }
+}
+
+/// Parse any of the daily "Time Series" payloads into bars within the range.
+///
+/// Field names vary by endpoint ("4. close", "5. adjusted close",
+/// "4b. close (USD)"), so they are matched on the part after the number,
+/// keeping only USD-denominated fields.
+fn parse_daily_series(
+    asset_id: &str,
+    body: &Value,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> anyhow::Result<Vec<Bar>> {
+    let series = body
+        .as_object()
+        .and_then(|obj| obj.iter().find(|(key, _)| key.starts_with("Time Series")))
+        .and_then(|(_, series)| series.as_object())
+        .ok_or_else(|| anyhow::anyhow!("Alpha Vantage: response has no time series"))?;
+
+    let mut bars = Vec::with_capacity(series.len());
+    for (day, fields) in series {
+        let timestamp = day
+            .parse::<NaiveDate>()?
+            .and_time(Default::default())
+            .and_utc();
+        if timestamp < start_date || timestamp > end_date {
+            continue;
+        }
+
+        let mut bar = Bar {
+            asset_id: asset_id.to_string(),
+            timestamp,
+            interval: BarInterval::Day,
+            open: f64::NAN,
+            high: f64::NAN,
+            low: f64::NAN,
+            close: f64::NAN,
+            adjusted_close: None,
+            volume: None,
+        };
+        for (key, value) in fields.as_object().into_iter().flatten() {
+            let name = key.split_once(". ").map_or(key.as_str(), |(_, name)| name);
+            let name = match name.split_once(" (") {
+                Some((name, "USD)")) => name,
+                Some(_) => continue,
+                None => name,
+            };
+            let Some(value) = value.as_str().and_then(|v| v.parse::<f64>().ok()) else {
+                continue;
+            };
+            match name {
+                "open" => bar.open = value,
+                "high" => bar.high = value,
+                "low" => bar.low = value,
+                "close" => bar.close = value,
+                "adjusted close" => bar.adjusted_close = Some(value),
+                "volume" => bar.volume = Some(value),
+                _ => {}
+            }
+        }
+
+        if [bar.open, bar.high, bar.low, bar.close]
+            .iter()
+            .any(|v| v.is_nan())
+        {
+            anyhow::bail!("Alpha Vantage: incomplete bar for {} on {}", asset_id, day);
+        }
+        bars.push(bar);
+    }
+
+    bars.sort_by_key(|bar| bar.timestamp);
+    Ok(bars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_daily_series() {
+        let adjusted = serde_json::json!({
+            "Meta Data": { "2. Symbol": "QQQ" },
+            "Time Series (Daily)": {
+                "2024-01-03": {
+                    "1. open": "402.0", "2. high": "404.5", "3. low": "400.1",
+                    "4. close": "403.2", "5. adjusted close": "401.9",
+                    "6. volume": "51000000", "7. dividend amount": "0.0000"
+                },
+                "2024-01-02": {
+                    "1. open": "405.0", "2. high": "406.0", "3. low": "401.0",
+                    "4. close": "402.0", "5. adjusted close": "400.7",
+                    "6. volume": "62000000", "7. dividend amount": "0.0000"
+                }
+            }
+        });
+        let crypto = serde_json::json!({
+            "Time Series (Digital Currency Daily)": {
+                "2024-01-02": {
+                    "1a. open (EUR)": "41000.0", "1b. open (USD)": "44000.0",
+                    "2b. high (USD)": "45800.0", "3b. low (USD)": "44200.0",
+                    "4b. close (USD)": "45000.0", "5. volume": "31000.5"
+                }
+            }
+        });
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 12, 31, 0, 0, 0).unwrap();
+
+        let bars = parse_daily_series("QQQ", &adjusted, start, end).unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(
+            bars[0].timestamp,
+            Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap()
+        );
+        assert_eq!(bars[0].close, 402.0);
+        assert_eq!(bars[0].adjusted_close, Some(400.7));
+        assert_eq!(bars[1].volume, Some(51_000_000.0));
+
+        let bars = parse_daily_series("BTC", &crypto, start, end).unwrap();
+        assert_eq!(bars[0].open, 44000.0);
+        assert_eq!(bars[0].close, 45000.0);
+        assert_eq!(bars[0].adjusted_close, None);
+
+        let later = Utc.with_ymd_and_hms(2024, 1, 3, 0, 0, 0).unwrap();
+        assert_eq!(
+            parse_daily_series("QQQ", &adjusted, later, end)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -1,42 +1,204 @@
 use super::PriceDataClient;
-use shared::PricePoint;
-use chrono::{DateTime, Utc};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveTime, Utc};
 use reqwest::Client;
 use serde::Deserialize;
+use shared::PricePoint;
+use std::collections::HashMap;
 
 pub struct CoinGeckoClient {
     client: Client,
     base_url: String,
+    api_key: Option<String>,
 }
 
 impl CoinGeckoClient {
+    /// Provider name recorded with each refresh
+    pub const SOURCE: &'static str = "CoinGecko";
+
     pub fn new() -> Self {
         Self {
             client: Client::new(),
             base_url: "https://api.coingecko.com/api/v3".to_string(),
+            api_key: std::env::var("COINGECKO_API_KEY")
+                .ok()
+                .filter(|key| !key.is_empty() && key != "optional"),
+        }
+    }
+
+    async fn get<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> anyhow::Result<T> {
+        let mut request = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .query(params);
+        if let Some(key) = &self.api_key {
+            request = request.header("x-cg-demo-api-key", key);
         }
+        Ok(request.send().await?.error_for_status()?.json().await?)
     }
 }
 
+impl Default for CoinGeckoClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// CoinGecko identifies coins by slug rather than ticker
+fn coin_id(symbol: &str) -> anyhow::Result<&'static str> {
+    match symbol.to_ascii_uppercase().as_str() {
+        "BTC" => Ok("bitcoin"),
+        "ETH" => Ok("ethereum"),
+        other => anyhow::bail!("CoinGecko: unknown coin {}", other),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinGeckoMarketChart {
+    /// `[unix_millis, price]` pairs
+    prices: Vec<(f64, f64)>,
+}
+
 #[derive(Debug, Deserialize)]
 struct CoinGeckoPrice {
-    // TODO: Define actual response structure
-}
-
-// #[async_trait]
-// impl PriceDataClient for CoinGeckoClient {
-//     async fn fetch_historical(
-//         &self,
-//         symbol: &str,
-//         start_date: DateTime<Utc>,
-//         end_date: DateTime<Utc>,
-//     ) -> anyhow::Result<Vec<PricePoint>> {
-//         // TODO: Implement CoinGecko API call
-//         todo!("Implement CoinGecko historical data fetch")
-//     }
-
-//     async fn fetch_latest(&self, symbol: &str) -> anyhow::Result<PricePoint> {
-//         // TODO: Implement CoinGecko API call
-//         todo!("Implement CoinGecko latest price fetch")
-//     }
-// }
+    usd: f64,
+    last_updated_at: i64,
+}
+
+fn parse_market_chart(symbol: &str, chart: CoinGeckoMarketChart) -> Vec<PricePoint> {
+    chart
+        .prices
+        .into_iter()
+        .filter_map(|(millis, price)| {
+            Some(PricePoint {
+                asset_id: symbol.to_string(),
+                timestamp: DateTime::from_timestamp_millis(millis as i64)?,
+                price,
+            })
+        })
+        .collect()
+}
+
+/// The last price of each UTC day, stamped at midnight like daily bars.
+/// Ranges under 90 days come back hourly or finer, which would otherwise
+/// mix intraday points into the daily history.
+fn daily_closes(prices: Vec<PricePoint>) -> Vec<PricePoint> {
+    let mut closes: Vec<PricePoint> = Vec::new();
+    for mut point in prices {
+        point.timestamp = point
+            .timestamp
+            .date_naive()
+            .and_time(NaiveTime::MIN)
+            .and_utc();
+        match closes.last_mut() {
+            Some(last) if last.timestamp == point.timestamp => *last = point,
+            _ => closes.push(point),
+        }
+    }
+    closes
+}
+
+#[async_trait]
+impl PriceDataClient for CoinGeckoClient {
+    async fn fetch_historical(
+        &self,
+        symbol: &str,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<PricePoint>> {
+        let chart: CoinGeckoMarketChart = self
+            .get(
+                &format!("/coins/{}/market_chart/range", coin_id(symbol)?),
+                &[
+                    ("vs_currency", "usd".to_string()),
+                    ("from", start_date.timestamp().to_string()),
+                    ("to", end_date.timestamp().to_string()),
+                ],
+            )
+            .await?;
+
+        Ok(daily_closes(parse_market_chart(symbol, chart)))
+    }
+
+    async fn fetch_latest(&self, symbol: &str) -> anyhow::Result<PricePoint> {
+        let id = coin_id(symbol)?;
+        let mut prices: HashMap<String, CoinGeckoPrice> = self
+            .get(
+                "/simple/price",
+                &[
+                    ("ids", id.to_string()),
+                    ("vs_currencies", "usd".to_string()),
+                    ("include_last_updated_at", "true".to_string()),
+                ],
+            )
+            .await?;
+        let latest = prices
+            .remove(id)
+            .ok_or_else(|| anyhow::anyhow!("CoinGecko: no price for {}", symbol))?;
+
+        Ok(PricePoint {
+            asset_id: symbol.to_string(),
+            timestamp: DateTime::from_timestamp(latest.last_updated_at, 0)
+                .ok_or_else(|| anyhow::anyhow!("CoinGecko: invalid timestamp"))?,
+            price: latest.usd,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_market_chart() {
+        let chart: CoinGeckoMarketChart = serde_json::from_str(
+            r#"{
+                "prices": [[1704067200000, 42280.23], [1704153600000, 44187.14]],
+                "market_caps": [[1704067200000, 828000000000.0]],
+                "total_volumes": [[1704067200000, 14000000000.0]]
+            }"#,
+        )
+        .unwrap();
+
+        let prices = parse_market_chart("BTC", chart);
+        assert_eq!(prices.len(), 2);
+        assert_eq!(
+            prices[0].timestamp.to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+        assert_eq!(prices[1].price, 44187.14);
+    }
+
+    #[test]
+    fn test_daily_closes_of_hourly_points() {
+        let chart: CoinGeckoMarketChart = serde_json::from_str(
+            r#"{
+                "prices": [
+                    [1704067200000, 42280.0],
+                    [1704070800000, 42300.0],
+                    [1704150000000, 44000.0],
+                    [1704153600000, 44187.0],
+                    [1704157200000, 44250.0]
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let closes = daily_closes(parse_market_chart("BTC", chart));
+        let days: Vec<(String, f64)> = closes
+            .iter()
+            .map(|p| (p.timestamp.to_rfc3339(), p.price))
+            .collect();
+        assert_eq!(
+            days,
+            vec![
+                ("2024-01-01T00:00:00+00:00".to_string(), 44000.0),
+                ("2024-01-02T00:00:00+00:00".to_string(), 44250.0),
+            ]
+        );
+    }
+}
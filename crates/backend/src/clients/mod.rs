@@ -4,7 +4,7 @@
 pub mod coingecko;
 pub mod alpha_vantage;
 
-use shared::PricePoint;
+use shared::{Asset, Bar, PricePoint};
 use chrono::{DateTime, Utc};
 use async_trait::async_trait;
 
@@ -21,3 +21,15 @@ pub trait PriceDataClient {
     /// Fetch the latest price for an asset
     async fn fetch_latest(&self, symbol: &str) -> anyhow::Result<PricePoint>;
 }
+
+/// Providers that supply full OHLCV bars rather than a single price
+#[async_trait]
+pub trait BarDataClient {
+    /// Fetch daily bars for an asset, oldest first
+    async fn fetch_daily_bars(
+        &self,
+        asset: &Asset,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<Bar>>;
+}
@@ -0,0 +1,23 @@
+use shared::Asset;
+use sqlx::Row;
+
+use super::DbPool;
+
+/// All assets in the `assets` table, ordered by id
+pub async fn fetch_assets(pool: &DbPool) -> anyhow::Result<Vec<Asset>> {
+    let rows = sqlx::query("SELECT id, symbol, name, asset_type FROM assets ORDER BY id")
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter()
+        .map(|row| {
+            let asset_type: String = row.try_get("asset_type")?;
+            Ok(Asset {
+                id: row.try_get("id")?,
+                symbol: row.try_get("symbol")?,
+                name: row.try_get("name")?,
+                asset_type: asset_type.parse().map_err(anyhow::Error::msg)?,
+            })
+        })
+        .collect()
+}
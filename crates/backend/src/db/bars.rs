@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use shared::{Bar, BarInterval};
+use sqlx::Row;
+
+use super::DbPool;
+
+/// Bars for one asset and interval within `[start_date, end_date]`, oldest first
+pub async fn fetch_bars(
+    pool: &DbPool,
+    asset_id: &str,
+    interval: BarInterval,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> anyhow::Result<Vec<Bar>> {
+    let rows = sqlx::query(
+        "SELECT asset_id, timestamp, interval, open, high, low, close, adjusted_close, volume \
+         FROM bars \
+         WHERE asset_id = ? AND interval = ? AND timestamp BETWEEN ? AND ? \
+         ORDER BY timestamp",
+    )
+    .bind(asset_id)
+    .bind(interval.as_str())
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let interval: String = row.try_get("interval")?;
+            Ok(Bar {
+                asset_id: row.try_get("asset_id")?,
+                timestamp: row.try_get("timestamp")?,
+                interval: interval.parse().map_err(anyhow::Error::msg)?,
+                open: row.try_get("open")?,
+                high: row.try_get("high")?,
+                low: row.try_get("low")?,
+                close: row.try_get("close")?,
+                adjusted_close: row.try_get("adjusted_close")?,
+                volume: row.try_get("volume")?,
+            })
+        })
+        .collect()
+}
+
+/// Insert or replace bars, keyed by asset, interval and timestamp
+pub async fn upsert_bars(pool: &DbPool, bars: &[Bar]) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for bar in bars {
+        sqlx::query(
+            "INSERT INTO bars \
+             (asset_id, timestamp, interval, open, high, low, close, adjusted_close, volume) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT(asset_id, interval, timestamp) DO UPDATE SET \
+             open = excluded.open, high = excluded.high, low = excluded.low, \
+             close = excluded.close, adjusted_close = excluded.adjusted_close, \
+             volume = excluded.volume",
+        )
+        .bind(&bar.asset_id)
+        .bind(bar.timestamp)
+        .bind(bar.interval.as_str())
+        .bind(bar.open)
+        .bind(bar.high)
+        .bind(bar.low)
+        .bind(bar.close)
+        .bind(bar.adjusted_close)
+        .bind(bar.volume)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use shared::{CorporateAction, CorporateActionKind};
+use sqlx::Row;
+
+use super::DbPool;
+
+/// All splits and dividends recorded for an asset, oldest first
+pub async fn fetch_corporate_actions(
+    pool: &DbPool,
+    asset_id: &str,
+) -> anyhow::Result<Vec<CorporateAction>> {
+    let rows = sqlx::query(
+        "SELECT asset_id, ex_date, action_type, value FROM corporate_actions \
+         WHERE asset_id = ? ORDER BY ex_date",
+    )
+    .bind(asset_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let action_type: String = row.try_get("action_type")?;
+            let value: f64 = row.try_get("value")?;
+            let kind = match action_type.as_str() {
+                "SPLIT" => CorporateActionKind::Split { ratio: value },
+                "DIVIDEND" => CorporateActionKind::CashDividend { amount: value },
+                other => anyhow::bail!("unknown corporate action type: {}", other),
+            };
+            Ok(CorporateAction {
+                asset_id: row.try_get("asset_id")?,
+                ex_date: row.try_get("ex_date")?,
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Insert or replace corporate actions, keyed by asset, ex-date and type
+pub async fn upsert_corporate_actions(
+    pool: &DbPool,
+    actions: &[CorporateAction],
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for action in actions {
+        sqlx::query(
+            "INSERT INTO corporate_actions (asset_id, ex_date, action_type, value) \
+             VALUES (?, ?, ?, ?) \
+             ON CONFLICT(asset_id, ex_date, action_type) DO UPDATE SET value = excluded.value",
+        )
+        .bind(&action.asset_id)
+        .bind(action.ex_date)
+        .bind(action.kind.action_type())
+        .bind(action.kind.value())
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use shared::{CreateEventRequest, EventCategory, MarketEvent};
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+
+use super::DbPool;
+
+fn event_from_row(row: &SqliteRow) -> anyhow::Result<MarketEvent> {
+    let category: String = row.try_get("category")?;
+    Ok(MarketEvent {
+        id: row.try_get("id")?,
+        category: category.parse().map_err(anyhow::Error::msg)?,
+        title: row.try_get("title")?,
+        description: row.try_get("description")?,
+        asset_id: row.try_get("asset_id")?,
+        start_date: row.try_get("start_date")?,
+        end_date: row.try_get("end_date")?,
+    })
+}
+
+/// Events overlapping `[start_date, end_date]`, optionally of one category,
+/// oldest first. Either bound may be left open.
+pub async fn fetch_events(
+    pool: &DbPool,
+    start_date: Option<DateTime<Utc>>,
+    end_date: Option<DateTime<Utc>>,
+    category: Option<EventCategory>,
+) -> anyhow::Result<Vec<MarketEvent>> {
+    let rows = sqlx::query(
+        "SELECT id, category, title, description, asset_id, start_date, end_date FROM events \
+         WHERE (?1 IS NULL OR COALESCE(end_date, start_date) >= ?1) \
+         AND (?2 IS NULL OR start_date <= ?2) \
+         AND (?3 IS NULL OR category = ?3) \
+         ORDER BY start_date, id",
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .bind(category.map(|c| c.as_str()))
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(event_from_row).collect()
+}
+
+/// Store a new event and return it with its id
+pub async fn insert_event(
+    pool: &DbPool,
+    event: &CreateEventRequest,
+) -> anyhow::Result<MarketEvent> {
+    let row = sqlx::query(
+        "INSERT INTO events (category, title, description, asset_id, start_date, end_date) \
+         VALUES (?, ?, ?, ?, ?, ?) \
+         RETURNING id, category, title, description, asset_id, start_date, end_date",
+    )
+    .bind(event.category.as_str())
+    .bind(&event.title)
+    .bind(&event.description)
+    .bind(&event.asset_id)
+    .bind(event.start_date)
+    .bind(event.end_date)
+    .fetch_one(pool)
+    .await?;
+
+    event_from_row(&row)
+}
+
+/// Delete an event; returns whether it existed
+pub async fn delete_event(pool: &DbPool, id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM events WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -1,3 +1,11 @@
+pub mod assets;
+pub mod bars;
+pub mod corporate_actions;
+pub mod events;
+pub mod portfolios;
+pub mod prices;
+pub mod refreshes;
+
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use std::env;
 
@@ -0,0 +1,111 @@
+use shared::{Portfolio, PortfolioAsset, RebalanceRule};
+use sqlx::Row;
+
+use super::DbPool;
+
+/// Insert a portfolio and its weights into `portfolios`/`portfolio_assets`
+pub async fn create_portfolio(
+    pool: &DbPool,
+    name: &str,
+    assets: &[PortfolioAsset],
+    rebalance: RebalanceRule,
+) -> anyhow::Result<Portfolio> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("INSERT INTO portfolios (id, name, rebalance) VALUES (?, ?, ?)")
+        .bind(&id)
+        .bind(name)
+        .bind(rebalance.as_str())
+        .execute(&mut *tx)
+        .await?;
+
+    for asset in assets {
+        sqlx::query(
+            "INSERT INTO portfolio_assets (portfolio_id, asset_id, weight) VALUES (?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(&asset.asset_id)
+        .bind(asset.weight)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(Portfolio {
+        id,
+        name: name.to_string(),
+        assets: assets.to_vec(),
+        rebalance,
+    })
+}
+
+/// A saved portfolio with its weights, if it exists
+pub async fn fetch_portfolio(pool: &DbPool, id: &str) -> anyhow::Result<Option<Portfolio>> {
+    let Some(row) = sqlx::query("SELECT id, name, rebalance FROM portfolios WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let assets = sqlx::query(
+        "SELECT asset_id, weight FROM portfolio_assets WHERE portfolio_id = ? ORDER BY asset_id",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| {
+        Ok(PortfolioAsset {
+            asset_id: row.try_get("asset_id")?,
+            weight: row.try_get("weight")?,
+        })
+    })
+    .collect::<anyhow::Result<_>>()?;
+
+    let rebalance: String = row.try_get("rebalance")?;
+    Ok(Some(Portfolio {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        assets,
+        rebalance: rebalance.parse().map_err(anyhow::Error::msg)?,
+    }))
+}
+
+/// All saved portfolios with their weights, newest first
+pub async fn fetch_portfolios(pool: &DbPool) -> anyhow::Result<Vec<Portfolio>> {
+    let rows = sqlx::query(
+        "SELECT p.id, p.name, p.rebalance, pa.asset_id, pa.weight FROM portfolios p \
+         LEFT JOIN portfolio_assets pa ON pa.portfolio_id = p.id \
+         ORDER BY p.created_at DESC, p.name, p.id, pa.asset_id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Rows of one portfolio are adjacent; a portfolio without holdings
+    // has a single row with no asset
+    let mut portfolios: Vec<Portfolio> = Vec::new();
+    for row in rows {
+        let id: String = row.try_get("id")?;
+        if portfolios.last().is_none_or(|p| p.id != id) {
+            let rebalance: String = row.try_get("rebalance")?;
+            portfolios.push(Portfolio {
+                id,
+                name: row.try_get("name")?,
+                assets: Vec::new(),
+                rebalance: rebalance.parse().map_err(anyhow::Error::msg)?,
+            });
+        }
+        let asset_id: Option<String> = row.try_get("asset_id")?;
+        if let (Some(asset_id), Some(portfolio)) = (asset_id, portfolios.last_mut()) {
+            portfolio.assets.push(PortfolioAsset {
+                asset_id,
+                weight: row.try_get("weight")?,
+            });
+        }
+    }
+    Ok(portfolios)
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use shared::PricePoint;
+use sqlx::Row;
+
+use super::DbPool;
+
+/// Price history for one asset within `[start_date, end_date]`, oldest first
+pub async fn fetch_prices(
+    pool: &DbPool,
+    asset_id: &str,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> anyhow::Result<Vec<PricePoint>> {
+    let rows = sqlx::query(
+        "SELECT asset_id, timestamp, price FROM price_points \
+         WHERE asset_id = ? AND timestamp BETWEEN ? AND ? \
+         ORDER BY timestamp",
+    )
+    .bind(asset_id)
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(PricePoint {
+                asset_id: row.try_get("asset_id")?,
+                timestamp: row.try_get("timestamp")?,
+                price: row.try_get("price")?,
+            })
+        })
+        .collect()
+}
+
+/// Insert or replace price points, keyed by asset and timestamp
+pub async fn upsert_prices(pool: &DbPool, prices: &[PricePoint]) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for point in prices {
+        sqlx::query(
+            "INSERT INTO price_points (asset_id, timestamp, price) VALUES (?, ?, ?) \
+             ON CONFLICT(asset_id, timestamp) DO UPDATE SET price = excluded.price",
+        )
+        .bind(&point.asset_id)
+        .bind(point.timestamp)
+        .bind(point.price)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// First and last price timestamps stored for an asset, if it has any
+pub async fn price_range(
+    pool: &DbPool,
+    asset_id: &str,
+) -> anyhow::Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+    let row = sqlx::query(
+        "SELECT MIN(timestamp) AS first, MAX(timestamp) AS last FROM price_points \
+         WHERE asset_id = ?",
+    )
+    .bind(asset_id)
+    .fetch_one(pool)
+    .await?;
+
+    let first: Option<DateTime<Utc>> = row.try_get("first")?;
+    let last: Option<DateTime<Utc>> = row.try_get("last")?;
+    Ok(first.zip(last))
+}
+
+/// Timestamp of the most recent stored price for an asset
+pub async fn latest_timestamp(
+    pool: &DbPool,
+    asset_id: &str,
+) -> anyhow::Result<Option<DateTime<Utc>>> {
+    let row = sqlx::query("SELECT MAX(timestamp) AS latest FROM price_points WHERE asset_id = ?")
+        .bind(asset_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(row.try_get("latest")?)
+}
+
+/// Most recent stored price of an asset
+pub async fn latest_price(pool: &DbPool, asset_id: &str) -> anyhow::Result<Option<f64>> {
+    let row = sqlx::query(
+        "SELECT price FROM price_points WHERE asset_id = ? ORDER BY timestamp DESC LIMIT 1",
+    )
+    .bind(asset_id)
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(|row| row.try_get("price")).transpose().map_err(Into::into)
+}
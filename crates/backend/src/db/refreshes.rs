@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use super::DbPool;
+
+/// Log a refresh of `asset_id` that wrote `points` prices from `source`
+pub async fn record_refresh(
+    pool: &DbPool,
+    asset_id: &str,
+    source: &str,
+    points: usize,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO price_refreshes (asset_id, source, points, refreshed_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(asset_id)
+    .bind(source)
+    .bind(points as i64)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Providers that have supplied prices for an asset, most recent first
+pub async fn fetch_sources(pool: &DbPool, asset_id: &str) -> anyhow::Result<Vec<String>> {
+    let rows = sqlx::query(
+        "SELECT source FROM price_refreshes WHERE asset_id = ? \
+         GROUP BY source ORDER BY MAX(refreshed_at) DESC",
+    )
+    .bind(asset_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| Ok(row.try_get("source")?))
+        .collect()
+}
+
+/// When an asset's prices were last refreshed, if ever
+pub async fn last_refresh(
+    pool: &DbPool,
+    asset_id: &str,
+) -> anyhow::Result<Option<DateTime<Utc>>> {
+    let row = sqlx::query(
+        "SELECT MAX(refreshed_at) AS refreshed_at FROM price_refreshes WHERE asset_id = ?",
+    )
+    .bind(asset_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.try_get("refreshed_at")?)
+}
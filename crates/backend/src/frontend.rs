@@ -0,0 +1,151 @@
+//! Serving the Trunk-built frontend, so a single process runs the whole app
+
+use std::path::{Path, PathBuf};
+
+use axum::{
+    extract::Request,
+    handler::HandlerWithoutStateExt,
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use tower::ServiceExt;
+use tower_http::services::{ServeDir, ServeFile};
+
+/// Trunk names built assets `<name>-<hash>`, with a 16 digit hex hash
+const FINGERPRINT_LEN: usize = 16;
+
+/// `Cache-Control` for a response to `path`. Fingerprinted assets never
+/// change, so browsers may keep them forever; everything else, and above
+/// all `index.html`, which names the current assets, is revalidated.
+fn cache_control(path: &str) -> &'static str {
+    if is_fingerprinted(path) {
+        "public, max-age=31536000, immutable"
+    } else if is_asset_path(path) && !path.ends_with(".html") {
+        "public, max-age=3600"
+    } else {
+        "no-cache"
+    }
+}
+
+/// Whether the file name in `path` carries a Trunk content hash, as in
+/// `frontend-0123456789abcdef_bg.wasm` or `style-0123456789abcdef.css`
+fn is_fingerprinted(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let stem = name.split('.').next().unwrap_or_default();
+    stem.rsplit_once('-').is_some_and(|(_, suffix)| {
+        let hash = suffix.split('_').next().unwrap_or_default();
+        hash.len() == FINGERPRINT_LEN && hash.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// Extensions of the files a build contains. Route segments may contain
+/// dots too, as in `/assets/BRK.B`, so only these mark a file.
+const STATIC_EXTENSIONS: &[&str] = &[
+    "html",
+    "js",
+    "mjs",
+    "wasm",
+    "css",
+    "map",
+    "json",
+    "txt",
+    "webmanifest",
+    "ico",
+    "png",
+    "svg",
+    "jpg",
+    "webp",
+    "woff",
+    "woff2",
+    "ttf",
+];
+
+/// Whether `path` names a file rather than a client-side route, so a
+/// missing asset is a 404 instead of the app
+fn is_asset_path(path: &str) -> bool {
+    path.rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .is_some_and(|(_, extension)| {
+            STATIC_EXTENSIONS
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+        })
+}
+
+async fn set_cache_control(request: Request, next: Next) -> Response {
+    let policy = cache_control(request.uri().path());
+    let mut response = next.run(request).await;
+    if response.status().is_success() {
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static(policy));
+    }
+    response
+}
+
+/// Files from `dir`, falling back to its `index.html` for client-side
+/// routes so deep links like `/assets/SPY` load the app
+pub fn router(dir: &Path) -> Router {
+    let index = dir.join("index.html");
+    let spa_fallback = move |request: Request| {
+        let index: PathBuf = index.clone();
+        async move {
+            if is_asset_path(request.uri().path()) {
+                return StatusCode::NOT_FOUND.into_response();
+            }
+            match ServeFile::new(index).oneshot(request).await {
+                Ok(response) => response.into_response(),
+                Err(e) => match e {},
+            }
+        }
+    };
+    let files = ServeDir::new(dir)
+        .precompressed_br()
+        .precompressed_gzip()
+        .fallback(spa_fallback.into_service());
+
+    Router::new()
+        .fallback_service(files)
+        .layer(middleware::from_fn(set_cache_control))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprinted_assets() {
+        assert!(is_fingerprinted("/frontend-0123456789abcdef_bg.wasm"));
+        assert!(is_fingerprinted("/frontend-0123456789abcdef.js"));
+        assert!(is_fingerprinted("/style-a1b2c3d4e5f6a7b8.css"));
+        assert!(!is_fingerprinted("/style.css"));
+        assert!(!is_fingerprinted("/date-range.js"));
+        assert!(!is_fingerprinted("/assets/SPY"));
+    }
+
+    #[test]
+    fn test_cache_control() {
+        assert_eq!(
+            cache_control("/frontend-0123456789abcdef_bg.wasm"),
+            "public, max-age=31536000, immutable"
+        );
+        assert_eq!(cache_control("/favicon.ico"), "public, max-age=3600");
+        assert_eq!(cache_control("/index.html"), "no-cache");
+        assert_eq!(cache_control("/"), "no-cache");
+        assert_eq!(cache_control("/compare"), "no-cache");
+        assert_eq!(cache_control("/assets/BRK.B"), "no-cache");
+    }
+
+    #[test]
+    fn test_asset_paths() {
+        assert!(is_asset_path("/frontend-0123456789abcdef_bg.wasm"));
+        assert!(is_asset_path("/static/style.css"));
+        assert!(is_asset_path("/favicon.ICO"));
+        assert!(!is_asset_path("/assets/BRK.B"));
+        assert!(!is_asset_path("/portfolios/import"));
+        assert!(!is_asset_path("/"));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod clients;
+pub mod db;
+pub mod frontend;
+pub mod routes;
+pub mod services;
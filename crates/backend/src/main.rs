@@ -1,4 +1,11 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use axum::{routing::get, Router};
+use backend::routes::AppState;
+use backend::services::price_feed::PriceFeed;
+use backend::{db, frontend, routes};
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
 
 #[tokio::main]
@@ -9,11 +16,32 @@ async fn main() {
     // Initialize tracing
     tracing_subscriber::fmt::init();
 
+    // Connect to the database and run migrations
+    let pool = db::init_db().await.unwrap();
+
     // Build the router
     let app = Router::new()
-        .route("/", get(root))
         .route("/health", get(health))
-        .layer(CorsLayer::permissive());
+        .nest("/api", routes::api_routes());
+
+    // With FRONTEND_DIR pointing at the Trunk build, the backend serves the
+    // app itself; otherwise the frontend runs separately and needs CORS
+    let app = match std::env::var("FRONTEND_DIR").map(PathBuf::from) {
+        Ok(dir) if dir.join("index.html").is_file() => {
+            tracing::info!("Serving frontend from {}", dir.display());
+            app.fallback_service(frontend::router(&dir))
+        }
+        Ok(dir) => {
+            tracing::warn!("No index.html in {}, not serving the frontend", dir.display());
+            app.route("/", get(root)).layer(CorsLayer::permissive())
+        }
+        Err(_) => app.route("/", get(root)).layer(CorsLayer::permissive()),
+    };
+    let state = AppState {
+        pool,
+        price_feed: Arc::new(PriceFeed::new()),
+    };
+    let app = app.layer(CompressionLayer::new()).with_state(state);
 
     let port = std::env::var("SERVER_PORT").unwrap_or_else(|_| "3000".to_string());
     let addr = format!("0.0.0.0:{}", port);
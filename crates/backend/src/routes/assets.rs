@@ -0,0 +1,101 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use shared::{
+    Asset, AssetDetailResponse, AssetHistoryQuery, AssetHistoryResponse, DataCoverage,
+    DownsampleMethod,
+};
+
+use super::{api_error, internal_error, ApiError};
+use crate::db::{self, DbPool};
+use crate::services::{metrics_service, price_service};
+
+async fn find_asset(pool: &DbPool, asset_id: &str) -> Result<Asset, ApiError> {
+    db::assets::fetch_assets(pool)
+        .await
+        .map_err(internal_error)?
+        .into_iter()
+        .find(|asset| asset.id == asset_id)
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Unknown asset", asset_id))
+}
+
+pub async fn get_asset(
+    State(pool): State<DbPool>,
+    Path(asset_id): Path<String>,
+) -> Result<Json<AssetDetailResponse>, ApiError> {
+    let asset = find_asset(&pool, &asset_id).await?;
+
+    let prices = match db::prices::price_range(&pool, &asset_id)
+        .await
+        .map_err(internal_error)?
+    {
+        Some((first, last)) => db::prices::fetch_prices(&pool, &asset_id, first, last)
+            .await
+            .map_err(internal_error)?,
+        None => vec![],
+    };
+    let coverage = prices
+        .first()
+        .zip(prices.last())
+        .map(|(first, last)| DataCoverage {
+            asset_id: asset_id.clone(),
+            first_date: first.timestamp,
+            last_date: last.timestamp,
+        });
+    let providers = db::refreshes::fetch_sources(&pool, &asset_id)
+        .await
+        .map_err(internal_error)?;
+    let last_refresh = db::refreshes::last_refresh(&pool, &asset_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(AssetDetailResponse {
+        coverage,
+        point_count: prices.len(),
+        gap_count: price_service::count_gaps(&prices, asset.asset_type.max_price_spacing_days()),
+        providers,
+        last_refresh,
+        windows: metrics_service::window_metrics(&asset_id, &prices),
+        asset,
+    }))
+}
+
+pub async fn get_asset_history(
+    State(pool): State<DbPool>,
+    Path(asset_id): Path<String>,
+    Query(query): Query<AssetHistoryQuery>,
+) -> Result<Json<AssetHistoryResponse>, ApiError> {
+    find_asset(&pool, &asset_id).await?;
+    if query.max_points.is_some_and(|n| n < 3) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid history request",
+            "max_points must be at least 3",
+        ));
+    }
+
+    let Some((first, last)) = db::prices::price_range(&pool, &asset_id)
+        .await
+        .map_err(internal_error)?
+    else {
+        return Ok(Json(AssetHistoryResponse {
+            asset_id,
+            prices: vec![],
+        }));
+    };
+    let mut prices = db::prices::fetch_prices(
+        &pool,
+        &asset_id,
+        query.start_date.unwrap_or(first),
+        query.end_date.unwrap_or(last),
+    )
+    .await
+    .map_err(internal_error)?;
+    if let Some(max_points) = query.max_points {
+        prices = price_service::downsample_prices(prices, max_points, DownsampleMethod::Lttb);
+    }
+
+    Ok(Json(AssetHistoryResponse { asset_id, prices }))
+}
@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use chrono::Utc;
+use shared::ChartQuery;
+
+use super::{api_error, internal_error, run_comparison, ApiError};
+use crate::db::DbPool;
+use crate::services::chart_service;
+
+/// Largest width or height, in pixels, a rendered chart may have; a PNG
+/// this size takes 16 MB to rasterize
+const MAX_CHART_SIZE: u32 = 2000;
+
+/// Shared by link previews and docs; short enough that refreshed prices
+/// show up soon after
+const CACHE_CONTROL: &str = "public, max-age=300";
+
+pub async fn chart_svg(
+    State(pool): State<DbPool>,
+    Query(query): Query<ChartQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let svg = render_svg(&pool, &query).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/svg+xml"),
+            (header::CACHE_CONTROL, CACHE_CONTROL),
+        ],
+        svg,
+    ))
+}
+
+pub async fn chart_png(
+    State(pool): State<DbPool>,
+    Query(query): Query<ChartQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let svg = render_svg(&pool, &query).await?;
+    // Rasterizing is CPU-bound; keep it off the async workers
+    let png = tokio::task::spawn_blocking(move || chart_service::svg_to_png(&svg))
+        .await
+        .map_err(|e| internal_error(e.into()))?
+        .map_err(|e| internal_error(e.into()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            (header::CACHE_CONTROL, CACHE_CONTROL),
+        ],
+        png,
+    ))
+}
+
+async fn render_svg(pool: &DbPool, query: &ChartQuery) -> Result<String, ApiError> {
+    let size_range = 100..=MAX_CHART_SIZE;
+    if !size_range.contains(&query.width) || !size_range.contains(&query.height) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid chart request",
+            format!(
+                "width and height must be between 100 and {}",
+                MAX_CHART_SIZE
+            ),
+        ));
+    }
+
+    let request = query.comparison_request(Utc::now());
+    let response = run_comparison(pool, &request).await?;
+
+    Ok(chart_service::comparison_chart(&response, query).to_svg())
+}
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use shared::{CorporateActionKind, CorporateActionsResponse, UpsertCorporateActionsRequest};
+
+use super::{api_error, internal_error, ApiError};
+use crate::db::{self, DbPool};
+
+pub async fn get_corporate_actions(
+    State(pool): State<DbPool>,
+    Path(asset_id): Path<String>,
+) -> Result<Json<CorporateActionsResponse>, ApiError> {
+    let actions = db::corporate_actions::fetch_corporate_actions(&pool, &asset_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(CorporateActionsResponse { actions }))
+}
+
+pub async fn upsert_corporate_actions(
+    State(pool): State<DbPool>,
+    Path(asset_id): Path<String>,
+    Json(request): Json<UpsertCorporateActionsRequest>,
+) -> Result<Json<CorporateActionsResponse>, ApiError> {
+    for action in &request.actions {
+        if action.asset_id != asset_id {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                "Invalid corporate action",
+                format!(
+                    "action for {} submitted under {}",
+                    action.asset_id, asset_id
+                ),
+            ));
+        }
+        let valid = match action.kind {
+            CorporateActionKind::Split { ratio } => ratio > 0.0,
+            CorporateActionKind::CashDividend { amount } => amount > 0.0,
+        };
+        if !valid {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                "Invalid corporate action",
+                format!(
+                    "{} on {} must be positive",
+                    action.kind.action_type(),
+                    action.ex_date
+                ),
+            ));
+        }
+    }
+
+    db::corporate_actions::upsert_corporate_actions(&pool, &request.actions)
+        .await
+        .map_err(internal_error)?;
+
+    get_corporate_actions(State(pool), Path(asset_id)).await
+}
@@ -0,0 +1,34 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use shared::{CoverageQuery, CoverageResponse, DataCoverage};
+
+use super::{internal_error, ApiError};
+use crate::db::{self, DbPool};
+
+pub async fn get_coverage(
+    State(pool): State<DbPool>,
+    Query(query): Query<CoverageQuery>,
+) -> Result<Json<CoverageResponse>, ApiError> {
+    let mut coverage = Vec::new();
+    for asset_id in query
+        .assets
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+    {
+        let range = db::prices::price_range(&pool, asset_id)
+            .await
+            .map_err(internal_error)?;
+        if let Some((first_date, last_date)) = range {
+            coverage.push(DataCoverage {
+                asset_id: asset_id.to_string(),
+                first_date,
+                last_date,
+            });
+        }
+    }
+
+    Ok(Json(CoverageResponse { coverage }))
+}
@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use shared::{CreateEventRequest, EventsQuery, EventsResponse, MarketEvent};
+
+use super::{api_error, internal_error, ApiError};
+use crate::db::{self, DbPool};
+
+pub async fn list_events(
+    State(pool): State<DbPool>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Json<EventsResponse>, ApiError> {
+    let events = db::events::fetch_events(&pool, query.start_date, query.end_date, query.category)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(EventsResponse { events }))
+}
+
+pub async fn create_event(
+    State(pool): State<DbPool>,
+    Json(mut request): Json<CreateEventRequest>,
+) -> Result<(StatusCode, Json<MarketEvent>), ApiError> {
+    request.title = request.title.trim().to_string();
+    if request.title.is_empty() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid event",
+            "title must not be empty",
+        ));
+    }
+    if request.end_date.is_some_and(|end| end < request.start_date) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid event",
+            "end_date must not be before start_date",
+        ));
+    }
+    if let Some(asset_id) = &request.asset_id {
+        let assets = db::assets::fetch_assets(&pool)
+            .await
+            .map_err(internal_error)?;
+        if !assets.iter().any(|a| &a.id == asset_id) {
+            return Err(api_error(StatusCode::NOT_FOUND, "Unknown asset", asset_id));
+        }
+    }
+
+    let event = db::events::insert_event(&pool, &request)
+        .await
+        .map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+pub async fn delete_event(
+    State(pool): State<DbPool>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let deleted = db::events::delete_event(&pool, id)
+        .await
+        .map_err(internal_error)?;
+    if !deleted {
+        return Err(api_error(StatusCode::NOT_FOUND, "Unknown event", id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,67 +1,418 @@
+use std::sync::Arc;
+
 use axum::{
-    extract::{Query, State},
+    extract::{FromRef, OriginalUri, State},
     http::StatusCode,
-    response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
+use chrono::{DateTime, Duration, Utc};
 use shared::{
-    ComparisonRequest, ComparisonResponse, GetAssetsResponse, RefreshDataRequest,
-    RefreshDataResponse, Asset, ErrorResponse,
+    AssetSeries, Bar, BarInterval, ComparisonRequest, ComparisonResponse, ErrorResponse,
+    GetAssetsResponse, PriceField, PricePoint, RefreshDataRequest, RefreshDataResponse,
+    ReturnBasis, VolatilityEstimator, PORTFOLIO_ID_PREFIX,
 };
 
-use crate::db::DbPool;
+use crate::db::{self, DbPool};
+use crate::services::price_feed::PriceFeed;
+use crate::services::{ingestion_service, metrics_service, portfolio_service, price_service};
+
+mod assets;
+mod chart;
+mod corporate_actions;
+mod coverage;
+mod events;
+mod optimizer;
+mod portfolios;
+mod prices;
+mod risk;
+
+pub type ApiError = (StatusCode, Json<ErrorResponse>);
+
+/// State shared by all handlers. Those needing only part of it extract
+/// that, e.g. `State<DbPool>`.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: DbPool,
+    pub price_feed: Arc<PriceFeed>,
+}
+
+impl FromRef<AppState> for DbPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
 
-pub fn api_routes() -> Router<DbPool> {
+impl FromRef<AppState> for Arc<PriceFeed> {
+    fn from_ref(state: &AppState) -> Self {
+        state.price_feed.clone()
+    }
+}
+
+pub fn api_routes() -> Router<AppState> {
     Router::new()
         .route("/assets", get(get_assets))
+        .route("/assets/{asset_id}", get(assets::get_asset))
+        .route("/assets/{asset_id}/history", get(assets::get_asset_history))
+        .route(
+            "/assets/{asset_id}/corporate-actions",
+            get(corporate_actions::get_corporate_actions)
+                .post(corporate_actions::upsert_corporate_actions),
+        )
         .route("/comparison", post(get_comparison))
+        .route("/coverage", get(coverage::get_coverage))
+        .route("/chart.svg", get(chart::chart_svg))
+        .route("/chart.png", get(chart::chart_png))
+        .route("/events", get(events::list_events).post(events::create_event))
+        .route("/events/{event_id}", delete(events::delete_event))
         .route("/refresh", post(refresh_data))
+        .route("/optimize", post(optimizer::optimize_portfolio))
+        .route(
+            "/portfolios",
+            get(portfolios::list_portfolios).post(portfolios::create_portfolio),
+        )
+        .route("/portfolios/backtest", post(portfolios::backtest_portfolio))
+        .route("/portfolios/import", post(portfolios::import_portfolio))
+        .route("/portfolios/{portfolio_id}", get(portfolios::get_portfolio))
+        .route("/prices/stream", get(prices::stream_prices))
+        .route("/risk", post(risk::get_risk_report))
+        .fallback(unknown_endpoint)
+}
+
+fn api_error(status: StatusCode, error: &str, details: impl ToString) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: error.to_string(),
+            details: Some(details.to_string()),
+        }),
+    )
+}
+
+fn internal_error(err: anyhow::Error) -> ApiError {
+    tracing::error!("{:#}", err);
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error", err)
 }
 
+/// Keeps unknown API paths from falling through to the frontend
+async fn unknown_endpoint(OriginalUri(uri): OriginalUri) -> ApiError {
+    api_error(StatusCode::NOT_FOUND, "Unknown endpoint", uri.path())
+}
+
+/// Tracked assets, the same ones the rest of the API resolves ids against
 async fn get_assets(
-    State(_pool): State<DbPool>,
+    State(pool): State<DbPool>,
 ) -> Result<Json<GetAssetsResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // For now, return the default assets
-    // Later, we'll fetch from database
-    let assets = Asset::all_default();
+    let assets = db::assets::fetch_assets(&pool)
+        .await
+        .map_err(internal_error)?;
 
     Ok(Json(GetAssetsResponse { assets }))
 }
 
 async fn get_comparison(
-    State(_pool): State<DbPool>,
-    Json(_request): Json<ComparisonRequest>,
+    State(pool): State<DbPool>,
+    Json(request): Json<ComparisonRequest>,
 ) -> Result<Json<ComparisonResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // TODO: Implement comparison logic
-    // 1. Fetch price data from database
-    // 2. Normalize to initial_amount
-    // 3. Calculate metrics
-    // 4. Return time series + metrics
-
-    Err((
-        StatusCode::NOT_IMPLEMENTED,
-        Json(ErrorResponse {
-            error: "Not implemented yet".to_string(),
-            details: Some("Comparison endpoint is under development".to_string()),
-        }),
-    ))
+    run_comparison(&pool, &request).await.map(Json)
+}
+
+/// Normalized series, metrics and events for a comparison request; shared
+/// by the JSON endpoint and the rendered charts
+async fn run_comparison(
+    pool: &DbPool,
+    request: &ComparisonRequest,
+) -> Result<ComparisonResponse, ApiError> {
+    if request.asset_ids.is_empty() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid comparison request",
+            "at least one asset is required",
+        ));
+    }
+    if request.start_date >= request.end_date {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid comparison request",
+            "start_date must be before end_date",
+        ));
+    }
+    if !(request.initial_amount.is_finite() && request.initial_amount > 0.0) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid comparison request",
+            "initial_amount must be a positive number",
+        ));
+    }
+    if request.max_points.is_some_and(|n| n < 3) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid comparison request",
+            "max_points must be at least 3",
+        ));
+    }
+    // Downsampled points shift as the series grows, so a later page of them
+    // wouldn't extend an earlier response
+    if request.since.is_some() && request.max_points.is_some() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid comparison request",
+            "since can't be combined with max_points",
+        ));
+    }
+
+    let mut series = Vec::with_capacity(request.asset_ids.len());
+    let mut metrics = Vec::with_capacity(request.asset_ids.len());
+    // Assets whose events concern the comparison, holdings included
+    let mut event_assets = request.asset_ids.clone();
+
+    let range_estimator = request.volatility_estimator != VolatilityEstimator::CloseToClose;
+    // Bars carry no intraday path of the unit to re-price them with
+    if range_estimator && request.unit_of_account.is_some() {
+        return Err(api_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Unsupported volatility estimator",
+            format!(
+                "{:?} volatility can't be measured in a unit of account",
+                request.volatility_estimator
+            ),
+        ));
+    }
+
+    let unit_prices = match &request.unit_of_account {
+        Some(unit) => {
+            let prices = db::prices::fetch_prices(pool, unit, request.start_date, request.end_date)
+                .await
+                .map_err(internal_error)?;
+            if prices.is_empty() {
+                return Err(api_error(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "Unknown unit of account",
+                    format!("no prices for {} in the requested range", unit),
+                ));
+            }
+            Some(prices)
+        }
+        None => None,
+    };
+
+    for asset_id in &request.asset_ids {
+        // 1. Rebuild each asset as a return index, and portfolios from
+        //    the indexes of their holdings
+        let portfolio_id = asset_id.strip_prefix(PORTFOLIO_ID_PREFIX);
+        let (label, mut index, bars) = match portfolio_id {
+            Some(portfolio_id) => {
+                let portfolio = db::portfolios::fetch_portfolio(pool, portfolio_id)
+                    .await
+                    .map_err(internal_error)?
+                    .ok_or_else(|| {
+                        api_error(StatusCode::NOT_FOUND, "Unknown portfolio", portfolio_id)
+                    })?;
+                let mut histories = Vec::with_capacity(portfolio.assets.len());
+                for holding in &portfolio.assets {
+                    let (index, _) = asset_index(pool, &holding.asset_id, request).await?;
+                    histories.push((holding.asset_id.clone(), index));
+                    event_assets.push(holding.asset_id.clone());
+                }
+                let weights: Vec<f64> = portfolio.assets.iter().map(|a| a.weight).collect();
+                let index = portfolio_service::backtest(
+                    asset_id,
+                    &histories,
+                    &weights,
+                    portfolio.rebalance,
+                    1.0,
+                );
+                (Some(portfolio.name), index, vec![])
+            }
+            None => {
+                let (index, bars) = asset_index(pool, asset_id, request).await?;
+                (None, index, bars)
+            }
+        };
+
+        // 2. Price in the unit of account
+        if let Some(unit_prices) = &unit_prices {
+            index = price_service::denominate(&index, unit_prices);
+        }
+
+        // 3. Normalize to initial_amount and calculate metrics
+        let mut points = price_service::normalize_prices(&index, request.initial_amount);
+        if let Some(max_points) = request.max_points {
+            points = price_service::downsample(points, max_points, request.downsample);
+        }
+        if let Some(since) = request.since {
+            points.retain(|p| p.timestamp > since);
+        }
+        series.push(AssetSeries {
+            asset_id: asset_id.clone(),
+            label,
+            points,
+        });
+        let Some(mut asset_metrics) = metrics_service::calculate_metrics(asset_id, &index) else {
+            continue;
+        };
+        // Portfolios have no bars of their own and keep close-to-close volatility
+        if range_estimator && portfolio_id.is_none() {
+            let volatility = metrics_service::range_volatility(&bars, request.volatility_estimator)
+                .ok_or_else(|| {
+                    api_error(
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        "Insufficient bar data",
+                        format!(
+                            "{:?} volatility needs daily OHLC bars for {}",
+                            request.volatility_estimator, asset_id
+                        ),
+                    )
+                })?;
+            // Annualized at the bars' own sampling rate, like the ratios
+            let per_year = match (bars.first(), bars.last()) {
+                (Some(first), Some(last)) => metrics_service::periods_per_year(
+                    bars.len() - 1,
+                    first.timestamp,
+                    last.timestamp,
+                ),
+                _ => 0.0,
+            };
+            asset_metrics.volatility = volatility * per_year.sqrt() * 100.0;
+        }
+        metrics.push(asset_metrics);
+    }
+
+    // Market-wide events, plus those tied to a compared asset or holding
+    let events = db::events::fetch_events(
+        pool,
+        Some(request.start_date),
+        Some(request.end_date),
+        None,
+    )
+    .await
+    .map_err(internal_error)?
+    .into_iter()
+    .filter(|event| {
+        event
+            .asset_id
+            .as_ref()
+            .is_none_or(|id| event_assets.contains(id))
+    })
+    .collect();
+
+    Ok(ComparisonResponse {
+        series,
+        metrics,
+        events,
+    })
+}
+
+/// Return index of one asset over the requested range, plus its daily bars,
+/// adjusted for corporate actions, when the request needs them
+async fn asset_index(
+    pool: &DbPool,
+    asset_id: &str,
+    request: &ComparisonRequest,
+) -> Result<(Vec<PricePoint>, Vec<Bar>), ApiError> {
+    let range_estimator = request.volatility_estimator != VolatilityEstimator::CloseToClose;
+    // Adjusted closes include dividends, so a price return follows the
+    // close instead, with splits neutralized like any raw close
+    let price_field = match request.price_field {
+        Some(PriceField::AdjustedClose) if request.return_basis == ReturnBasis::PriceReturn => {
+            Some(PriceField::Close)
+        }
+        field => field,
+    };
+    let bars = if price_field.is_some() || range_estimator {
+        db::bars::fetch_bars(
+            pool,
+            asset_id,
+            BarInterval::Day,
+            request.start_date,
+            request.end_date,
+        )
+        .await
+        .map_err(internal_error)?
+    } else {
+        vec![]
+    };
+    let prices = match price_field {
+        Some(field) => bars.iter().map(|bar| bar.to_price_point(field)).collect(),
+        None => db::prices::fetch_prices(pool, asset_id, request.start_date, request.end_date)
+            .await
+            .map_err(internal_error)?,
+    };
+    let actions = db::corporate_actions::fetch_corporate_actions(pool, asset_id)
+        .await
+        .map_err(internal_error)?;
+    // Adjusted closes already account for splits and dividends, except
+    // where a bar has none and its raw close stands in
+    let index = match price_field {
+        Some(PriceField::AdjustedClose) => {
+            let unadjusted = price_service::unadjusted_actions(&bars, &actions);
+            price_service::return_index(&prices, &unadjusted, request.return_basis)
+        }
+        _ => price_service::return_index(&prices, &actions, request.return_basis),
+    };
+    let bars = price_service::adjust_bars(&bars, &actions, request.return_basis);
+    Ok((index, bars))
+}
+
+/// Return index of one asset over a range, priced as in a default
+/// comparison: stored prices with corporate actions neutralized
+async fn default_index(
+    pool: &DbPool,
+    asset_id: &str,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Result<Vec<PricePoint>, ApiError> {
+    let request = ComparisonRequest {
+        asset_ids: vec![asset_id.to_string()],
+        start_date,
+        end_date,
+        initial_amount: 1.0,
+        return_basis: Default::default(),
+        price_field: None,
+        volatility_estimator: Default::default(),
+        max_points: None,
+        downsample: Default::default(),
+        unit_of_account: None,
+        since: None,
+    };
+    let (index, _) = asset_index(pool, asset_id, &request).await?;
+    Ok(index)
 }
 
 async fn refresh_data(
-    State(_pool): State<DbPool>,
-    Json(_request): Json<RefreshDataRequest>,
+    State(pool): State<DbPool>,
+    State(price_feed): State<Arc<PriceFeed>>,
+    Json(request): Json<RefreshDataRequest>,
 ) -> Result<Json<RefreshDataResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // TODO: Implement data refresh
-    // 1. Fetch latest prices from external APIs
-    // 2. Store in database
-    // 3. Return success response
+    let assets = db::assets::fetch_assets(&pool)
+        .await
+        .map_err(internal_error)?;
 
-    Err((
-        StatusCode::NOT_IMPLEMENTED,
-        Json(ErrorResponse {
-            error: "Not implemented yet".to_string(),
-            details: Some("Refresh endpoint is under development".to_string()),
-        }),
-    ))
+    let end_date = Utc::now();
+    let mut updated_assets = Vec::new();
+    let mut points_added = 0;
+
+    for asset_id in &request.asset_ids {
+        let asset = assets.iter().find(|a| &a.id == asset_id).ok_or_else(|| {
+            api_error(StatusCode::NOT_FOUND, "Unknown asset", asset_id)
+        })?;
+
+        // 1. Resume from the last stored price, or backfill ten years
+        let start_date = db::prices::latest_timestamp(&pool, asset_id)
+            .await
+            .map_err(internal_error)?
+            .unwrap_or_else(|| end_date - Duration::days(365 * 10));
+
+        // 2. Fetch from external APIs and store in database
+        points_added +=
+            ingestion_service::refresh_asset(&pool, &price_feed, asset, start_date, end_date)
+                .await
+                .map_err(|e| api_error(StatusCode::BAD_GATEWAY, "Failed to refresh data", e))?;
+        updated_assets.push(asset_id.clone());
+    }
+
+    Ok(Json(RefreshDataResponse {
+        updated_assets,
+        points_added,
+    }))
 }
@@ -0,0 +1,178 @@
+use axum::{extract::State, http::StatusCode, Json};
+use shared::{
+    OptimizationObjective, OptimizationRequest, OptimizationResponse, OptimizedPortfolio,
+    PortfolioAsset,
+};
+
+use super::portfolios::validate_holdings;
+use super::{api_error, default_index, internal_error, ApiError};
+use crate::db::{self, DbPool};
+use crate::services::optimizer_service::{self, OptimizerError, ReturnStatistics, WeightBounds};
+
+/// Frontier points solved per request at most
+const MAX_FRONTIER_POINTS: usize = 200;
+
+pub async fn optimize_portfolio(
+    State(pool): State<DbPool>,
+    Json(request): Json<OptimizationRequest>,
+) -> Result<Json<OptimizationResponse>, ApiError> {
+    validate_request(&request)?;
+    let asset_ids = if request.asset_ids.is_empty() {
+        db::assets::fetch_assets(&pool)
+            .await
+            .map_err(internal_error)?
+            .into_iter()
+            .map(|asset| asset.id)
+            .collect()
+    } else {
+        request.asset_ids.clone()
+    };
+
+    // Returns come from the same split-neutral index as comparisons
+    let mut histories = Vec::with_capacity(asset_ids.len());
+    for asset_id in asset_ids {
+        let index = default_index(&pool, &asset_id, request.start_date, request.end_date).await?;
+        histories.push((asset_id, index));
+    }
+
+    let stats = optimizer_service::estimate_statistics(&histories).map_err(optimizer_error)?;
+    let bounds = weight_bounds(&stats, &request)?;
+    let risk_free_rate = request.risk_free_rate_pct / 100.0;
+
+    let weights = match request.objective {
+        OptimizationObjective::MinVariance => optimizer_service::min_variance(&stats, &bounds),
+        OptimizationObjective::MaxSharpe => {
+            optimizer_service::max_sharpe(&stats, &bounds, risk_free_rate)
+        }
+        OptimizationObjective::TargetReturn { target_return_pct } => {
+            optimizer_service::target_return(&stats, &bounds, target_return_pct / 100.0)
+        }
+    }
+    .map_err(optimizer_error)?;
+
+    let frontier = optimizer_service::efficient_frontier(&stats, &bounds, request.frontier_points)
+        .map_err(optimizer_error)?
+        .iter()
+        .map(|w| describe(&stats, w, risk_free_rate))
+        .collect();
+
+    let portfolio = describe(&stats, &weights, risk_free_rate);
+    let saved_portfolio = match &request.save_as {
+        Some(name) => {
+            // Bounds may allow weights a saved portfolio can't hold
+            validate_holdings(&pool, &portfolio.weights).await?;
+            Some(
+                db::portfolios::create_portfolio(
+                    &pool,
+                    name.trim(),
+                    &portfolio.weights,
+                    Default::default(),
+                )
+                .await
+                .map_err(internal_error)?,
+            )
+        }
+        None => None,
+    };
+
+    Ok(Json(OptimizationResponse {
+        portfolio,
+        frontier,
+        saved_portfolio,
+    }))
+}
+
+fn validate_request(request: &OptimizationRequest) -> Result<(), ApiError> {
+    let invalid = |details: String| {
+        api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid optimization request",
+            details,
+        )
+    };
+    for (i, asset_id) in request.asset_ids.iter().enumerate() {
+        if request.asset_ids[..i].contains(asset_id) {
+            return Err(invalid(format!("{} is listed twice", asset_id)));
+        }
+    }
+    if request
+        .save_as
+        .as_ref()
+        .is_some_and(|name| name.trim().is_empty())
+    {
+        return Err(invalid("save_as must not be empty".to_string()));
+    }
+    if request.frontier_points > MAX_FRONTIER_POINTS {
+        return Err(invalid(format!(
+            "frontier_points must be at most {}",
+            MAX_FRONTIER_POINTS
+        )));
+    }
+    if !request.risk_free_rate_pct.is_finite() {
+        return Err(invalid("risk_free_rate_pct must be a number".to_string()));
+    }
+    if let OptimizationObjective::TargetReturn { target_return_pct } = request.objective {
+        if !target_return_pct.is_finite() {
+            return Err(invalid("target_return_pct must be a number".to_string()));
+        }
+    }
+    if let Some(bound) = request
+        .bounds
+        .iter()
+        .find(|b| !b.min.is_finite() || !b.max.is_finite() || b.min > b.max)
+    {
+        return Err(invalid(format!(
+            "bounds for {} must be numbers with min <= max, not {}..{}",
+            bound.asset_id, bound.min, bound.max
+        )));
+    }
+    Ok(())
+}
+
+fn weight_bounds(
+    stats: &ReturnStatistics,
+    request: &OptimizationRequest,
+) -> Result<WeightBounds, ApiError> {
+    let mut bounds = WeightBounds::long_only(stats.asset_ids.len());
+    for bound in &request.bounds {
+        let index = stats
+            .asset_ids
+            .iter()
+            .position(|id| id == &bound.asset_id)
+            .ok_or_else(|| {
+                api_error(
+                    StatusCode::BAD_REQUEST,
+                    "Invalid weight bounds",
+                    format!("{} is not part of the optimization", bound.asset_id),
+                )
+            })?;
+        bounds.lower[index] = bound.min;
+        bounds.upper[index] = bound.max;
+    }
+    Ok(bounds)
+}
+
+fn describe(stats: &ReturnStatistics, weights: &[f64], risk_free_rate: f64) -> OptimizedPortfolio {
+    OptimizedPortfolio {
+        weights: stats
+            .asset_ids
+            .iter()
+            .zip(weights)
+            .map(|(asset_id, weight)| PortfolioAsset {
+                asset_id: asset_id.clone(),
+                weight: *weight,
+            })
+            .collect(),
+        expected_return_pct: optimizer_service::portfolio_return(stats, weights) * 100.0,
+        volatility_pct: optimizer_service::portfolio_volatility(stats, weights) * 100.0,
+        sharpe_ratio: optimizer_service::sharpe_ratio(stats, weights, risk_free_rate),
+    }
+}
+
+fn optimizer_error(err: OptimizerError) -> ApiError {
+    api_error(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "Optimization failed",
+        err.to_string(),
+    )
+}
@@ -0,0 +1,251 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::collections::HashMap;
+
+use shared::{
+    AssetSeries, BacktestRequest, BacktestResponse, CreatePortfolioRequest, ImportPortfolioRequest,
+    ImportPreview, Portfolio, PortfolioAsset, PortfoliosResponse,
+};
+
+use super::{api_error, default_index, internal_error, ApiError};
+use crate::db::{self, DbPool};
+use crate::services::{import_service, metrics_service, portfolio_service, price_service};
+
+/// How far weights may sum from 1 before a portfolio is rejected
+pub(super) const WEIGHT_TOLERANCE: f64 = 1e-6;
+
+pub async fn list_portfolios(
+    State(pool): State<DbPool>,
+) -> Result<Json<PortfoliosResponse>, ApiError> {
+    let portfolios = db::portfolios::fetch_portfolios(&pool)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(PortfoliosResponse { portfolios }))
+}
+
+pub async fn get_portfolio(
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> Result<Json<Portfolio>, ApiError> {
+    db::portfolios::fetch_portfolio(&pool, &id)
+        .await
+        .map_err(internal_error)?
+        .map(Json)
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Unknown portfolio", id))
+}
+
+/// Holdings must be distinct known assets with non-negative weights
+/// summing to 1
+pub(super) async fn validate_holdings(
+    pool: &DbPool,
+    holdings: &[PortfolioAsset],
+) -> Result<(), ApiError> {
+    let invalid =
+        |details: String| api_error(StatusCode::BAD_REQUEST, "Invalid portfolio", details);
+    if holdings.is_empty() {
+        return Err(invalid("at least one asset is required".to_string()));
+    }
+    if let Some(h) = holdings
+        .iter()
+        .find(|h| !h.weight.is_finite() || h.weight < 0.0)
+    {
+        return Err(invalid(format!(
+            "invalid weight {} for {}",
+            h.weight, h.asset_id
+        )));
+    }
+    let total: f64 = holdings.iter().map(|h| h.weight).sum();
+    if (total - 1.0).abs() > WEIGHT_TOLERANCE {
+        return Err(invalid(format!("weights sum to {}, not 1", total)));
+    }
+    for (i, holding) in holdings.iter().enumerate() {
+        if holdings[..i].iter().any(|h| h.asset_id == holding.asset_id) {
+            return Err(invalid(format!("{} is listed twice", holding.asset_id)));
+        }
+    }
+
+    let assets = db::assets::fetch_assets(pool)
+        .await
+        .map_err(internal_error)?;
+    if let Some(h) = holdings
+        .iter()
+        .find(|h| !assets.iter().any(|a| a.id == h.asset_id))
+    {
+        return Err(api_error(
+            StatusCode::NOT_FOUND,
+            "Unknown asset",
+            &h.asset_id,
+        ));
+    }
+    Ok(())
+}
+
+pub async fn create_portfolio(
+    State(pool): State<DbPool>,
+    Json(request): Json<CreatePortfolioRequest>,
+) -> Result<(StatusCode, Json<Portfolio>), ApiError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid portfolio",
+            "name must not be empty",
+        ));
+    }
+    validate_holdings(&pool, &request.assets).await?;
+
+    let portfolio =
+        db::portfolios::create_portfolio(&pool, name, &request.assets, request.rebalance)
+            .await
+            .map_err(internal_error)?;
+
+    Ok((StatusCode::CREATED, Json(portfolio)))
+}
+
+/// Create a portfolio weighted by the positions in a broker CSV, or with
+/// `dry_run`, preview them along with unknown symbols and rejected rows
+pub async fn import_portfolio(
+    State(pool): State<DbPool>,
+    Json(request): Json<ImportPortfolioRequest>,
+) -> Result<(StatusCode, Json<ImportPreview>), ApiError> {
+    let assets = db::assets::fetch_assets(&pool)
+        .await
+        .map_err(internal_error)?;
+    let parsed = import_service::parse_csv(&request.csv, request.kind, &request.mapping, &assets)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "Unreadable CSV", e))?;
+
+    // Positions without a market value are valued at the latest price
+    let mut prices = HashMap::new();
+    for position in parsed.positions.iter().filter(|p| p.market_value.is_none()) {
+        if let Some(price) = db::prices::latest_price(&pool, &position.asset_id)
+            .await
+            .map_err(internal_error)?
+        {
+            prices.insert(position.asset_id.clone(), price);
+        }
+    }
+    let (holdings, unpriced) = import_service::weigh(&parsed.positions, &prices);
+    let mut rejected_rows = parsed.rejected_rows;
+    rejected_rows.extend(unpriced);
+    rejected_rows.sort_by_key(|r| r.line);
+
+    let mut preview = ImportPreview {
+        columns: parsed.columns,
+        mapping: parsed.mapping,
+        mapping_error: parsed.mapping_error,
+        holdings,
+        unknown_symbols: parsed.unknown_symbols,
+        rejected_rows,
+        portfolio: None,
+    };
+    if request.dry_run {
+        return Ok((StatusCode::OK, Json(preview)));
+    }
+
+    if let Some(e) = &preview.mapping_error {
+        return Err(api_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Invalid column mapping",
+            e,
+        ));
+    }
+    if preview.holdings.is_empty() {
+        return Err(api_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Nothing to import",
+            "no row holds a known asset that could be valued",
+        ));
+    }
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid portfolio",
+            "name must not be empty",
+        ));
+    }
+    let holdings: Vec<PortfolioAsset> = preview
+        .holdings
+        .iter()
+        .map(|h| PortfolioAsset {
+            asset_id: h.asset_id.clone(),
+            weight: h.weight,
+        })
+        .collect();
+    validate_holdings(&pool, &holdings).await?;
+
+    let portfolio = db::portfolios::create_portfolio(&pool, name, &holdings, request.rebalance)
+        .await
+        .map_err(internal_error)?;
+    preview.portfolio = Some(portfolio);
+
+    Ok((StatusCode::CREATED, Json(preview)))
+}
+
+pub async fn backtest_portfolio(
+    State(pool): State<DbPool>,
+    Json(request): Json<BacktestRequest>,
+) -> Result<Json<BacktestResponse>, ApiError> {
+    validate_holdings(&pool, &request.assets).await?;
+    if request.start_date >= request.end_date {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid backtest request",
+            "start_date must be before end_date",
+        ));
+    }
+    if !(request.initial_amount.is_finite() && request.initial_amount > 0.0) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid backtest request",
+            "initial_amount must be a positive number",
+        ));
+    }
+    if request.max_points.is_some_and(|n| n < 3) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid backtest request",
+            "max_points must be at least 3",
+        ));
+    }
+
+    // Holdings are priced the same way as in a comparison
+    let mut histories = Vec::with_capacity(request.assets.len());
+    for holding in &request.assets {
+        let index = default_index(
+            &pool,
+            &holding.asset_id,
+            request.start_date,
+            request.end_date,
+        )
+        .await?;
+        histories.push((holding.asset_id.clone(), index));
+    }
+
+    let weights: Vec<f64> = request.assets.iter().map(|a| a.weight).collect();
+    let values = portfolio_service::backtest(
+        "portfolio",
+        &histories,
+        &weights,
+        request.rebalance,
+        request.initial_amount,
+    );
+    let metrics = metrics_service::calculate_metrics("portfolio", &values);
+    let mut points = price_service::normalize_prices(&values, request.initial_amount);
+    if let Some(max_points) = request.max_points {
+        points = price_service::downsample(points, max_points, Default::default());
+    }
+
+    Ok(Json(BacktestResponse {
+        series: AssetSeries {
+            asset_id: "portfolio".to_string(),
+            label: None,
+            points,
+        },
+        metrics,
+    }))
+}
@@ -0,0 +1,77 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use shared::{PriceStreamQuery, PRICE_EVENT, RESET_EVENT};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::services::price_feed::{FeedEvent, PriceFeed, Resume};
+
+/// What a client is sent: a batch of prices, or word that it missed some
+enum Item {
+    Prices(FeedEvent),
+    Missed(Option<u64>),
+}
+
+/// Server-Sent Events with prices as ingestion stores them. Clients that
+/// reconnect with the last event id they saw get what they missed, or a
+/// reset event when that is no longer available.
+pub async fn stream_prices(
+    State(feed): State<Arc<PriceFeed>>,
+    headers: HeaderMap,
+    Query(query): Query<PriceStreamQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .or(query.last_event_id);
+    let asset_ids: Option<Vec<String>> = query.assets.map(|assets| {
+        assets
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .collect()
+    });
+
+    let (resume, receiver) = feed.subscribe(last_event_id.as_deref());
+    let initial = match resume {
+        Resume::Replay(events) => events.into_iter().map(Item::Prices).collect(),
+        Resume::Reset(latest) => vec![Item::Missed(latest)],
+    };
+    let live = BroadcastStream::new(receiver).map(|result| match result {
+        Ok(event) => Item::Prices(event),
+        // Too slow to keep up; the next event carries a fresh id
+        Err(BroadcastStreamRecvError::Lagged(_)) => Item::Missed(None),
+    });
+
+    let stream = tokio_stream::iter(initial)
+        .chain(live)
+        .filter(move |item| match (item, &asset_ids) {
+            (Item::Prices(event), Some(ids)) => ids.contains(&event.update.asset_id),
+            _ => true,
+        })
+        .filter_map(move |item| match item {
+            Item::Prices(event) => Event::default()
+                .event(PRICE_EVENT)
+                .id(feed.event_id(event.id))
+                .json_data(&*event.update)
+                .ok()
+                .map(Ok),
+            Item::Missed(latest) => {
+                let event = Event::default().event(RESET_EVENT).data("");
+                Some(Ok(match latest {
+                    Some(id) => event.id(feed.event_id(id)),
+                    None => event,
+                }))
+            }
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,131 @@
+use axum::{extract::State, http::StatusCode, Json};
+use shared::{RiskRequest, RiskResponse};
+
+use super::portfolios::WEIGHT_TOLERANCE;
+use super::{api_error, default_index, internal_error, ApiError};
+use crate::db::{self, DbPool};
+use crate::services::{metrics_service, price_service};
+
+/// Longest horizon a VaR can be scaled to, about a trading year
+const MAX_HORIZON_DAYS: usize = 252;
+/// Measures per report at most: methods x confidence levels x horizons
+const MAX_MEASURES: usize = 100;
+
+pub async fn get_risk_report(
+    State(pool): State<DbPool>,
+    Json(request): Json<RiskRequest>,
+) -> Result<Json<RiskResponse>, ApiError> {
+    let (label, holdings) = match &request.portfolio_id {
+        Some(id) => {
+            let portfolio = db::portfolios::fetch_portfolio(&pool, id)
+                .await
+                .map_err(internal_error)?
+                .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Unknown portfolio", id))?;
+            (portfolio.name, portfolio.assets)
+        }
+        None => ("portfolio".to_string(), request.holdings.clone()),
+    };
+
+    if holdings.is_empty() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid risk request",
+            "at least one holding is required",
+        ));
+    }
+    if let Some(h) = holdings
+        .iter()
+        .find(|h| !h.weight.is_finite() || h.weight < 0.0)
+    {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid risk request",
+            format!("invalid weight {} for {}", h.weight, h.asset_id),
+        ));
+    }
+    let total: f64 = holdings.iter().map(|h| h.weight).sum();
+    if (total - 1.0).abs() > WEIGHT_TOLERANCE {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid risk request",
+            format!("weights sum to {}, not 1", total),
+        ));
+    }
+    if let Some(c) = request
+        .confidence_levels
+        .iter()
+        .find(|c| !(0.5..1.0).contains(*c))
+    {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid risk request",
+            format!("confidence level {} must be in [0.5, 1)", c),
+        ));
+    }
+    if let Some(h) = request
+        .horizons_days
+        .iter()
+        .find(|h| !(1..=MAX_HORIZON_DAYS).contains(*h))
+    {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid risk request",
+            format!("horizon {} must be 1 to {} days", h, MAX_HORIZON_DAYS),
+        ));
+    }
+    let measures =
+        request.methods.len() * request.confidence_levels.len() * request.horizons_days.len();
+    if measures > MAX_MEASURES {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Invalid risk request",
+            format!("{} measures requested, at most {}", measures, MAX_MEASURES),
+        ));
+    }
+
+    // Returns come from the same split-neutral index as comparisons
+    let mut histories = Vec::with_capacity(holdings.len());
+    for holding in &holdings {
+        let index = default_index(
+            &pool,
+            &holding.asset_id,
+            request.start_date,
+            request.end_date,
+        )
+        .await?;
+        histories.push((holding.asset_id.clone(), index));
+    }
+
+    let report = |label: &str, returns: &[f64]| {
+        metrics_service::risk_report(
+            label,
+            returns,
+            &request.methods,
+            &request.confidence_levels,
+            &request.horizons_days,
+        )
+    };
+
+    let assets = histories
+        .iter()
+        .map(|(asset_id, prices)| report(asset_id, &metrics_service::simple_returns(prices)))
+        .collect();
+
+    // Portfolio returns assume the weights are held constant each period
+    let (_, returns) = price_service::aligned_returns(&histories);
+    let periods = returns.first().map_or(0, Vec::len);
+    let portfolio_returns: Vec<f64> = (0..periods)
+        .map(|t| {
+            holdings
+                .iter()
+                .zip(&returns)
+                .map(|(holding, r)| holding.weight * r[t])
+                .sum()
+        })
+        .collect();
+
+    Ok(Json(RiskResponse {
+        assets,
+        portfolio: report(&label, &portfolio_returns),
+    }))
+}
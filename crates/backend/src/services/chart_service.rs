@@ -0,0 +1,191 @@
+use std::sync::{Arc, OnceLock};
+
+use charting::{DrawdownChart, EventMarker, LineChart, Series, YAxisMode};
+use resvg::{tiny_skia, usvg};
+use shared::{ChartQuery, ChartScale, ChartView, ComparisonResponse};
+use thiserror::Error;
+
+/// Font for all chart text, embedded rather than looked up on the server so
+/// the same chart rasterizes to the same pixels everywhere
+const FONTS: [&[u8]; 2] = [
+    include_bytes!("../../fonts/DejaVuSans.ttf"),
+    include_bytes!("../../fonts/DejaVuSans-Bold.ttf"),
+];
+const FONT_FAMILY: &str = "DejaVu Sans";
+
+#[derive(Debug, Error)]
+pub enum ChartRenderError {
+    #[error("invalid SVG: {0}")]
+    Svg(#[from] usvg::Error),
+    #[error("cannot allocate a {0}x{1} image")]
+    Pixmap(u32, u32),
+    #[error("PNG encoding failed: {0}")]
+    Png(String),
+}
+
+/// The comparison chart described by `query`, drawn the same way as in the
+/// web app minus the interactive overlay
+pub fn comparison_chart(response: &ComparisonResponse, query: &ChartQuery) -> LineChart {
+    let (width, height) = (query.width as f64, query.height as f64);
+    let mut chart = match query.view {
+        ChartView::Drawdown => response
+            .series
+            .iter()
+            .fold(DrawdownChart::new(width, height), |chart, series| {
+                chart.with_series(Series::new(
+                    series.display_name().to_string(),
+                    series
+                        .points
+                        .iter()
+                        .map(|p| (p.timestamp, p.normalized_value))
+                        .collect(),
+                ))
+            })
+            .line_chart(),
+        ChartView::Performance => {
+            let y_axis = match query.scale {
+                ChartScale::Linear => YAxisMode::Linear,
+                ChartScale::Log => YAxisMode::Log,
+                ChartScale::Percent => YAxisMode::Percent,
+            };
+            response.series.iter().fold(
+                LineChart::new(width, height).with_y_axis(y_axis),
+                |chart, series| {
+                    chart.with_series(Series::new(
+                        series.display_name().to_string(),
+                        series
+                            .points
+                            .iter()
+                            .map(|p| match y_axis {
+                                YAxisMode::Percent => (p.timestamp, p.return_pct),
+                                YAxisMode::Linear | YAxisMode::Log => {
+                                    (p.timestamp, p.normalized_value)
+                                }
+                            })
+                            .collect(),
+                    ))
+                },
+            )
+        }
+    };
+
+    if let Some(title) = &query.title {
+        chart = chart.with_title(title.clone());
+    }
+    if query.events {
+        for event in &response.events {
+            chart = chart.with_event(EventMarker {
+                start: event.start_date,
+                end: event.end_date,
+                label: event.title.clone(),
+                color: event.category.color().to_string(),
+            });
+        }
+    }
+    chart
+}
+
+/// The embedded fonts, loaded once, standing in for every generic family
+fn font_database() -> Arc<usvg::fontdb::Database> {
+    static DATABASE: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    DATABASE
+        .get_or_init(|| {
+            let mut fonts = usvg::fontdb::Database::new();
+            for font in FONTS {
+                fonts.load_font_data(font.to_vec());
+            }
+            fonts.set_sans_serif_family(FONT_FAMILY);
+            fonts.set_serif_family(FONT_FAMILY);
+            fonts.set_monospace_family(FONT_FAMILY);
+            Arc::new(fonts)
+        })
+        .clone()
+}
+
+/// Rasterize an SVG document at its own size, with text in the embedded font
+pub fn svg_to_png(svg: &str) -> Result<Vec<u8>, ChartRenderError> {
+    let options = usvg::Options {
+        fontdb: font_database(),
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options)?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or(ChartRenderError::Pixmap(size.width(), size.height()))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap
+        .encode_png()
+        .map_err(|e| ChartRenderError::Png(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use shared::{AssetSeries, EventCategory, MarketEvent, NormalizedPricePoint};
+
+    fn response() -> ComparisonResponse {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let series = |asset_id: &str, values: &[f64]| AssetSeries {
+            asset_id: asset_id.to_string(),
+            label: None,
+            points: values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| NormalizedPricePoint {
+                    timestamp: start + Duration::days(i as i64 * 7),
+                    normalized_value: *v,
+                    return_pct: (v / values[0] - 1.0) * 100.0,
+                })
+                .collect(),
+        };
+        ComparisonResponse {
+            series: vec![
+                series("SPY", &[100.0, 102.0, 101.0, 104.0, 107.0, 106.0]),
+                series("BTC", &[100.0, 110.0, 95.0, 120.0, 135.0, 128.0]),
+            ],
+            metrics: vec![],
+            events: vec![MarketEvent {
+                id: 1,
+                category: EventCategory::Halving,
+                title: "Bitcoin halving".to_string(),
+                description: None,
+                asset_id: Some("BTC".to_string()),
+                start_date: start + Duration::days(20),
+                end_date: None,
+            }],
+        }
+    }
+
+    fn query(scale: ChartScale) -> ChartQuery {
+        let query: ChartQuery =
+            serde_json::from_str(r#"{"assets": "SPY,BTC", "start": "2024-01-01"}"#).unwrap();
+        ChartQuery { scale, ..query }
+    }
+
+    fn assert_snapshot(file: &str, output: &[u8]) {
+        charting::svg::assert_snapshot_in(env!("CARGO_MANIFEST_DIR"), file, output);
+    }
+
+    #[test]
+    fn test_svg_matches_snapshot() {
+        let svg = comparison_chart(&response(), &query(ChartScale::Percent)).to_svg();
+        assert_snapshot("comparison_chart.svg", svg.as_bytes());
+    }
+
+    #[test]
+    fn test_png_matches_snapshot() {
+        let svg = comparison_chart(&response(), &query(ChartScale::Percent)).to_svg();
+        assert_snapshot("comparison_chart.png", &svg_to_png(&svg).unwrap());
+    }
+
+    #[test]
+    fn test_png_has_chart_size() {
+        let svg = comparison_chart(&response(), &query(ChartScale::Linear)).to_svg();
+        let png = svg_to_png(&svg).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        // IHDR width and height, big-endian
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 1000);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 450);
+    }
+}
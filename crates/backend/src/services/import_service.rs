@@ -0,0 +1,452 @@
+use std::collections::HashMap;
+
+use shared::{Asset, CsvColumnMapping, ImportKind, ImportedHolding, RejectedRow};
+
+/// Header names used for each field by common broker exports (Fidelity,
+/// Schwab, Vanguard, Interactive Brokers), compared case-insensitively
+/// with punctuation ignored, most specific first
+const SYMBOL_HEADERS: [&str; 4] = ["symbol", "ticker", "instrument", "security"];
+const QUANTITY_HEADERS: [&str; 5] = ["quantity", "qty quantity", "qty", "shares", "position"];
+const VALUE_HEADERS: [&str; 5] = [
+    "current value",
+    "market value",
+    "mkt val market value",
+    "total value",
+    "value",
+];
+const ACTION_HEADERS: [&str; 5] = ["action", "transaction type", "buy sell", "side", "type"];
+
+/// Rows of a CSV read into positions of known assets
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub columns: Vec<String>,
+    pub mapping: CsvColumnMapping,
+    pub mapping_error: Option<String>,
+    pub positions: Vec<Position>,
+    pub unknown_symbols: Vec<String>,
+    pub rejected_rows: Vec<RejectedRow>,
+}
+
+/// Everything a CSV says about one asset
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub asset_id: String,
+    pub quantity: f64,
+    /// Known only if every row for the asset gave one
+    pub market_value: Option<f64>,
+    /// CSV lines the position was read from
+    pub lines: Vec<u64>,
+}
+
+fn normalize_header(header: &str) -> String {
+    header
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn detect_column(columns: &[String], candidates: &[&str]) -> Option<String> {
+    let normalized: Vec<String> = columns.iter().map(|c| normalize_header(c)).collect();
+    candidates.iter().find_map(|candidate| {
+        normalized
+            .iter()
+            .position(|header| header == candidate)
+            .map(|i| columns[i].clone())
+    })
+}
+
+/// `mapping` with unset columns filled in from the header
+pub fn detect_mapping(
+    columns: &[String],
+    kind: ImportKind,
+    mapping: &CsvColumnMapping,
+) -> CsvColumnMapping {
+    CsvColumnMapping {
+        symbol: mapping
+            .symbol
+            .clone()
+            .or_else(|| detect_column(columns, &SYMBOL_HEADERS)),
+        quantity: mapping
+            .quantity
+            .clone()
+            .or_else(|| detect_column(columns, &QUANTITY_HEADERS)),
+        market_value: mapping.market_value.clone().or_else(|| match kind {
+            ImportKind::Positions => detect_column(columns, &VALUE_HEADERS),
+            ImportKind::Transactions => None,
+        }),
+        action: mapping.action.clone().or_else(|| match kind {
+            ImportKind::Positions => None,
+            ImportKind::Transactions => detect_column(columns, &ACTION_HEADERS),
+        }),
+    }
+}
+
+/// Number as brokers write them: `$1,234.50`, `(12.5)` for negatives,
+/// `--` or nothing for none
+fn parse_number(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let (value, sign) = match value.strip_prefix('(').and_then(|v| v.strip_suffix(')')) {
+        Some(inner) => (inner, -1.0),
+        None => (value, 1.0),
+    };
+    let digits: String = value
+        .chars()
+        .filter(|c| !matches!(c, '$' | ',' | ' '))
+        .collect();
+    digits
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .map(|n| n * sign)
+}
+
+/// Sign a transaction's quantity by its action, or `None` for actions that
+/// don't trade, such as dividends
+fn signed_quantity(action: &str, quantity: f64) -> Option<f64> {
+    let action = action.to_lowercase();
+    if ["buy", "bought", "reinvest"]
+        .iter()
+        .any(|a| action.contains(a))
+    {
+        Some(quantity.abs())
+    } else if ["sell", "sold"].iter().any(|a| action.contains(a)) {
+        Some(-quantity.abs())
+    } else {
+        None
+    }
+}
+
+fn find_asset<'a>(assets: &'a [Asset], symbol: &str) -> Option<&'a Asset> {
+    // Fidelity marks money market funds with asterisks
+    let symbol = symbol.trim().trim_end_matches('*');
+    assets
+        .iter()
+        .find(|a| a.symbol.eq_ignore_ascii_case(symbol) || a.id.eq_ignore_ascii_case(symbol))
+}
+
+/// Read positions from a broker CSV. Lines before the header, like the
+/// account title some exports start with, are skipped. Fails only if the
+/// CSV has no header at all.
+pub fn parse_csv(
+    csv: &str,
+    kind: ImportKind,
+    mapping: &CsvColumnMapping,
+    assets: &[Asset],
+) -> Result<ParsedImport, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+    let mut records = reader.records();
+
+    // The header is the first row with at least two named columns
+    let columns: Vec<String> = loop {
+        match records.next() {
+            Some(Ok(record)) if record.iter().filter(|f| !f.is_empty()).count() >= 2 => {
+                break record.iter().map(str::to_string).collect();
+            }
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(e.to_string()),
+            None => return Err("no header row found".to_string()),
+        }
+    };
+    let mapping = detect_mapping(&columns, kind, mapping);
+    let mut parsed = ParsedImport {
+        columns,
+        mapping,
+        ..Default::default()
+    };
+
+    let index = |column: &Option<String>| -> Result<Option<usize>, String> {
+        match column {
+            None => Ok(None),
+            Some(name) => parsed
+                .columns
+                .iter()
+                .position(|c| c == name)
+                .map(Some)
+                .ok_or_else(|| format!("no column named {:?}", name)),
+        }
+    };
+    let columns = (|| {
+        let symbol = index(&parsed.mapping.symbol)?.ok_or("no symbol column")?;
+        let quantity = index(&parsed.mapping.quantity)?;
+        let value = index(&parsed.mapping.market_value)?;
+        let action = index(&parsed.mapping.action)?;
+        match (kind, quantity, value) {
+            (ImportKind::Positions, None, None) => {
+                Err("no quantity or market value column".to_string())
+            }
+            (ImportKind::Transactions, None, _) => Err("no quantity column".to_string()),
+            _ => Ok((symbol, quantity, value, action)),
+        }
+    })();
+    let (symbol_col, quantity_col, value_col, action_col) = match columns {
+        Ok(columns) => columns,
+        Err(e) => {
+            parsed.mapping_error = Some(e);
+            return Ok(parsed);
+        }
+    };
+
+    let mut positions: Vec<Position> = Vec::new();
+    for record in records {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(0, |p| p.line());
+                parsed.rejected_rows.push(RejectedRow {
+                    line,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |p| p.line());
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        let mut reject = |reason: String| parsed.rejected_rows.push(RejectedRow { line, reason });
+
+        let symbol = record.get(symbol_col).unwrap_or_default();
+        if symbol.is_empty() {
+            reject("no symbol".to_string());
+            continue;
+        }
+        let Some(asset) = find_asset(assets, symbol) else {
+            if !parsed.unknown_symbols.iter().any(|s| s == symbol) {
+                parsed.unknown_symbols.push(symbol.to_string());
+            }
+            continue;
+        };
+
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).and_then(parse_number);
+        let (quantity, value) = match kind {
+            ImportKind::Positions => {
+                let (quantity, value) = (field(quantity_col), field(value_col));
+                if quantity.is_none() && value.is_none() {
+                    reject(format!("no quantity for {}", symbol));
+                    continue;
+                }
+                if quantity.is_some_and(|q| q <= 0.0) || value.is_some_and(|v| v <= 0.0) {
+                    reject(format!(
+                        "only long positions can be imported, not {}",
+                        symbol
+                    ));
+                    continue;
+                }
+                (quantity.unwrap_or_default(), value)
+            }
+            ImportKind::Transactions => {
+                let Some(quantity) = field(quantity_col) else {
+                    reject(format!("no quantity for {}", symbol));
+                    continue;
+                };
+                let quantity = match action_col.map(|c| record.get(c).unwrap_or_default()) {
+                    None => quantity,
+                    Some(action) => match signed_quantity(action, quantity) {
+                        Some(quantity) => quantity,
+                        None => {
+                            reject(format!("{:?} is not a buy or sell", action));
+                            continue;
+                        }
+                    },
+                };
+                (quantity, None)
+            }
+        };
+
+        match positions.iter_mut().find(|p| p.asset_id == asset.id) {
+            Some(position) => {
+                position.quantity += quantity;
+                position.market_value = position.market_value.zip(value).map(|(a, b)| a + b);
+                position.lines.push(line);
+            }
+            None => positions.push(Position {
+                asset_id: asset.id.clone(),
+                quantity,
+                market_value: value,
+                lines: vec![line],
+            }),
+        }
+    }
+
+    parsed.positions = positions;
+    Ok(parsed)
+}
+
+/// Weight positions by market value, valuing those without one at
+/// `prices`. Positions closed out by sales are left out; rows of positions
+/// that can't be valued are rejected.
+pub fn weigh(
+    positions: &[Position],
+    prices: &HashMap<String, f64>,
+) -> (Vec<ImportedHolding>, Vec<RejectedRow>) {
+    let mut holdings = Vec::new();
+    let mut rejected = Vec::new();
+    for position in positions {
+        if position.market_value.is_none() && position.quantity <= 0.0 {
+            continue;
+        }
+        let value = position.market_value.or_else(|| {
+            prices
+                .get(&position.asset_id)
+                .map(|p| p * position.quantity)
+        });
+        match value {
+            Some(value) => holdings.push(ImportedHolding {
+                asset_id: position.asset_id.clone(),
+                quantity: position.quantity,
+                market_value: value,
+                weight: 0.0,
+            }),
+            None => rejected.extend(position.lines.iter().map(|&line| RejectedRow {
+                line,
+                reason: format!("no stored price to value {}", position.asset_id),
+            })),
+        }
+    }
+
+    let total: f64 = holdings.iter().map(|h| h.market_value).sum();
+    if total > 0.0 {
+        for holding in &mut holdings {
+            holding.weight = holding.market_value / total;
+        }
+    }
+    (holdings, rejected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reasons(parsed: &ParsedImport) -> Vec<(u64, &str)> {
+        parsed
+            .rejected_rows
+            .iter()
+            .map(|r| (r.line, r.reason.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_positions_export() {
+        let csv = "\
+Positions for account Brokerage ...123
+\"Symbol\",\"Description\",\"Qty (Quantity)\",\"Mkt Val (Market Value)\"
+\"SPY\",\"SPDR S&P 500\",\"10\",\"$5,000.00\"
+\"BTC\",\"Bitcoin\",\"0.5\",\"$30,000.00\"
+\"SPAXX**\",\"Money market\",\"--\",\"$1,000.00\"
+\"QQQ\",\"Invesco QQQ\",\"(2)\",\"($900.00)\"
+\"\",\"Account Total\",\"\",\"$36,100.00\"
+";
+        let parsed = parse_csv(
+            csv,
+            ImportKind::Positions,
+            &CsvColumnMapping::default(),
+            &Asset::all_default(),
+        )
+        .unwrap();
+
+        assert_eq!(parsed.mapping.symbol.as_deref(), Some("Symbol"));
+        assert_eq!(parsed.mapping.quantity.as_deref(), Some("Qty (Quantity)"));
+        assert_eq!(
+            parsed.mapping.market_value.as_deref(),
+            Some("Mkt Val (Market Value)")
+        );
+        assert_eq!(parsed.mapping_error, None);
+        assert_eq!(parsed.unknown_symbols, vec!["SPAXX**"]);
+        assert_eq!(
+            reasons(&parsed),
+            vec![
+                (6, "only long positions can be imported, not QQQ"),
+                (7, "no symbol")
+            ]
+        );
+
+        let (holdings, rejected) = weigh(&parsed.positions, &HashMap::new());
+        assert!(rejected.is_empty());
+        let weights: Vec<(&str, f64)> = holdings
+            .iter()
+            .map(|h| (h.asset_id.as_str(), h.weight))
+            .collect();
+        assert_eq!(weights, vec![("SPY", 5.0 / 35.0), ("BTC", 30.0 / 35.0)]);
+    }
+
+    #[test]
+    fn test_parse_transactions_export() {
+        let csv = "\
+Date,Action,Symbol,Quantity,Amount
+01/02/2024,YOU BOUGHT,SPY,10,-4700
+02/01/2024,YOU SOLD,SPY,-4,1950
+03/01/2024,DIVIDEND RECEIVED,SPY,,12.5
+03/05/2024,YOU BOUGHT,XAU,2,-4000
+04/01/2024,YOU BOUGHT,BTC,1,-60000
+05/01/2024,YOU SOLD,BTC,1,65000
+";
+        let parsed = parse_csv(
+            csv,
+            ImportKind::Transactions,
+            &CsvColumnMapping::default(),
+            &Asset::all_default(),
+        )
+        .unwrap();
+        assert_eq!(parsed.mapping.action.as_deref(), Some("Action"));
+        assert_eq!(parsed.mapping.market_value, None);
+        assert_eq!(reasons(&parsed), vec![(4, "no quantity for SPY")]);
+
+        // Gold has no stored price, Bitcoin was sold again
+        let prices = HashMap::from([("SPY".to_string(), 500.0), ("BTC".to_string(), 1.0)]);
+        let (holdings, rejected) = weigh(&parsed.positions, &prices);
+        assert_eq!(
+            holdings,
+            vec![ImportedHolding {
+                asset_id: "SPY".to_string(),
+                quantity: 6.0,
+                market_value: 3000.0,
+                weight: 1.0,
+            }]
+        );
+        assert_eq!(
+            rejected,
+            vec![RejectedRow {
+                line: 5,
+                reason: "no stored price to value XAU".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_mapping_errors() {
+        let assets = Asset::all_default();
+        let parsed = parse_csv(
+            "Name,Amount\nSPY,5\n",
+            ImportKind::Positions,
+            &CsvColumnMapping::default(),
+            &assets,
+        )
+        .unwrap();
+        assert_eq!(parsed.mapping_error.as_deref(), Some("no symbol column"));
+
+        let mapping = CsvColumnMapping {
+            symbol: Some("Name".to_string()),
+            quantity: Some("Units".to_string()),
+            ..Default::default()
+        };
+        let parsed = parse_csv(
+            "Name,Amount\nSPY,5\n",
+            ImportKind::Positions,
+            &mapping,
+            &assets,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.mapping_error.as_deref(),
+            Some("no column named \"Units\"")
+        );
+
+        assert!(parse_csv("", ImportKind::Positions, &mapping, &assets).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use shared::{Asset, AssetType, PriceField, PricePoint, PriceUpdate};
+
+use crate::clients::alpha_vantage::AlphaVantageClient;
+use crate::clients::coingecko::CoinGeckoClient;
+use crate::clients::{BarDataClient, PriceDataClient};
+use crate::db::{self, DbPool};
+
+use super::price_feed::PriceFeed;
+
+/// Fetch and store prices for an asset, returning the number of points written.
+///
+/// Providers that supply OHLCV bars are preferred; their bars are stored as-is
+/// and the close is written to `price_points`. Otherwise only prices are stored.
+/// Prices newer than those already stored are published to `feed` for
+/// streaming clients.
+pub async fn refresh_asset(
+    pool: &DbPool,
+    feed: &PriceFeed,
+    asset: &Asset,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> anyhow::Result<usize> {
+    let alpha_vantage = AlphaVantageClient::from_env();
+
+    let (source, prices): (&str, Vec<PricePoint>) = match (&alpha_vantage, &asset.asset_type) {
+        (Some(client), AssetType::Stock | AssetType::Crypto) => {
+            let bars = client.fetch_daily_bars(asset, start_date, end_date).await?;
+            db::bars::upsert_bars(pool, &bars).await?;
+            let prices = bars
+                .iter()
+                .map(|bar| bar.to_price_point(PriceField::Close))
+                .collect();
+            (AlphaVantageClient::SOURCE, prices)
+        }
+        (None, AssetType::Crypto) => {
+            let prices = CoinGeckoClient::new()
+                .fetch_historical(&asset.symbol, start_date, end_date)
+                .await?
+                .into_iter()
+                .map(|mut point| {
+                    point.asset_id = asset.id.clone();
+                    point
+                })
+                .collect();
+            (CoinGeckoClient::SOURCE, prices)
+        }
+        _ => anyhow::bail!("no data provider configured for {}", asset.symbol),
+    };
+
+    let previous_latest = db::prices::latest_timestamp(pool, &asset.id).await?;
+    db::prices::upsert_prices(pool, &prices).await?;
+    db::refreshes::record_refresh(pool, &asset.id, source, prices.len()).await?;
+
+    let points = prices.len();
+    let new_prices = new_prices(prices, previous_latest);
+    if !new_prices.is_empty() {
+        feed.publish(PriceUpdate {
+            asset_id: asset.id.clone(),
+            prices: new_prices,
+        });
+    }
+    Ok(points)
+}
+
+/// Prices after `previous_latest` worth streaming. A first backfill only
+/// announces its latest price; clients load the history themselves.
+fn new_prices(
+    mut prices: Vec<PricePoint>,
+    previous_latest: Option<DateTime<Utc>>,
+) -> Vec<PricePoint> {
+    match previous_latest {
+        Some(latest) => {
+            prices.retain(|p| p.timestamp > latest);
+            prices
+        }
+        None => prices
+            .into_iter()
+            .max_by_key(|p| p.timestamp)
+            .into_iter()
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn prices(days: &[u32]) -> Vec<PricePoint> {
+        days.iter()
+            .map(|&day| PricePoint {
+                asset_id: "SPY".to_string(),
+                timestamp: Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap(),
+                price: day as f64,
+            })
+            .collect()
+    }
+
+    fn days(prices: &[PricePoint]) -> Vec<f64> {
+        prices.iter().map(|p| p.price).collect()
+    }
+
+    #[test]
+    fn test_new_prices_after_previous_latest() {
+        let latest = Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap();
+        assert_eq!(
+            days(&new_prices(prices(&[1, 2, 3, 4]), Some(latest))),
+            vec![3.0, 4.0]
+        );
+        assert!(new_prices(prices(&[1, 2]), Some(latest)).is_empty());
+    }
+
+    #[test]
+    fn test_new_prices_of_backfill_is_latest_only() {
+        assert_eq!(days(&new_prices(prices(&[1, 3, 2]), None)), vec![3.0]);
+        assert!(new_prices(vec![], None).is_empty());
+    }
+}
@@ -1,5 +1,12 @@
-use shared::{PricePoint, PerformanceMetrics};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveTime, Utc};
+use shared::{
+    Bar, PricePoint, PerformanceMetrics, RiskReport, TailRiskMeasure, VarMethod,
+    VolatilityEstimator, WindowMetrics,
+};
+
+/// How late a history may start and still count as covering a window, so
+/// windows opening on a weekend or holiday aren't dropped
+const WINDOW_START_TOLERANCE_DAYS: i64 = 4;
 
 /// Calculate performance metrics from price data
 pub fn calculate_metrics(
@@ -27,28 +34,365 @@ pub fn calculate_metrics(
         0.0
     };
 
-    // Calculate volatility (standard deviation of returns)
-    let returns: Vec<f64> = prices
-        .windows(2)
-        .map(|window| {
-            let prev_price = window[0].price;
-            let curr_price = window[1].price;
-            (curr_price / prev_price) - 1.0
-        })
-        .collect();
+    // Calculate volatility (standard deviation of returns), annualized
+    // like the ratios
+    let returns = simple_returns(prices);
 
-    let volatility = calculate_std_dev(&returns) * 100.0;
+    let std_dev = calculate_std_dev(&returns);
+    let periods_per_year = periods_per_year(returns.len(), start_date, end_date);
+    let volatility = std_dev * periods_per_year.sqrt() * 100.0;
+    let mean_return = returns.iter().sum::<f64>() / returns.len() as f64;
+    let max_drawdown_pct = max_drawdown_pct(prices);
 
     Some(PerformanceMetrics {
         asset_id: asset_id.to_string(),
         total_return_pct,
         annualized_return_pct,
         volatility,
+        max_drawdown_pct,
+        sharpe_ratio: ratio(mean_return * periods_per_year.sqrt(), std_dev),
+        sortino_ratio: ratio(
+            mean_return * periods_per_year.sqrt(),
+            downside_deviation(&returns),
+        ),
+        calmar_ratio: ratio(annualized_return_pct, -max_drawdown_pct),
         start_date,
         end_date,
     })
 }
 
+/// Periods per year at the sampling rate observed over `start..end`, since
+/// crypto trades every day and stocks only on weekdays. 0 for an empty span.
+pub fn periods_per_year(periods: usize, start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    let years = (end - start).num_days() as f64 / 365.25;
+    if years > 0.0 {
+        periods as f64 / years
+    } else {
+        0.0
+    }
+}
+
+/// Metrics over the usual trailing windows (1M, 3M, YTD, 1Y, 3Y, 5Y and
+/// everything) ending at the latest price. Windows reaching back before the
+/// history starts are left out.
+pub fn window_metrics(asset_id: &str, prices: &[PricePoint]) -> Vec<WindowMetrics> {
+    let (Some(first), Some(last)) = (prices.first(), prices.last()) else {
+        return vec![];
+    };
+    let end = last.timestamp;
+    let months_back = |months: u32| end.checked_sub_months(Months::new(months));
+    let year_start = NaiveDate::from_ymd_opt(end.year(), 1, 1)
+        .map(|date| date.and_time(NaiveTime::MIN).and_utc());
+    let windows = [
+        ("1M", months_back(1)),
+        ("3M", months_back(3)),
+        ("YTD", year_start),
+        ("1Y", months_back(12)),
+        ("3Y", months_back(36)),
+        ("5Y", months_back(60)),
+        ("Max", Some(first.timestamp)),
+    ];
+
+    windows
+        .into_iter()
+        .filter_map(|(window, start)| {
+            let start = start?;
+            if first.timestamp > start + Duration::days(WINDOW_START_TOLERANCE_DAYS) {
+                return None;
+            }
+            // Measure from the last price at or before the window opens
+            let from = prices.partition_point(|p| p.timestamp <= start).saturating_sub(1);
+            calculate_metrics(asset_id, &prices[from..]).map(|metrics| WindowMetrics {
+                window: window.to_string(),
+                metrics,
+            })
+        })
+        .collect()
+}
+
+/// Largest peak-to-trough decline of a price series, as a negative
+/// percentage (0 if it never fell)
+pub fn max_drawdown_pct(prices: &[PricePoint]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut worst: f64 = 0.0;
+    for point in prices {
+        peak = peak.max(point.price);
+        worst = worst.min(point.price / peak - 1.0);
+    }
+    worst * 100.0
+}
+
+/// Root mean square of the negative returns
+fn downside_deviation(returns: &[f64]) -> f64 {
+    if returns.is_empty() {
+        return 0.0;
+    }
+    let sum_sq = returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>();
+    (sum_sq / returns.len() as f64).sqrt()
+}
+
+/// `numerator / denominator`, or `None` when the denominator is zero
+fn ratio(numerator: f64, denominator: f64) -> Option<f64> {
+    (denominator > 0.0)
+        .then(|| numerator / denominator)
+        .filter(|r| r.is_finite())
+}
+
+/// Period-over-period simple returns
+pub fn simple_returns(prices: &[PricePoint]) -> Vec<f64> {
+    prices
+        .windows(2)
+        .map(|window| (window[1].price / window[0].price) - 1.0)
+        .collect()
+}
+
+/// Skewness of returns (0 for a symmetric distribution)
+pub fn skewness(returns: &[f64]) -> f64 {
+    let (m2, m3, _) = central_moments(returns);
+    if m2 > 0.0 {
+        m3 / m2.powf(1.5)
+    } else {
+        0.0
+    }
+}
+
+/// Excess kurtosis of returns (0 for a normal distribution)
+pub fn excess_kurtosis(returns: &[f64]) -> f64 {
+    let (m2, _, m4) = central_moments(returns);
+    if m2 > 0.0 {
+        m4 / (m2 * m2) - 3.0
+    } else {
+        0.0
+    }
+}
+
+/// Value-at-Risk and Expected Shortfall of per-period returns over
+/// `horizon` periods, as positive loss fractions.
+///
+/// Historical estimates use overlapping compounded `horizon`-period returns;
+/// the parametric and Cornish-Fisher estimates scale the one-period moments
+/// assuming independent returns.
+pub fn tail_risk(
+    returns: &[f64],
+    method: VarMethod,
+    confidence: f64,
+    horizon: usize,
+) -> Option<(f64, f64)> {
+    if returns.len() < 2 || horizon == 0 || !(0.0..1.0).contains(&confidence) {
+        return None;
+    }
+    let alpha = 1.0 - confidence;
+
+    if method == VarMethod::Historical {
+        let mut aggregated: Vec<f64> = returns
+            .windows(horizon)
+            .map(|w| w.iter().map(|r| 1.0 + r).product::<f64>() - 1.0)
+            .collect();
+        if aggregated.len() < 2 {
+            return None;
+        }
+        aggregated.sort_by(f64::total_cmp);
+
+        // Guard against 1 - 0.95 rounding up past a whole observation
+        let tail = ((alpha * aggregated.len() as f64 - 1e-9).ceil() as usize).max(1);
+        let var = -aggregated[tail - 1];
+        let es = -aggregated[..tail].iter().sum::<f64>() / tail as f64;
+        return Some((var, es));
+    }
+
+    let h = horizon as f64;
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n * h;
+    let std_dev =
+        (returns.iter().map(|r| (r - mean / h).powi(2)).sum::<f64>() / (n - 1.0)).sqrt() * h.sqrt();
+
+    let (var_z, es_z) = match method {
+        VarMethod::Parametric => {
+            let z = inverse_normal_cdf(alpha);
+            (z, -normal_pdf(z) / alpha)
+        }
+        _ => {
+            let s = skewness(returns) / h.sqrt();
+            let k = excess_kurtosis(returns) / h;
+            let cornish_fisher = |u: f64| {
+                let z = inverse_normal_cdf(u);
+                z + (z * z - 1.0) * s / 6.0 + (z.powi(3) - 3.0 * z) * k / 24.0
+                    - (2.0 * z.powi(3) - 5.0 * z) * s * s / 36.0
+            };
+            // Average the adjusted quantile over the tail (midpoint rule)
+            let steps = 1000;
+            let tail_mean = (0..steps)
+                .map(|i| cornish_fisher(alpha * (i as f64 + 0.5) / steps as f64))
+                .sum::<f64>()
+                / steps as f64;
+            (cornish_fisher(alpha), tail_mean)
+        }
+    };
+
+    Some((-(mean + std_dev * var_z), -(mean + std_dev * es_z)))
+}
+
+/// Skewness, kurtosis and tail-risk measures for every requested method,
+/// confidence level and horizon
+pub fn risk_report(
+    label: &str,
+    returns: &[f64],
+    methods: &[VarMethod],
+    confidence_levels: &[f64],
+    horizons: &[usize],
+) -> RiskReport {
+    let mut measures = Vec::new();
+    for &method in methods {
+        for &confidence in confidence_levels {
+            for &horizon in horizons {
+                if let Some((var, es)) = tail_risk(returns, method, confidence, horizon) {
+                    measures.push(TailRiskMeasure {
+                        method,
+                        confidence,
+                        horizon_days: horizon,
+                        value_at_risk_pct: var * 100.0,
+                        expected_shortfall_pct: es * 100.0,
+                    });
+                }
+            }
+        }
+    }
+
+    RiskReport {
+        label: label.to_string(),
+        observations: returns.len(),
+        skewness: skewness(returns),
+        excess_kurtosis: excess_kurtosis(returns),
+        measures,
+    }
+}
+
+/// Second, third and fourth central moments
+fn central_moments(values: &[f64]) -> (f64, f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let moment = |p: i32| values.iter().map(|v| (v - mean).powi(p)).sum::<f64>() / n;
+    (moment(2), moment(3), moment(4))
+}
+
+fn normal_pdf(z: f64) -> f64 {
+    (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Quantile of the standard normal distribution (Acklam's approximation,
+/// relative error below 1.2e-9)
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Per-period volatility (as a fraction) from OHLC bars using a range-based
+/// estimator, unannualized: daily bars give daily volatility. Bars should
+/// already be adjusted for corporate actions. Returns `None` for
+/// `CloseToClose`, which works on prices, or when there are too few bars.
+pub fn range_volatility(bars: &[Bar], estimator: VolatilityEstimator) -> Option<f64> {
+    let ln = |a: f64, b: f64| (a / b).ln();
+    let mean = |values: &mut dyn Iterator<Item = f64>| {
+        let (sum, count) = values.fold((0.0, 0usize), |(s, c), v| (s + v, c + 1));
+        if count > 0 {
+            Some(sum / count as f64)
+        } else {
+            None
+        }
+    };
+    let rogers_satchell = |bar: &Bar| {
+        ln(bar.high, bar.close) * ln(bar.high, bar.open)
+            + ln(bar.low, bar.close) * ln(bar.low, bar.open)
+    };
+
+    let variance = match estimator {
+        VolatilityEstimator::CloseToClose => return None,
+        VolatilityEstimator::Parkinson => {
+            mean(&mut bars.iter().map(|b| ln(b.high, b.low).powi(2)))?
+                / (4.0 * std::f64::consts::LN_2)
+        }
+        VolatilityEstimator::GarmanKlass => mean(&mut bars.iter().map(|b| {
+            0.5 * ln(b.high, b.low).powi(2)
+                - (2.0 * std::f64::consts::LN_2 - 1.0) * ln(b.close, b.open).powi(2)
+        }))?,
+        VolatilityEstimator::RogersSatchell => mean(&mut bars.iter().map(rogers_satchell))?,
+        VolatilityEstimator::YangZhang => {
+            // Needs the previous close for the overnight return
+            if bars.len() < 3 {
+                return None;
+            }
+            let periods = &bars[1..];
+            let n = periods.len() as f64;
+            let overnight: Vec<f64> = bars.windows(2).map(|w| ln(w[1].open, w[0].close)).collect();
+            let open_to_close: Vec<f64> = periods.iter().map(|b| ln(b.close, b.open)).collect();
+            let k = 0.34 / (1.34 + (n + 1.0) / (n - 1.0));
+
+            sample_variance(&overnight)
+                + k * sample_variance(&open_to_close)
+                + (1.0 - k) * mean(&mut periods.iter().map(rogers_satchell))?
+        }
+    };
+
+    Some(variance.max(0.0).sqrt())
+}
+
+fn sample_variance(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64
+}
+
 fn calculate_std_dev(values: &[f64]) -> f64 {
     if values.is_empty() {
         return 0.0;
@@ -73,4 +417,181 @@ mod tests {
         let std_dev = calculate_std_dev(&values);
         assert!((std_dev - 2.0).abs() < 0.1);
     }
+
+    #[test]
+    fn test_drawdown_and_ratios() {
+        let start = chrono::Utc::now();
+        let prices: Vec<PricePoint> = [100.0, 120.0, 90.0, 108.0, 130.0]
+            .iter()
+            .enumerate()
+            .map(|(i, price)| PricePoint {
+                asset_id: "TEST".to_string(),
+                timestamp: start + chrono::Duration::days(i as i64 * 91),
+                price: *price,
+            })
+            .collect();
+
+        let metrics = calculate_metrics("TEST", &prices).unwrap();
+        assert!((metrics.max_drawdown_pct + 25.0).abs() < 1e-9);
+        let calmar = metrics.calmar_ratio.unwrap();
+        assert!((calmar - metrics.annualized_return_pct / 25.0).abs() < 1e-9);
+        // Only one losing period, so downside deviation is below volatility
+        assert!(metrics.sortino_ratio.unwrap() > metrics.sharpe_ratio.unwrap());
+        // Quarterly returns, annualized like Sharpe
+        let returns = simple_returns(&prices);
+        let quarterly = calculate_std_dev(&returns) * 100.0;
+        let per_year = returns.len() as f64 / (364.0 / 365.25);
+        assert!((metrics.volatility - quarterly * per_year.sqrt()).abs() < 1e-9);
+
+        let flat = vec![prices[0].clone(), PricePoint { price: 100.0, ..prices[1].clone() }];
+        let metrics = calculate_metrics("TEST", &flat).unwrap();
+        assert_eq!(metrics.max_drawdown_pct, 0.0);
+        assert_eq!(metrics.sharpe_ratio, None);
+        assert_eq!(metrics.calmar_ratio, None);
+    }
+
+    #[test]
+    fn test_window_metrics_skips_uncovered_windows() {
+        use chrono::TimeZone;
+        let start = chrono::Utc.with_ymd_and_hms(2023, 3, 1, 0, 0, 0).unwrap();
+        let prices: Vec<PricePoint> = (0..500)
+            .map(|i| PricePoint {
+                asset_id: "TEST".to_string(),
+                timestamp: start + chrono::Duration::days(i),
+                price: 100.0 + i as f64,
+            })
+            .collect();
+
+        let windows = window_metrics("TEST", &prices);
+        let labels: Vec<&str> = windows.iter().map(|w| w.window.as_str()).collect();
+        assert_eq!(labels, vec!["1M", "3M", "YTD", "1Y", "Max"]);
+
+        // Last price is on 2024-07-12
+        let one_month = &windows[0].metrics;
+        assert_eq!(one_month.start_date.date_naive().to_string(), "2024-06-12");
+        assert_eq!(windows[4].metrics.start_date, start);
+    }
+
+    #[test]
+    fn test_inverse_normal_cdf() {
+        assert!((inverse_normal_cdf(0.5)).abs() < 1e-9);
+        assert!((inverse_normal_cdf(0.05) + 1.6448536).abs() < 1e-6);
+        assert!((inverse_normal_cdf(0.01) + 2.3263479).abs() < 1e-6);
+        assert!((inverse_normal_cdf(0.975) - 1.9599640).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_historical_tail_risk() {
+        // 100 returns from -0.50 to 0.49: the worst 5 are -0.50..-0.46
+        let returns: Vec<f64> = (0..100).map(|i| (i as f64 - 50.0) / 100.0).collect();
+        let (var, es) = tail_risk(&returns, VarMethod::Historical, 0.95, 1).unwrap();
+
+        assert!((var - 0.46).abs() < 1e-12);
+        assert!((es - 0.48).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_parametric_tail_risk() {
+        // Symmetric two-point distribution: zero skew, kurtosis of -2
+        let returns: Vec<f64> = (0..200)
+            .map(|i| if i % 2 == 0 { 0.01 } else { -0.01 })
+            .collect();
+        assert!(skewness(&returns).abs() < 1e-12);
+        assert!((excess_kurtosis(&returns) + 2.0).abs() < 1e-12);
+
+        let (var, es) = tail_risk(&returns, VarMethod::Parametric, 0.99, 1).unwrap();
+        let sd = (200.0f64 / 199.0).sqrt() * 0.01;
+        assert!((var - 2.3263479 * sd).abs() < 1e-8);
+        assert!((es - normal_pdf(2.3263479) / 0.01 * sd).abs() < 1e-6);
+        assert!(es > var);
+
+        // Horizon scaling with zero mean is the square-root-of-time rule
+        let (var_10, _) = tail_risk(&returns, VarMethod::Parametric, 0.99, 10).unwrap();
+        assert!((var_10 - var * 10f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cornish_fisher_penalizes_fat_left_tail() {
+        // Mostly small gains with occasional large losses
+        let returns: Vec<f64> = (0..500)
+            .map(|i| if i % 10 == 0 { -0.03 } else { 0.004 })
+            .collect();
+        assert!(skewness(&returns) < 0.0);
+
+        let (parametric, _) = tail_risk(&returns, VarMethod::Parametric, 0.99, 1).unwrap();
+        let (cornish_fisher, _) = tail_risk(&returns, VarMethod::CornishFisher, 0.99, 1).unwrap();
+        assert!(cornish_fisher > parametric);
+    }
+
+    fn bars(ohlc: &[(f64, f64, f64, f64)]) -> Vec<Bar> {
+        use chrono::{Duration, TimeZone, Utc};
+        use shared::BarInterval;
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        ohlc.iter()
+            .enumerate()
+            .map(|(i, &(open, high, low, close))| Bar {
+                asset_id: "TEST".to_string(),
+                timestamp: start + Duration::days(i as i64),
+                interval: BarInterval::Day,
+                open,
+                high,
+                low,
+                close,
+                adjusted_close: None,
+                volume: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_range_estimators_single_bar() {
+        let bar = bars(&[(100.0, 110.0, 95.0, 105.0)]);
+        let (hl, co) = ((110.0f64 / 95.0).ln(), (105.0f64 / 100.0).ln());
+        let (hc, ho) = ((110.0f64 / 105.0).ln(), (110.0f64 / 100.0).ln());
+        let (lc, lo) = ((95.0f64 / 105.0).ln(), (95.0f64 / 100.0).ln());
+
+        let parkinson = range_volatility(&bar, VolatilityEstimator::Parkinson).unwrap();
+        assert!((parkinson - hl / (4.0 * std::f64::consts::LN_2).sqrt()).abs() < 1e-12);
+
+        let garman_klass = range_volatility(&bar, VolatilityEstimator::GarmanKlass).unwrap();
+        let expected = 0.5 * hl * hl - (2.0 * std::f64::consts::LN_2 - 1.0) * co * co;
+        assert!((garman_klass - expected.sqrt()).abs() < 1e-12);
+
+        let rogers_satchell = range_volatility(&bar, VolatilityEstimator::RogersSatchell).unwrap();
+        assert!((rogers_satchell - (hc * ho + lc * lo).sqrt()).abs() < 1e-12);
+
+        assert_eq!(range_volatility(&bar, VolatilityEstimator::CloseToClose), None);
+        assert_eq!(range_volatility(&bar, VolatilityEstimator::YangZhang), None);
+    }
+
+    #[test]
+    fn test_yang_zhang_without_gaps_or_drift() {
+        // No overnight gaps and open == close: only the Rogers-Satchell term remains
+        let flat = bars(&[(100.0, 104.0, 97.0, 100.0); 5]);
+        let rs = range_volatility(&flat, VolatilityEstimator::RogersSatchell).unwrap();
+        let yz = range_volatility(&flat, VolatilityEstimator::YangZhang).unwrap();
+
+        let n = 4.0;
+        let k = 0.34 / (1.34 + (n + 1.0) / (n - 1.0));
+        assert!((yz - ((1.0 - k) * rs * rs).sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_yang_zhang_captures_overnight_gaps() {
+        // Identical intraday ranges, but the second series gaps between sessions
+        let continuous = bars(&[
+            (100.0, 103.0, 99.0, 102.0),
+            (102.0, 105.0, 101.0, 104.0),
+            (104.0, 107.0, 103.0, 106.0),
+        ]);
+        let gapping = bars(&[
+            (100.0, 103.0, 99.0, 102.0),
+            (96.0, 99.0, 95.0, 98.0),
+            (104.0, 107.0, 103.0, 106.0),
+        ]);
+
+        let yz = |b: &[Bar]| range_volatility(b, VolatilityEstimator::YangZhang).unwrap();
+        assert!(yz(&gapping) > yz(&continuous));
+    }
 }
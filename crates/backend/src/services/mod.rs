@@ -4,5 +4,11 @@
 // - Metrics calculation service
 // - Portfolio comparison service
 
+pub mod chart_service;
 pub mod price_service;
+pub mod ingestion_service;
 pub mod metrics_service;
+pub mod optimizer_service;
+pub mod portfolio_service;
+pub mod price_feed;
+pub mod import_service;
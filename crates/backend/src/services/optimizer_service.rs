@@ -0,0 +1,547 @@
+use shared::PricePoint;
+use thiserror::Error;
+
+use super::price_service;
+
+const MAX_ITERATIONS: usize = 20_000;
+const MAX_OUTER_ITERATIONS: usize = 100;
+const STEP_TOLERANCE: f64 = 1e-13;
+const CONSTRAINT_TOLERANCE: f64 = 1e-9;
+const BOUNDS_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum OptimizerError {
+    #[error("not enough overlapping price history to estimate returns")]
+    InsufficientData,
+    #[error("infeasible weight bounds: {0}")]
+    InfeasibleBounds(String),
+    #[error("target return {target:.2}% is outside the achievable range {min:.2}%..{max:.2}%")]
+    TargetOutOfRange { target: f64, min: f64, max: f64 },
+    #[error("no asset has an expected return above the risk-free rate")]
+    NoExcessReturn,
+}
+
+/// Annualized expected returns and covariance of periodic returns
+#[derive(Debug, Clone)]
+pub struct ReturnStatistics {
+    pub asset_ids: Vec<String>,
+    pub expected_returns: Vec<f64>,
+    pub covariance: Vec<Vec<f64>>,
+}
+
+/// Lower/upper weight per asset, in the same order as `ReturnStatistics::asset_ids`
+#[derive(Debug, Clone)]
+pub struct WeightBounds {
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+impl WeightBounds {
+    /// No short selling, no leverage
+    pub fn long_only(n: usize) -> Self {
+        Self {
+            lower: vec![0.0; n],
+            upper: vec![1.0; n],
+        }
+    }
+
+    pub fn validate(&self, n: usize) -> Result<(), OptimizerError> {
+        if self.lower.len() != n || self.upper.len() != n {
+            return Err(OptimizerError::InfeasibleBounds(format!(
+                "expected {} bounds, got {}/{}",
+                n,
+                self.lower.len(),
+                self.upper.len()
+            )));
+        }
+        if let Some(i) = (0..n).find(|&i| self.lower[i] > self.upper[i]) {
+            return Err(OptimizerError::InfeasibleBounds(format!(
+                "lower bound {} exceeds upper bound {} for asset #{}",
+                self.lower[i], self.upper[i], i
+            )));
+        }
+        let lower_sum: f64 = self.lower.iter().sum();
+        let upper_sum: f64 = self.upper.iter().sum();
+        if lower_sum > 1.0 + BOUNDS_TOLERANCE || upper_sum < 1.0 - BOUNDS_TOLERANCE {
+            return Err(OptimizerError::InfeasibleBounds(format!(
+                "weights must sum to 1 but bounds allow {:.4}..{:.4}",
+                lower_sum, upper_sum
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Estimate annualized return statistics from price histories aligned on
+/// their common dates (see `price_service::aligned_returns`).
+pub fn estimate_statistics(
+    histories: &[(String, Vec<PricePoint>)],
+) -> Result<ReturnStatistics, OptimizerError> {
+    let (dates, returns) = price_service::aligned_returns(histories);
+    if dates.len() < 3 {
+        return Err(OptimizerError::InsufficientData);
+    }
+
+    let years = (dates[dates.len() - 1] - dates[0]).num_days() as f64 / 365.25;
+    if years <= 0.0 {
+        return Err(OptimizerError::InsufficientData);
+    }
+    let periods = dates.len() - 1;
+    let periods_per_year = periods as f64 / years;
+
+    let means: Vec<f64> = returns
+        .iter()
+        .map(|r| r.iter().sum::<f64>() / periods as f64)
+        .collect();
+
+    let n = histories.len();
+    let mut covariance = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i..n {
+            let cov = (0..periods)
+                .map(|t| (returns[i][t] - means[i]) * (returns[j][t] - means[j]))
+                .sum::<f64>()
+                / (periods - 1) as f64
+                * periods_per_year;
+            covariance[i][j] = cov;
+            covariance[j][i] = cov;
+        }
+    }
+
+    Ok(ReturnStatistics {
+        asset_ids: histories.iter().map(|(id, _)| id.clone()).collect(),
+        expected_returns: means.iter().map(|m| m * periods_per_year).collect(),
+        covariance,
+    })
+}
+
+pub fn portfolio_return(stats: &ReturnStatistics, weights: &[f64]) -> f64 {
+    dot(&stats.expected_returns, weights)
+}
+
+pub fn portfolio_volatility(stats: &ReturnStatistics, weights: &[f64]) -> f64 {
+    dot(weights, &mat_vec(&stats.covariance, weights))
+        .max(0.0)
+        .sqrt()
+}
+
+pub fn sharpe_ratio(stats: &ReturnStatistics, weights: &[f64], risk_free_rate: f64) -> f64 {
+    let volatility = portfolio_volatility(stats, weights);
+    if volatility > 0.0 {
+        (portfolio_return(stats, weights) - risk_free_rate) / volatility
+    } else {
+        0.0
+    }
+}
+
+/// Lowest and highest expected return reachable within the bounds
+pub fn achievable_return_range(stats: &ReturnStatistics, bounds: &WeightBounds) -> (f64, f64) {
+    let mut order: Vec<usize> = (0..stats.expected_returns.len()).collect();
+    order.sort_by(|&a, &b| stats.expected_returns[a].total_cmp(&stats.expected_returns[b]));
+
+    let extreme = |order: &mut dyn Iterator<Item = usize>| {
+        let mut weights = bounds.lower.clone();
+        let mut budget = 1.0 - weights.iter().sum::<f64>();
+        for i in order {
+            let add = budget.min(bounds.upper[i] - bounds.lower[i]);
+            weights[i] += add;
+            budget -= add;
+        }
+        portfolio_return(stats, &weights)
+    };
+
+    (
+        extreme(&mut order.iter().copied()),
+        extreme(&mut order.iter().rev().copied()),
+    )
+}
+
+/// Global minimum-variance portfolio within the bounds
+pub fn min_variance(
+    stats: &ReturnStatistics,
+    bounds: &WeightBounds,
+) -> Result<Vec<f64>, OptimizerError> {
+    let n = stats.asset_ids.len();
+    bounds.validate(n)?;
+
+    let cov = &stats.covariance;
+    let lipschitz = 2.0 * frobenius_norm(cov);
+    Ok(minimize(
+        |w| mat_vec(cov, w).iter().map(|g| 2.0 * g).collect(),
+        lipschitz,
+        bounds,
+        vec![1.0 / n as f64; n],
+    ))
+}
+
+/// Minimum-variance portfolio with the given annualized expected return
+pub fn target_return(
+    stats: &ReturnStatistics,
+    bounds: &WeightBounds,
+    target: f64,
+) -> Result<Vec<f64>, OptimizerError> {
+    let n = stats.asset_ids.len();
+    bounds.validate(n)?;
+
+    let (min, max) = achievable_return_range(stats, bounds);
+    if target < min - CONSTRAINT_TOLERANCE || target > max + CONSTRAINT_TOLERANCE {
+        return Err(OptimizerError::TargetOutOfRange {
+            target: target * 100.0,
+            min: min * 100.0,
+            max: max * 100.0,
+        });
+    }
+
+    // Augmented Lagrangian on the return constraint; the budget and bounds
+    // are handled exactly by the projection.
+    let mu = &stats.expected_returns;
+    let cov = &stats.covariance;
+    let cov_norm = frobenius_norm(cov).max(f64::EPSILON);
+    let mu_norm = dot(mu, mu).max(f64::EPSILON);
+    let mut penalty = 10.0 * cov_norm / mu_norm;
+    let mut multiplier = 0.0;
+    let mut weights = vec![1.0 / n as f64; n];
+    let mut last_residual = f64::INFINITY;
+
+    for _ in 0..MAX_OUTER_ITERATIONS {
+        let (lambda, rho) = (multiplier, penalty);
+        weights = minimize(
+            |w| {
+                let scale = lambda + rho * (dot(mu, w) - target);
+                mat_vec(cov, w)
+                    .iter()
+                    .zip(mu)
+                    .map(|(g, m)| 2.0 * g + scale * m)
+                    .collect()
+            },
+            2.0 * cov_norm + rho * mu_norm,
+            bounds,
+            weights,
+        );
+
+        let residual = dot(mu, &weights) - target;
+        if residual.abs() < CONSTRAINT_TOLERANCE {
+            break;
+        }
+        multiplier += penalty * residual;
+        if residual.abs() > 0.25 * last_residual {
+            penalty *= 10.0;
+        }
+        last_residual = residual.abs();
+    }
+
+    Ok(weights)
+}
+
+/// Tangency portfolio: highest Sharpe ratio on the efficient frontier
+pub fn max_sharpe(
+    stats: &ReturnStatistics,
+    bounds: &WeightBounds,
+    risk_free_rate: f64,
+) -> Result<Vec<f64>, OptimizerError> {
+    let min_var = min_variance(stats, bounds)?;
+    let (_, max_return) = achievable_return_range(stats, bounds);
+    if max_return <= risk_free_rate {
+        return Err(OptimizerError::NoExcessReturn);
+    }
+
+    let mut low = portfolio_return(stats, &min_var);
+    let mut high = max_return;
+    if high - low < CONSTRAINT_TOLERANCE {
+        return Ok(min_var);
+    }
+
+    // Sharpe ratio is unimodal along the frontier, so a golden-section
+    // search over the target return finds the tangency point.
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let sharpe_at = |target: f64| -> Result<(f64, Vec<f64>), OptimizerError> {
+        let weights = target_return(stats, bounds, target)?;
+        Ok((sharpe_ratio(stats, &weights, risk_free_rate), weights))
+    };
+
+    let mut a = high - ratio * (high - low);
+    let mut b = low + ratio * (high - low);
+    let mut fa = sharpe_at(a)?;
+    let mut fb = sharpe_at(b)?;
+    while high - low > 1e-7 {
+        if fa.0 < fb.0 {
+            low = a;
+            a = b;
+            fa = fb;
+            b = low + ratio * (high - low);
+            fb = sharpe_at(b)?;
+        } else {
+            high = b;
+            b = a;
+            fb = fa;
+            a = high - ratio * (high - low);
+            fa = sharpe_at(a)?;
+        }
+    }
+
+    let candidates = [
+        fa,
+        fb,
+        sharpe_at(high)?,
+        (sharpe_ratio(stats, &min_var, risk_free_rate), min_var),
+    ];
+    Ok(candidates
+        .into_iter()
+        .max_by(|x, y| x.0.total_cmp(&y.0))
+        .map(|(_, weights)| weights)
+        .expect("candidates is non-empty"))
+}
+
+/// Evenly spaced portfolios from the minimum-variance point to the highest
+/// achievable return
+pub fn efficient_frontier(
+    stats: &ReturnStatistics,
+    bounds: &WeightBounds,
+    points: usize,
+) -> Result<Vec<Vec<f64>>, OptimizerError> {
+    let min_var = min_variance(stats, bounds)?;
+    if points < 2 {
+        return Ok(vec![min_var]);
+    }
+
+    let low = portfolio_return(stats, &min_var);
+    let (_, high) = achievable_return_range(stats, bounds);
+    let mut frontier = vec![min_var];
+    for k in 1..points {
+        let target = low + (high - low) * k as f64 / (points - 1) as f64;
+        frontier.push(target_return(stats, bounds, target)?);
+    }
+    Ok(frontier)
+}
+
+/// Accelerated projected gradient descent over the bounded simplex, with
+/// momentum restarts whenever it stops making progress
+fn minimize<G>(gradient: G, lipschitz: f64, bounds: &WeightBounds, start: Vec<f64>) -> Vec<f64>
+where
+    G: Fn(&[f64]) -> Vec<f64>,
+{
+    let step = 1.0 / lipschitz.max(f64::EPSILON);
+    let mut x = project(&start, bounds);
+    let mut y = x.clone();
+    let mut t: f64 = 1.0;
+
+    for _ in 0..MAX_ITERATIONS {
+        let g = gradient(&y);
+        let moved: Vec<f64> = y.iter().zip(&g).map(|(yi, gi)| yi - step * gi).collect();
+        let next = project(&moved, bounds);
+
+        let change = next
+            .iter()
+            .zip(&x)
+            .map(|(n, p)| (n - p).abs())
+            .fold(0.0, f64::max);
+        if change < STEP_TOLERANCE {
+            return next;
+        }
+
+        let overshoot: f64 = y
+            .iter()
+            .zip(&next)
+            .zip(&x)
+            .map(|((yi, n), p)| (yi - n) * (n - p))
+            .sum();
+        if overshoot > 0.0 {
+            t = 1.0;
+        }
+
+        let t_next = (1.0 + (1.0 + 4.0 * t * t).sqrt()) / 2.0;
+        let momentum = (t - 1.0) / t_next;
+        y = next
+            .iter()
+            .zip(&x)
+            .map(|(n, p)| n + momentum * (n - p))
+            .collect();
+        x = next;
+        t = t_next;
+    }
+
+    x
+}
+
+/// Euclidean projection onto `{w : sum(w) = 1, lower <= w <= upper}`
+fn project(v: &[f64], bounds: &WeightBounds) -> Vec<f64> {
+    let clipped = |shift: f64| -> Vec<f64> {
+        v.iter()
+            .enumerate()
+            .map(|(i, x)| (x - shift).clamp(bounds.lower[i], bounds.upper[i]))
+            .collect()
+    };
+
+    // The clipped sum is non-increasing in the shift; bisect for sum == 1
+    let mut low = (0..v.len())
+        .map(|i| v[i] - bounds.upper[i])
+        .fold(f64::INFINITY, f64::min);
+    let mut high = (0..v.len())
+        .map(|i| v[i] - bounds.lower[i])
+        .fold(f64::NEG_INFINITY, f64::max);
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if mid <= low || mid >= high {
+            break;
+        }
+        if clipped(mid).iter().sum::<f64>() > 1.0 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    clipped((low + high) / 2.0)
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn mat_vec(m: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    m.iter().map(|row| dot(row, v)).collect()
+}
+
+fn frobenius_norm(m: &[Vec<f64>]) -> f64 {
+    m.iter().flatten().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(expected_returns: Vec<f64>, covariance: Vec<Vec<f64>>) -> ReturnStatistics {
+        ReturnStatistics {
+            asset_ids: (0..expected_returns.len())
+                .map(|i| format!("A{}", i))
+                .collect(),
+            expected_returns,
+            covariance,
+        }
+    }
+
+    fn uncorrelated() -> ReturnStatistics {
+        stats(
+            vec![0.08, 0.12, 0.30],
+            vec![
+                vec![0.04, 0.0, 0.0],
+                vec![0.0, 0.09, 0.0],
+                vec![0.0, 0.0, 0.64],
+            ],
+        )
+    }
+
+    #[test]
+    fn test_min_variance_matches_closed_form() {
+        let s = uncorrelated();
+        let weights = min_variance(&s, &WeightBounds::long_only(3)).unwrap();
+
+        // Uncorrelated assets: w_i proportional to 1 / variance
+        let inverse: Vec<f64> = [0.04, 0.09, 0.64].iter().map(|v| 1.0 / v).collect();
+        let total: f64 = inverse.iter().sum();
+        for (w, inv) in weights.iter().zip(&inverse) {
+            assert!((w - inv / total).abs() < 1e-6, "{:?}", weights);
+        }
+    }
+
+    #[test]
+    fn test_bounds_are_respected() {
+        let s = uncorrelated();
+        let bounds = WeightBounds {
+            lower: vec![0.0, 0.0, 0.1],
+            upper: vec![0.5, 1.0, 1.0],
+        };
+        let weights = min_variance(&s, &bounds).unwrap();
+
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(weights[0] <= 0.5 + 1e-9);
+        assert!(weights[2] >= 0.1 - 1e-9);
+    }
+
+    #[test]
+    fn test_target_return_hits_target() {
+        let s = uncorrelated();
+        let bounds = WeightBounds::long_only(3);
+        let weights = target_return(&s, &bounds, 0.15).unwrap();
+
+        assert!((portfolio_return(&s, &weights) - 0.15).abs() < 1e-6);
+        assert!(weights.iter().all(|w| *w >= -1e-9));
+
+        let err = target_return(&s, &bounds, 0.5).unwrap_err();
+        assert!(matches!(err, OptimizerError::TargetOutOfRange { .. }));
+    }
+
+    #[test]
+    fn test_max_sharpe_matches_tangency_portfolio() {
+        let s = uncorrelated();
+        let rf = 0.02;
+        let weights = max_sharpe(&s, &WeightBounds::long_only(3), rf).unwrap();
+
+        // Unconstrained tangency: w proportional to inv(cov) * (mu - rf)
+        let raw: Vec<f64> = [(0.08, 0.04), (0.12, 0.09), (0.30, 0.64)]
+            .iter()
+            .map(|(m, v)| (m - rf) / v)
+            .collect();
+        let total: f64 = raw.iter().sum();
+        for (w, r) in weights.iter().zip(&raw) {
+            assert!((w - r / total).abs() < 1e-3, "{:?}", weights);
+        }
+    }
+
+    #[test]
+    fn test_efficient_frontier_is_monotonic() {
+        let s = uncorrelated();
+        let frontier = efficient_frontier(&s, &WeightBounds::long_only(3), 8).unwrap();
+
+        assert_eq!(frontier.len(), 8);
+        for pair in frontier.windows(2) {
+            assert!(portfolio_return(&s, &pair[1]) > portfolio_return(&s, &pair[0]));
+            assert!(
+                portfolio_volatility(&s, &pair[1]) >= portfolio_volatility(&s, &pair[0]) - 1e-9
+            );
+        }
+    }
+
+    #[test]
+    fn test_estimate_statistics_uses_common_dates() {
+        use chrono::{Duration, TimeZone, Utc};
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let history = |asset_id: &str, days: Vec<i64>| {
+            let prices = days
+                .into_iter()
+                .map(|day| PricePoint {
+                    asset_id: asset_id.to_string(),
+                    timestamp: start + Duration::days(day),
+                    price: 100.0 + day as f64,
+                })
+                .collect();
+            (asset_id.to_string(), prices)
+        };
+
+        // Stocks skip the weekend, crypto trades every day
+        let stock = history("SPY", vec![0, 1, 2, 3, 4, 7, 8]);
+        let crypto = history("BTC", (0..9).collect());
+        let s = estimate_statistics(&[stock.clone(), crypto]).unwrap();
+
+        assert_eq!(s.asset_ids, vec!["SPY", "BTC"]);
+        assert_eq!(s.expected_returns[0], s.expected_returns[1]);
+        assert!((s.covariance[0][1] - s.covariance[1][0]).abs() < 1e-15);
+
+        let disjoint = history("XAU", vec![5, 6]);
+        assert_eq!(
+            estimate_statistics(&[stock, disjoint]).unwrap_err(),
+            OptimizerError::InsufficientData
+        );
+    }
+
+    #[test]
+    fn test_infeasible_bounds() {
+        let bounds = WeightBounds {
+            lower: vec![0.0, 0.0, 0.0],
+            upper: vec![0.3, 0.3, 0.3],
+        };
+        let err = min_variance(&uncorrelated(), &bounds).unwrap_err();
+        assert!(matches!(err, OptimizerError::InfeasibleBounds(_)));
+    }
+}
@@ -0,0 +1,107 @@
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use shared::{PricePoint, RebalanceRule};
+
+use super::price_service;
+
+/// Rebalancing period a date falls in; weights are reset when it changes
+fn period(rule: RebalanceRule, date: NaiveDate) -> Option<(i32, u32)> {
+    match rule {
+        RebalanceRule::Never => None,
+        RebalanceRule::Monthly => Some((date.year(), date.month())),
+        RebalanceRule::Quarterly => Some((date.year(), (date.month() - 1) / 3)),
+        RebalanceRule::Yearly => Some((date.year(), 0)),
+    }
+}
+
+/// Value of a portfolio holding `weights` of each history, over the dates
+/// all of them have a price. It starts at `initial_amount` and is traded
+/// back to the target weights at the first date of each new rebalancing
+/// period; in between, weights drift with prices.
+pub fn backtest(
+    label: &str,
+    histories: &[(String, Vec<PricePoint>)],
+    weights: &[f64],
+    rebalance: RebalanceRule,
+    initial_amount: f64,
+) -> Vec<PricePoint> {
+    let (dates, returns) = price_service::aligned_returns(histories);
+    let mut holdings: Vec<f64> = weights.iter().map(|w| w * initial_amount).collect();
+    let point = |date: NaiveDate, price: f64| PricePoint {
+        asset_id: label.to_string(),
+        timestamp: date.and_time(NaiveTime::MIN).and_utc(),
+        price,
+    };
+
+    let Some(first) = dates.first() else {
+        return vec![];
+    };
+    let mut values = vec![point(*first, initial_amount)];
+    for (t, window) in dates.windows(2).enumerate() {
+        for (holding, asset_returns) in holdings.iter_mut().zip(&returns) {
+            *holding *= 1.0 + asset_returns[t];
+        }
+        let total: f64 = holdings.iter().sum();
+        if period(rebalance, window[1]) != period(rebalance, window[0]) {
+            for (holding, weight) in holdings.iter_mut().zip(weights) {
+                *holding = total * weight;
+            }
+        }
+        values.push(point(window[1], total));
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(asset_id: &str, prices: &[(u32, f64)]) -> (String, Vec<PricePoint>) {
+        let prices = prices
+            .iter()
+            .map(|(month, price)| PricePoint {
+                asset_id: asset_id.to_string(),
+                timestamp: NaiveDate::from_ymd_opt(2024, *month, 1)
+                    .unwrap()
+                    .and_time(NaiveTime::MIN)
+                    .and_utc(),
+                price: *price,
+            })
+            .collect();
+        (asset_id.to_string(), prices)
+    }
+
+    #[test]
+    fn test_backtest_rebalancing() {
+        // One asset doubles every month while the other stays flat
+        let histories = vec![
+            history("A", &[(1, 1.0), (2, 2.0), (3, 4.0)]),
+            history("B", &[(1, 1.0), (2, 1.0), (3, 1.0)]),
+        ];
+        let weights = [0.5, 0.5];
+        let last = |rule| {
+            backtest("P", &histories, &weights, rule, 100.0)
+                .last()
+                .unwrap()
+                .price
+        };
+
+        assert!((last(RebalanceRule::Never) - 250.0).abs() < 1e-9);
+        // Back to 75/75 after February, so March ends at 150 + 75
+        assert!((last(RebalanceRule::Monthly) - 225.0).abs() < 1e-9);
+        assert!((last(RebalanceRule::Yearly) - 250.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_backtest_uses_common_dates() {
+        let histories = vec![
+            history("A", &[(1, 1.0), (2, 1.5), (3, 3.0)]),
+            history("B", &[(2, 1.0), (3, 1.0)]),
+        ];
+        let values = backtest("P", &histories, &[0.5, 0.5], RebalanceRule::Never, 100.0);
+
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].price, 100.0);
+        assert!((values[1].price - 150.0).abs() < 1e-9);
+        assert_eq!(values[1].asset_id, "P");
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use shared::PriceUpdate;
+use tokio::sync::broadcast;
+
+/// Events kept for clients resuming after a disconnect
+const REPLAY_CAPACITY: usize = 256;
+
+/// A stored batch of prices, numbered in publishing order
+#[derive(Debug, Clone)]
+pub struct FeedEvent {
+    pub id: u64,
+    pub update: Arc<PriceUpdate>,
+}
+
+/// Where a subscriber picks up the feed
+#[derive(Debug)]
+pub enum Resume {
+    /// Events published after the subscriber's last one, if any
+    Replay(Vec<FeedEvent>),
+    /// Some events are gone, so the subscriber has to reload. Carries the
+    /// id of the latest event, from which it can resume next time.
+    Reset(Option<u64>),
+}
+
+#[derive(Debug, Default)]
+struct Recent {
+    next_id: u64,
+    events: VecDeque<FeedEvent>,
+}
+
+/// Prices as ingestion stores them, broadcast to streaming clients. The
+/// most recent events are kept so a client that reconnects with the last
+/// event id it saw misses nothing.
+#[derive(Debug)]
+pub struct PriceFeed {
+    /// Tells this process's event ids apart from those of a previous run
+    epoch: i64,
+    sender: broadcast::Sender<FeedEvent>,
+    recent: Mutex<Recent>,
+}
+
+impl Default for PriceFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PriceFeed {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(REPLAY_CAPACITY);
+        PriceFeed {
+            epoch: Utc::now().timestamp_millis(),
+            sender,
+            recent: Mutex::new(Recent::default()),
+        }
+    }
+
+    pub fn publish(&self, update: PriceUpdate) {
+        if update.prices.is_empty() {
+            return;
+        }
+        let mut recent = self.recent.lock().unwrap();
+        let event = FeedEvent {
+            id: recent.next_id,
+            update: Arc::new(update),
+        };
+        recent.next_id += 1;
+        if recent.events.len() == REPLAY_CAPACITY {
+            recent.events.pop_front();
+        }
+        recent.events.push_back(event.clone());
+        // No receivers just means nobody is listening
+        let _ = self.sender.send(event);
+    }
+
+    /// `Last-Event-ID` of an event
+    pub fn event_id(&self, id: u64) -> String {
+        format!("{}-{}", self.epoch, id)
+    }
+
+    /// Subscribe to new events, resuming after `last_event_id` if given.
+    /// Replayed events all precede those the receiver will yield.
+    pub fn subscribe(
+        &self,
+        last_event_id: Option<&str>,
+    ) -> (Resume, broadcast::Receiver<FeedEvent>) {
+        // Holding the lock keeps publishing out of the gap between the two
+        let recent = self.recent.lock().unwrap();
+        let receiver = self.sender.subscribe();
+        let resume = match last_event_id {
+            None => Resume::Replay(vec![]),
+            Some(last) => self.resume_after(&recent, last),
+        };
+        (resume, receiver)
+    }
+
+    fn resume_after(&self, recent: &Recent, last_event_id: &str) -> Resume {
+        let latest = recent.next_id.checked_sub(1);
+        let last = last_event_id
+            .split_once('-')
+            .filter(|(epoch, _)| *epoch == self.epoch.to_string())
+            .and_then(|(_, id)| id.parse::<u64>().ok())
+            .filter(|id| Some(*id) <= latest);
+        let Some(last) = last else {
+            return Resume::Reset(latest);
+        };
+
+        let oldest = recent.events.front().map_or(recent.next_id, |e| e.id);
+        if last + 1 < oldest {
+            return Resume::Reset(latest);
+        }
+        Resume::Replay(
+            recent
+                .events
+                .iter()
+                .filter(|e| e.id > last)
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use shared::PricePoint;
+
+    fn update(price: f64) -> PriceUpdate {
+        PriceUpdate {
+            asset_id: "SPY".to_string(),
+            prices: vec![PricePoint {
+                asset_id: "SPY".to_string(),
+                timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+                price,
+            }],
+        }
+    }
+
+    fn replayed(resume: Resume) -> Vec<u64> {
+        match resume {
+            Resume::Replay(events) => events.iter().map(|e| e.id).collect(),
+            Resume::Reset(_) => panic!("expected a replay"),
+        }
+    }
+
+    #[test]
+    fn test_resume_replays_missed_events() {
+        let feed = PriceFeed::new();
+        for price in [1.0, 2.0, 3.0] {
+            feed.publish(update(price));
+        }
+
+        let (resume, mut receiver) = feed.subscribe(Some(&feed.event_id(0)));
+        assert_eq!(replayed(resume), vec![1, 2]);
+        let (resume, _) = feed.subscribe(Some(&feed.event_id(2)));
+        assert!(replayed(resume).is_empty());
+        let (resume, _) = feed.subscribe(None);
+        assert!(replayed(resume).is_empty());
+
+        feed.publish(update(4.0));
+        assert_eq!(receiver.try_recv().unwrap().id, 3);
+    }
+
+    #[test]
+    fn test_resume_resets_when_events_are_gone() {
+        let feed = PriceFeed::new();
+        for i in 0..REPLAY_CAPACITY + 2 {
+            feed.publish(update(i as f64));
+        }
+        let latest = Some(REPLAY_CAPACITY as u64 + 1);
+
+        // Evicted from the replay buffer
+        let (resume, _) = feed.subscribe(Some(&feed.event_id(0)));
+        assert!(matches!(resume, Resume::Reset(id) if id == latest));
+        // Oldest kept event follows directly
+        let (resume, _) = feed.subscribe(Some(&feed.event_id(1)));
+        assert_eq!(replayed(resume).len(), REPLAY_CAPACITY);
+        // From a previous run, or not an id at all
+        let (resume, _) = feed.subscribe(Some("1-5"));
+        assert!(matches!(resume, Resume::Reset(_)));
+        let (resume, _) = feed.subscribe(Some("garbage"));
+        assert!(matches!(resume, Resume::Reset(_)));
+    }
+}
@@ -1,5 +1,9 @@
-use shared::{PricePoint, NormalizedPricePoint};
-use chrono::{DateTime, Utc};
+use chrono::NaiveDate;
+use shared::{
+    Bar, CorporateAction, CorporateActionKind, DownsampleMethod, PricePoint, NormalizedPricePoint,
+    ReturnBasis,
+};
+use std::collections::{BTreeMap, BTreeSet};
 
 /// Normalize price points to start from a specific initial amount
 pub fn normalize_prices(
@@ -27,9 +31,224 @@ pub fn normalize_prices(
         .collect()
 }
 
+/// Periodic simple returns for several assets over the calendar dates every
+/// one of them has a price for, so weekend crypto prices are dropped when
+/// compared against stocks.
+///
+/// Returns the common dates and, per history, one return for each date
+/// after the first.
+pub fn aligned_returns(histories: &[(String, Vec<PricePoint>)]) -> (Vec<NaiveDate>, Vec<Vec<f64>>) {
+    let daily: Vec<BTreeMap<NaiveDate, f64>> = histories
+        .iter()
+        .map(|(_, prices)| {
+            prices
+                .iter()
+                .filter(|p| p.price > 0.0)
+                .map(|p| (p.timestamp.date_naive(), p.price))
+                .collect()
+        })
+        .collect();
+
+    let Some((first, rest)) = daily.split_first() else {
+        return (vec![], vec![]);
+    };
+    let mut common: BTreeSet<NaiveDate> = first.keys().copied().collect();
+    for series in rest {
+        common.retain(|date| series.contains_key(date));
+    }
+    let dates: Vec<NaiveDate> = common.into_iter().collect();
+
+    let returns = daily
+        .iter()
+        .map(|series| {
+            dates
+                .windows(2)
+                .map(|w| series[&w[1]] / series[&w[0]] - 1.0)
+                .collect()
+        })
+        .collect();
+
+    (dates, returns)
+}
+
+/// Rebuild raw closes as a cumulative return index starting at the first price.
+///
+/// Prices are expected to be unadjusted closes. Splits are always neutralized
+/// so they don't show up as cliffs; with `ReturnBasis::TotalReturn` cash
+/// dividends are also reinvested at the close on their ex-date. Prices that
+/// aren't positive can't be compounded from and are skipped.
+pub fn return_index(
+    prices: &[PricePoint],
+    actions: &[CorporateAction],
+    basis: ReturnBasis,
+) -> Vec<PricePoint> {
+    let prices: Vec<&PricePoint> = prices
+        .iter()
+        .filter(|p| p.price.is_finite() && p.price > 0.0)
+        .collect();
+    let Some(first) = prices.first() else {
+        return vec![];
+    };
+
+    let mut index = first.price;
+    let mut result = vec![PricePoint {
+        asset_id: first.asset_id.clone(),
+        timestamp: first.timestamp,
+        price: index,
+    }];
+
+    for window in prices.windows(2) {
+        let (prev, curr) = (window[0], window[1]);
+
+        let mut split_ratio = 1.0;
+        let mut dividend = 0.0;
+        for action in actions
+            .iter()
+            .filter(|a| a.ex_date > prev.timestamp && a.ex_date <= curr.timestamp)
+        {
+            match action.kind {
+                CorporateActionKind::Split { ratio } => split_ratio *= ratio,
+                CorporateActionKind::CashDividend { amount } => dividend += amount,
+            }
+        }
+        if basis == ReturnBasis::PriceReturn {
+            dividend = 0.0;
+        }
+
+        index *= (curr.price + dividend) * split_ratio / prev.price;
+        result.push(PricePoint {
+            asset_id: curr.asset_id.clone(),
+            timestamp: curr.timestamp,
+            price: index,
+        });
+    }
+
+    result
+}
+
+/// Actions still to apply to a series of adjusted closes: those taking
+/// effect on bars without an adjusted close, whose raw close stands in.
+/// `bars` are in chronological order.
+pub fn unadjusted_actions(bars: &[Bar], actions: &[CorporateAction]) -> Vec<CorporateAction> {
+    actions
+        .iter()
+        .filter(|action| {
+            bars.iter()
+                .find(|bar| bar.timestamp >= action.ex_date)
+                .is_some_and(|bar| bar.adjusted_close.is_none())
+        })
+        .cloned()
+        .collect()
+}
+
+/// Bars scaled for the corporate actions after them, the way `return_index`
+/// neutralizes them, so a split doesn't read as an overnight gap. All prices
+/// of a bar share one factor, leaving its intraday ranges intact. `bars` are
+/// raw and in chronological order.
+pub fn adjust_bars(bars: &[Bar], actions: &[CorporateAction], basis: ReturnBasis) -> Vec<Bar> {
+    let mut factor = 1.0;
+    let mut adjusted: Vec<Bar> = Vec::with_capacity(bars.len());
+    for (i, bar) in bars.iter().enumerate().rev() {
+        adjusted.push(Bar {
+            open: bar.open * factor,
+            high: bar.high * factor,
+            low: bar.low * factor,
+            close: bar.close * factor,
+            ..bar.clone()
+        });
+
+        // Actions taking effect on this bar scale the bars before it
+        let previous = i.checked_sub(1).map(|j| bars[j].timestamp);
+        for action in actions
+            .iter()
+            .filter(|a| a.ex_date <= bar.timestamp && previous.is_none_or(|p| a.ex_date > p))
+        {
+            match action.kind {
+                CorporateActionKind::Split { ratio } if ratio > 0.0 => factor /= ratio,
+                CorporateActionKind::CashDividend { amount }
+                    if basis == ReturnBasis::TotalReturn && bar.close + amount > 0.0 =>
+                {
+                    factor *= bar.close / (bar.close + amount)
+                }
+                _ => {}
+            }
+        }
+    }
+    adjusted.reverse();
+    adjusted
+}
+
+/// Re-price a series in another asset, e.g. a stock measured in ounces of
+/// gold. Each price is divided by the latest unit price at or before it;
+/// points before the first unit price are dropped.
+pub fn denominate(prices: &[PricePoint], unit_prices: &[PricePoint]) -> Vec<PricePoint> {
+    let mut unit = unit_prices.iter().filter(|p| p.price > 0.0).peekable();
+    let mut current = None;
+
+    prices
+        .iter()
+        .filter_map(|point| {
+            while let Some(next) = unit.next_if(|u| u.timestamp <= point.timestamp) {
+                current = Some(next.price);
+            }
+            current.map(|unit_price| PricePoint {
+                asset_id: point.asset_id.clone(),
+                timestamp: point.timestamp,
+                price: point.price / unit_price,
+            })
+        })
+        .collect()
+}
+
+/// Number of spaces between consecutive prices wider than `max_days`
+pub fn count_gaps(prices: &[PricePoint], max_days: i64) -> usize {
+    prices
+        .windows(2)
+        .filter(|w| {
+            (w[1].timestamp.date_naive() - w[0].timestamp.date_naive()).num_days() > max_days
+        })
+        .count()
+}
+
+/// Reduce a normalized series to at most `max_points` points for display
+pub fn downsample(
+    points: Vec<NormalizedPricePoint>,
+    max_points: usize,
+    method: DownsampleMethod,
+) -> Vec<NormalizedPricePoint> {
+    downsample_by(points, max_points, method, |p| {
+        (p.timestamp.timestamp_millis() as f64, p.normalized_value)
+    })
+}
+
+/// Reduce a raw price history to at most `max_points` points for display
+pub fn downsample_prices(
+    prices: Vec<PricePoint>,
+    max_points: usize,
+    method: DownsampleMethod,
+) -> Vec<PricePoint> {
+    downsample_by(prices, max_points, method, |p| {
+        (p.timestamp.timestamp_millis() as f64, p.price)
+    })
+}
+
+fn downsample_by<T: Clone>(
+    points: Vec<T>,
+    max_points: usize,
+    method: DownsampleMethod,
+    xy: impl Fn(&T) -> (f64, f64),
+) -> Vec<T> {
+    let kept = charting::downsample(method, &points, max_points, xy);
+    if kept.len() == points.len() {
+        return points;
+    }
+    kept.into_iter().map(|i| points[i].clone()).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{Duration, TimeZone, Utc};
 
     #[test]
     fn test_normalize_prices() {
@@ -58,4 +277,203 @@ mod tests {
         assert_eq!(normalized[1].normalized_value, 11000.0);
         assert_eq!(normalized[2].normalized_value, 9500.0);
     }
+
+    fn prices_start() -> chrono::DateTime<Utc> {
+        Utc.with_ymd_and_hms(2020, 8, 27, 0, 0, 0).unwrap()
+    }
+
+    fn daily_prices(closes: &[f64]) -> Vec<PricePoint> {
+        let start = prices_start();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, price)| PricePoint {
+                asset_id: "TEST".to_string(),
+                timestamp: start + Duration::days(i as i64),
+                price: *price,
+            })
+            .collect()
+    }
+
+    fn action(prices: &[PricePoint], day: usize, kind: CorporateActionKind) -> CorporateAction {
+        CorporateAction {
+            asset_id: "TEST".to_string(),
+            ex_date: prices[day].timestamp,
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_downsample_keeps_endpoints_and_crash() {
+        let mut closes: Vec<f64> = (0..5_000).map(|i| 100.0 + (i % 10) as f64).collect();
+        closes[2_500] = 20.0;
+        let points = normalize_prices(&daily_prices(&closes), 10_000.0);
+
+        let reduced = downsample(points.clone(), 200, DownsampleMethod::Lttb);
+        assert_eq!(reduced.len(), 200);
+        assert_eq!(reduced[0].timestamp, points[0].timestamp);
+        assert_eq!(reduced[199].timestamp, points[4_999].timestamp);
+        assert!(reduced.iter().any(|p| p.timestamp == points[2_500].timestamp));
+
+        assert_eq!(downsample(points[..100].to_vec(), 200, DownsampleMethod::MinMax).len(), 100);
+    }
+
+    #[test]
+    fn test_return_index_removes_split_cliff() {
+        let prices = daily_prices(&[400.0, 404.0, 101.0, 102.0]);
+        let split = action(&prices, 2, CorporateActionKind::Split { ratio: 4.0 });
+
+        let index = return_index(&prices, &[split], ReturnBasis::PriceReturn);
+        let values: Vec<f64> = index.iter().map(|p| p.price).collect();
+
+        assert_eq!(values, vec![400.0, 404.0, 404.0, 408.0]);
+    }
+
+    #[test]
+    fn test_return_index_skips_non_positive_prices() {
+        let prices = daily_prices(&[0.0, 100.0, 0.0, 110.0, -5.0, 121.0]);
+
+        let index = return_index(&prices, &[], ReturnBasis::PriceReturn);
+        assert_eq!(index.len(), 3);
+        for (point, expected) in index.iter().zip([100.0, 110.0, 121.0]) {
+            assert!((point.price - expected).abs() < 1e-9);
+        }
+        assert_eq!(index[1].timestamp, prices[3].timestamp);
+    }
+
+    #[test]
+    fn test_return_index_ignores_actions_outside_range() {
+        let prices = daily_prices(&[100.0, 110.0]);
+        let on_first_day = action(&prices, 0, CorporateActionKind::Split { ratio: 2.0 });
+        let after_last_day = CorporateAction {
+            ex_date: prices[1].timestamp + Duration::days(1),
+            ..action(&prices, 1, CorporateActionKind::CashDividend { amount: 5.0 })
+        };
+
+        let index = return_index(&prices, &[on_first_day, after_last_day], ReturnBasis::TotalReturn);
+        assert!((index[1].price - 110.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_total_return_reinvests_dividends() {
+        // Ex-dividend drop of 2.0 is offset when the dividend is reinvested
+        let prices = daily_prices(&[100.0, 102.0, 100.0, 101.0]);
+        let dividends = vec![action(&prices, 2, CorporateActionKind::CashDividend { amount: 2.0 })];
+
+        let price_return = return_index(&prices, &dividends, ReturnBasis::PriceReturn);
+        let total_return = return_index(&prices, &dividends, ReturnBasis::TotalReturn);
+
+        assert_eq!(price_return[2].price, 100.0);
+        assert!((total_return[2].price - 102.0).abs() < 1e-9);
+        assert!((total_return[3].price - 102.0 * 1.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_total_return_with_split_and_dividend_on_same_day() {
+        // 2-for-1 split plus a post-split dividend of 1.0 per new share
+        let prices = daily_prices(&[200.0, 99.0]);
+        let actions = vec![
+            action(&prices, 1, CorporateActionKind::Split { ratio: 2.0 }),
+            action(&prices, 1, CorporateActionKind::CashDividend { amount: 1.0 }),
+        ];
+
+        let index = return_index(&prices, &actions, ReturnBasis::TotalReturn);
+        assert!((index[1].price - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_unadjusted_actions_apply_to_raw_closes_only() {
+        let prices = daily_prices(&[400.0, 404.0, 101.0, 102.0]);
+        let bars: Vec<Bar> = prices
+            .iter()
+            .enumerate()
+            .map(|(i, p)| Bar {
+                asset_id: p.asset_id.clone(),
+                timestamp: p.timestamp,
+                interval: shared::BarInterval::Day,
+                open: p.price,
+                high: p.price,
+                low: p.price,
+                close: p.price,
+                // Adjusted before the split, which only the raw closes show
+                adjusted_close: (i < 2).then_some(p.price),
+                volume: None,
+            })
+            .collect();
+        let adjusted_split = action(&prices, 1, CorporateActionKind::Split { ratio: 2.0 });
+        let raw_split = action(&prices, 2, CorporateActionKind::Split { ratio: 4.0 });
+
+        let actions = unadjusted_actions(&bars, &[adjusted_split, raw_split.clone()]);
+        assert_eq!(actions, vec![raw_split]);
+
+        let adjusted: Vec<PricePoint> = bars
+            .iter()
+            .map(|b| b.to_price_point(shared::PriceField::AdjustedClose))
+            .collect();
+        let index = return_index(&adjusted, &actions, ReturnBasis::TotalReturn);
+        assert!((index[3].price - 408.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_adjust_bars_removes_split_gap() {
+        let prices = daily_prices(&[400.0, 404.0, 101.0, 102.0]);
+        let bars: Vec<Bar> = prices
+            .iter()
+            .map(|p| Bar {
+                asset_id: p.asset_id.clone(),
+                timestamp: p.timestamp,
+                interval: shared::BarInterval::Day,
+                open: p.price,
+                high: p.price * 1.01,
+                low: p.price * 0.99,
+                close: p.price,
+                adjusted_close: None,
+                volume: None,
+            })
+            .collect();
+        let actions = vec![
+            action(&prices, 2, CorporateActionKind::Split { ratio: 4.0 }),
+            action(&prices, 3, CorporateActionKind::CashDividend { amount: 1.0 }),
+        ];
+
+        let closes = |bars: &[Bar]| bars.iter().map(|b| b.close).collect::<Vec<f64>>();
+        let price_return = adjust_bars(&bars, &actions, ReturnBasis::PriceReturn);
+        assert_eq!(closes(&price_return), vec![100.0, 101.0, 101.0, 102.0]);
+        assert_eq!(price_return[0].high, 101.0);
+
+        // Reinvesting the dividend scales earlier bars by 102 / 103
+        let total_return = adjust_bars(&bars, &actions, ReturnBasis::TotalReturn);
+        for (bar, expected) in total_return.iter().zip([100.0, 101.0, 101.0]) {
+            assert!((bar.close - expected * 102.0 / 103.0).abs() < 1e-9);
+        }
+        assert_eq!(total_return[3].close, 102.0);
+    }
+
+    #[test]
+    fn test_denominate_uses_latest_unit_price() {
+        // Unit prices only on days 1 and 3, like a weekday-only asset
+        let prices = daily_prices(&[100.0, 120.0, 130.0, 150.0]);
+        let unit: Vec<PricePoint> = daily_prices(&[0.0, 2.0, 0.0, 3.0])
+            .into_iter()
+            .filter(|p| p.price > 0.0)
+            .collect();
+
+        let denominated = denominate(&prices, &unit);
+        let values: Vec<f64> = denominated.iter().map(|p| p.price).collect();
+        assert_eq!(values, vec![60.0, 65.0, 50.0]);
+        assert_eq!(denominated[0].timestamp, prices[1].timestamp);
+    }
+
+    #[test]
+    fn test_count_gaps() {
+        // Friday to Monday is normal for stocks; a missing week is not
+        let mut prices = daily_prices(&[1.0; 12]);
+        prices.retain(|p| {
+            let day = (p.timestamp - prices_start()).num_days();
+            !(1..=2).contains(&day) && !(5..=10).contains(&day)
+        });
+
+        assert_eq!(count_gaps(&prices, 4), 1);
+        assert_eq!(count_gaps(&prices, 1), 2);
+    }
 }
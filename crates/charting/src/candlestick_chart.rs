@@ -0,0 +1,483 @@
+// Candlestick / OHLC chart with an optional volume pane
+
+use chrono::{DateTime, Utc};
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+
+use crate::canvas::CanvasRenderer;
+use crate::line_chart::{ChartTheme, Margins};
+use crate::renderer::{Rect, Renderer, TextAlign, TextBaseline, TextStyle};
+use crate::scale::{LinearScale, Scale, YAxisMode};
+use crate::svg::SvgRenderer;
+use crate::ticks::{format_compact, nice_ticks, time_ticks, value_axis, TimeTick, ValueTick};
+
+/// Share of the plot height given to the volume pane
+const VOLUME_PANE_RATIO: f64 = 0.25;
+
+/// Vertical gap between the price and volume panes
+const PANE_GAP: f64 = 12.0;
+
+/// Open/high/low/close (and optionally volume) for one interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    pub timestamp: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: Option<f64>,
+}
+
+impl Candle {
+    fn is_valid(&self) -> bool {
+        [self.open, self.high, self.low, self.close]
+            .iter()
+            .all(|v| v.is_finite())
+    }
+
+    fn is_up(&self) -> bool {
+        self.close >= self.open
+    }
+}
+
+/// How each interval is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CandleStyle {
+    /// Filled body between open and close with a high/low wick
+    #[default]
+    Candlestick,
+    /// High/low bar with an open tick on the left and a close tick on the right
+    Ohlc,
+}
+
+/// Positions and scales for one render of a `CandlestickChart`. The x
+/// scale maps milliseconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandlestickLayout {
+    pub price_plot: Rect,
+    /// Present when any candle has a volume
+    pub volume_plot: Option<Rect>,
+    pub x_scale: LinearScale,
+    pub price_scale: Scale,
+    pub volume_scale: Option<LinearScale>,
+    pub x_ticks: Vec<TimeTick>,
+    pub price_ticks: Vec<ValueTick>,
+    pub volume_ticks: Vec<ValueTick>,
+    /// Width of a candle body in pixels
+    pub candle_width: f64,
+}
+
+/// Price chart of one asset's bars, with volume underneath when available
+#[derive(Debug, Clone)]
+pub struct CandlestickChart {
+    width: f64,
+    height: f64,
+    title: Option<String>,
+    candles: Vec<Candle>,
+    style: CandleStyle,
+    y_axis: YAxisMode,
+    margins: Margins,
+    theme: ChartTheme,
+    max_x_ticks: usize,
+    max_y_ticks: usize,
+}
+
+impl CandlestickChart {
+    /// Chart of `width` x `height` CSS pixels
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            title: None,
+            candles: Vec::new(),
+            style: CandleStyle::default(),
+            y_axis: YAxisMode::Linear,
+            margins: Margins::default(),
+            theme: ChartTheme::default(),
+            max_x_ticks: 8,
+            max_y_ticks: 6,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Candles in any order; they are drawn sorted by time
+    pub fn with_candles(mut self, mut candles: Vec<Candle>) -> Self {
+        candles.retain(Candle::is_valid);
+        candles.sort_by_key(|c| c.timestamp);
+        self.candles = candles;
+        self
+    }
+
+    pub fn with_style(mut self, style: CandleStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Linear or logarithmic price axis; `Percent` is treated as linear
+    pub fn with_y_axis(mut self, mode: YAxisMode) -> Self {
+        self.y_axis = match mode {
+            YAxisMode::Percent => YAxisMode::Linear,
+            mode => mode,
+        };
+        self
+    }
+
+    pub fn with_margins(mut self, margins: Margins) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: ChartTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    fn title_height(&self) -> f64 {
+        if self.title.is_some() {
+            self.theme.font_size * 1.5 + 8.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Layout for the current candles, or `None` if there are none
+    pub fn layout(&self) -> Option<CandlestickLayout> {
+        let first = self.candles.first()?;
+        let last = self.candles.last()?;
+        if self.y_axis == YAxisMode::Log && self.candles.iter().any(|c| c.low <= 0.0) {
+            return None;
+        }
+
+        let top = self.margins.top + self.title_height();
+        let width = (self.width - self.margins.left - self.margins.right).max(0.0);
+        let height = (self.height - top - self.margins.bottom).max(0.0);
+        let max_volume = self
+            .candles
+            .iter()
+            .filter_map(|c| c.volume)
+            .filter(|v| v.is_finite())
+            .fold(None, |max: Option<f64>, v| {
+                Some(max.map_or(v, |m| m.max(v)))
+            });
+
+        let (price_plot, volume_plot) = match max_volume {
+            Some(_) => {
+                let volume_height = height * VOLUME_PANE_RATIO;
+                let price_height = (height - volume_height - PANE_GAP).max(0.0);
+                (
+                    Rect {
+                        x: self.margins.left,
+                        y: top,
+                        width,
+                        height: price_height,
+                    },
+                    Some(Rect {
+                        x: self.margins.left,
+                        y: top + price_height + PANE_GAP,
+                        width,
+                        height: volume_height,
+                    }),
+                )
+            }
+            None => (
+                Rect {
+                    x: self.margins.left,
+                    y: top,
+                    width,
+                    height,
+                },
+                None,
+            ),
+        };
+
+        // Pad the time axis by half an interval so the outer candles fit
+        let t_min = first.timestamp.timestamp_millis() as f64;
+        let t_max = last.timestamp.timestamp_millis() as f64;
+        let n = self.candles.len() as f64;
+        let spacing = if n > 1.0 {
+            (t_max - t_min) / (n - 1.0)
+        } else {
+            86_400_000.0
+        };
+        let x_scale = LinearScale::new(
+            (t_min - spacing / 2.0, t_max + spacing / 2.0),
+            (price_plot.x, price_plot.right()),
+        );
+        let candle_width = (price_plot.width / n * 0.7).max(1.0);
+
+        let low = self
+            .candles
+            .iter()
+            .map(|c| c.low)
+            .fold(f64::INFINITY, f64::min);
+        let high = self
+            .candles
+            .iter()
+            .map(|c| c.high)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let (price_domain, price_ticks) = value_axis(self.y_axis, low, high, self.max_y_ticks);
+
+        let (volume_scale, volume_ticks) = match (volume_plot, max_volume) {
+            (Some(plot), Some(max)) => {
+                let max = if max > 0.0 { max } else { 1.0 };
+                let ticks = nice_ticks(0.0, max, 2)
+                    .into_iter()
+                    .map(|value| ValueTick {
+                        value,
+                        label: format_compact(value),
+                    })
+                    .collect();
+                (
+                    Some(LinearScale::new((0.0, max), (plot.bottom(), plot.y))),
+                    ticks,
+                )
+            }
+            _ => (None, Vec::new()),
+        };
+
+        let x_tick_budget = ((price_plot.width / 80.0) as usize).clamp(1, self.max_x_ticks);
+
+        Some(CandlestickLayout {
+            price_plot,
+            volume_plot,
+            x_scale,
+            price_scale: Scale::new(
+                self.y_axis,
+                price_domain,
+                (price_plot.bottom(), price_plot.y),
+            ),
+            volume_scale,
+            x_ticks: time_ticks(first.timestamp, last.timestamp, x_tick_budget),
+            price_ticks,
+            volume_ticks,
+            candle_width,
+        })
+    }
+
+    pub fn render(&self, canvas: &HtmlCanvasElement) -> Result<(), JsValue> {
+        self.draw(&mut CanvasRenderer::new(canvas, self.width, self.height)?)
+    }
+
+    pub fn to_svg(&self) -> String {
+        let mut svg = SvgRenderer::new(self.width, self.height);
+        let Ok(()) = self.draw(&mut svg);
+        svg.finish()
+    }
+
+    pub fn draw<R: Renderer>(&self, renderer: &mut R) -> Result<(), R::Error> {
+        let theme = &self.theme;
+        renderer.fill_rect(
+            Rect {
+                x: 0.0,
+                y: 0.0,
+                width: self.width,
+                height: self.height,
+            },
+            &theme.background,
+        )?;
+
+        if let Some(title) = &self.title {
+            let style = TextStyle {
+                font_size: theme.font_size * 1.25,
+                bold: true,
+                ..theme.text_style(TextAlign::Left, TextBaseline::Top)
+            };
+            renderer.fill_text(title, self.margins.left, self.margins.top, &style)?;
+        }
+
+        let Some(layout) = self.layout() else {
+            let style = theme.text_style(TextAlign::Center, TextBaseline::Middle);
+            return renderer.fill_text("No data", self.width / 2.0, self.height / 2.0, &style);
+        };
+        let price_plot = layout.price_plot;
+        let bottom_plot = layout.volume_plot.unwrap_or(price_plot);
+
+        // Grid lines sit on half pixels so one-pixel strokes stay crisp
+        let y_label_style = theme.text_style(TextAlign::Right, TextBaseline::Middle);
+        let volume_grid = layout
+            .volume_plot
+            .zip(layout.volume_scale)
+            .map(|(plot, scale)| (plot, Scale::Linear(scale), &layout.volume_ticks));
+        let value_grid = [(price_plot, layout.price_scale, &layout.price_ticks)]
+            .into_iter()
+            .chain(volume_grid);
+        for (plot, scale, ticks) in value_grid {
+            for tick in ticks {
+                let y = scale.map(tick.value).round() + 0.5;
+                renderer.stroke_polyline(&[(plot.x, y), (plot.right(), y)], &theme.grid, 1.0)?;
+                renderer.fill_text(&tick.label, plot.x - 8.0, y, &y_label_style)?;
+            }
+        }
+
+        let x_label_style = theme.text_style(TextAlign::Center, TextBaseline::Top);
+        for tick in &layout.x_ticks {
+            let x = layout
+                .x_scale
+                .map(tick.timestamp.timestamp_millis() as f64)
+                .round()
+                + 0.5;
+            renderer.stroke_polyline(
+                &[(x, price_plot.y), (x, price_plot.bottom())],
+                &theme.grid,
+                1.0,
+            )?;
+            if let Some(volume) = layout.volume_plot {
+                renderer.stroke_polyline(
+                    &[(x, volume.y), (x, volume.bottom())],
+                    &theme.grid,
+                    1.0,
+                )?;
+            }
+            renderer.fill_text(&tick.label, x, bottom_plot.bottom() + 8.0, &x_label_style)?;
+        }
+
+        for plot in [Some(price_plot), layout.volume_plot].into_iter().flatten() {
+            let axes = [
+                (plot.x + 0.5, plot.y),
+                (plot.x + 0.5, plot.bottom() + 0.5),
+                (plot.right(), plot.bottom() + 0.5),
+            ];
+            renderer.stroke_polyline(&axes, &theme.axis, 1.0)?;
+        }
+
+        renderer.push_clip(price_plot)?;
+        let half = layout.candle_width / 2.0;
+        for candle in &self.candles {
+            let color = if candle.is_up() {
+                &theme.up
+            } else {
+                &theme.down
+            };
+            let x = layout
+                .x_scale
+                .map(candle.timestamp.timestamp_millis() as f64);
+            let y = |v: f64| layout.price_scale.map(v);
+            renderer.stroke_polyline(&[(x, y(candle.high)), (x, y(candle.low))], color, 1.0)?;
+            match self.style {
+                CandleStyle::Candlestick => {
+                    let (top, bottom) = (
+                        y(candle.open.max(candle.close)),
+                        y(candle.open.min(candle.close)),
+                    );
+                    let body = Rect {
+                        x: x - half,
+                        y: top,
+                        width: layout.candle_width,
+                        height: (bottom - top).max(1.0),
+                    };
+                    renderer.fill_rect(body, color)?;
+                }
+                CandleStyle::Ohlc => {
+                    renderer.stroke_polyline(
+                        &[(x - half, y(candle.open)), (x, y(candle.open))],
+                        color,
+                        1.0,
+                    )?;
+                    renderer.stroke_polyline(
+                        &[(x, y(candle.close)), (x + half, y(candle.close))],
+                        color,
+                        1.0,
+                    )?;
+                }
+            }
+        }
+        renderer.pop_clip()?;
+
+        if let (Some(plot), Some(scale)) = (layout.volume_plot, layout.volume_scale) {
+            renderer.push_clip(plot)?;
+            for candle in &self.candles {
+                let Some(volume) = candle.volume.filter(|v| v.is_finite()) else {
+                    continue;
+                };
+                let color = if candle.is_up() {
+                    &theme.up
+                } else {
+                    &theme.down
+                };
+                let x = layout
+                    .x_scale
+                    .map(candle.timestamp.timestamp_millis() as f64);
+                let top = scale.map(volume);
+                let bar = [
+                    (x - half, plot.bottom()),
+                    (x - half, top),
+                    (x + half, top),
+                    (x + half, plot.bottom()),
+                ];
+                renderer.fill_polygon(&bar, color, 0.5)?;
+            }
+            renderer.pop_clip()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn candles(ohlc: &[(f64, f64, f64, f64)], with_volume: bool) -> Vec<Candle> {
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap();
+        ohlc.iter()
+            .enumerate()
+            .map(|(i, (open, high, low, close))| Candle {
+                timestamp: start + Duration::days(i as i64),
+                open: *open,
+                high: *high,
+                low: *low,
+                close: *close,
+                volume: with_volume.then_some(1_000.0 * (i + 1) as f64),
+            })
+            .collect()
+    }
+
+    const BARS: [(f64, f64, f64, f64); 5] = [
+        (100.0, 104.0, 98.0, 103.0),
+        (103.0, 106.0, 101.0, 102.0),
+        (102.0, 102.5, 95.0, 96.0),
+        (96.0, 99.0, 94.0, 98.5),
+        (98.5, 108.0, 98.0, 107.0),
+    ];
+
+    #[test]
+    fn test_layout_splits_price_and_volume_panes() {
+        let chart = CandlestickChart::new(600.0, 400.0).with_candles(candles(&BARS, true));
+        let layout = chart.layout().unwrap();
+
+        let volume = layout.volume_plot.unwrap();
+        assert!(volume.y > layout.price_plot.bottom());
+        assert_eq!(volume.bottom(), 400.0 - Margins::default().bottom);
+        assert_eq!(layout.price_scale.domain(), (90.0, 110.0));
+        assert_eq!(layout.volume_scale.unwrap().domain, (0.0, 5_000.0));
+
+        // Outer candles sit half an interval inside the plot edges
+        let first_x = layout
+            .x_scale
+            .map(candles(&BARS, true)[0].timestamp.timestamp_millis() as f64);
+        assert!((first_x - layout.price_plot.x - layout.price_plot.width / 10.0).abs() < 1e-9);
+
+        let without_volume =
+            CandlestickChart::new(600.0, 400.0).with_candles(candles(&BARS, false));
+        let layout = without_volume.layout().unwrap();
+        assert!(layout.volume_plot.is_none());
+        assert_eq!(
+            layout.price_plot.bottom(),
+            400.0 - Margins::default().bottom
+        );
+    }
+
+    #[test]
+    fn test_svg_snapshots() {
+        let chart = CandlestickChart::new(640.0, 360.0).with_candles(candles(&BARS, true));
+        crate::svg::assert_snapshot("candlestick_chart", &chart.to_svg());
+
+        let ohlc = chart.with_style(CandleStyle::Ohlc).to_svg();
+        crate::svg::assert_snapshot("ohlc_chart", &ohlc);
+    }
+}
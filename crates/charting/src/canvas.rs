@@ -0,0 +1,124 @@
+// Renderer drawing onto an HTML canvas
+
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use crate::renderer::{Rect, Renderer, TextAlign, TextBaseline, TextStyle};
+
+/// Draws through a canvas' 2d context
+pub struct CanvasRenderer {
+    ctx: CanvasRenderingContext2d,
+}
+
+impl CanvasRenderer {
+    /// Size `canvas` to `width` x `height` CSS pixels, with a backing store
+    /// scaled for the display's pixel ratio so lines stay sharp
+    pub fn new(canvas: &HtmlCanvasElement, width: f64, height: f64) -> Result<Self, JsValue> {
+        let ctx = canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("canvas has no 2d context"))?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+
+        let ratio = web_sys::window().map_or(1.0, |w| w.device_pixel_ratio());
+        canvas.set_width((width * ratio).round() as u32);
+        canvas.set_height((height * ratio).round() as u32);
+        canvas.set_attribute("style", &format!("width: {}px;", width))?;
+        ctx.scale(ratio, ratio)?;
+
+        Ok(Self { ctx })
+    }
+}
+
+impl Renderer for CanvasRenderer {
+    type Error = JsValue;
+
+    fn fill_rect(&mut self, rect: Rect, color: &str) -> Result<(), JsValue> {
+        self.ctx.set_fill_style_str(color);
+        self.ctx.fill_rect(rect.x, rect.y, rect.width, rect.height);
+        Ok(())
+    }
+
+    fn stroke_polyline(
+        &mut self,
+        points: &[(f64, f64)],
+        color: &str,
+        width: f64,
+    ) -> Result<(), JsValue> {
+        let Some(((x0, y0), rest)) = points.split_first() else {
+            return Ok(());
+        };
+        self.ctx.set_stroke_style_str(color);
+        self.ctx.set_line_width(width);
+        self.ctx.set_line_join("round");
+        self.ctx.begin_path();
+        self.ctx.move_to(*x0, *y0);
+        for (x, y) in rest {
+            self.ctx.line_to(*x, *y);
+        }
+        self.ctx.stroke();
+        Ok(())
+    }
+
+    fn fill_polygon(
+        &mut self,
+        points: &[(f64, f64)],
+        color: &str,
+        opacity: f64,
+    ) -> Result<(), JsValue> {
+        let Some(((x0, y0), rest)) = points.split_first() else {
+            return Ok(());
+        };
+        self.ctx.set_fill_style_str(color);
+        self.ctx.set_global_alpha(opacity);
+        self.ctx.begin_path();
+        self.ctx.move_to(*x0, *y0);
+        for (x, y) in rest {
+            self.ctx.line_to(*x, *y);
+        }
+        self.ctx.close_path();
+        self.ctx.fill();
+        self.ctx.set_global_alpha(1.0);
+        Ok(())
+    }
+
+    fn fill_circle(&mut self, x: f64, y: f64, radius: f64, color: &str) -> Result<(), JsValue> {
+        self.ctx.set_fill_style_str(color);
+        self.ctx.begin_path();
+        self.ctx.arc(x, y, radius, 0.0, std::f64::consts::TAU)?;
+        self.ctx.fill();
+        Ok(())
+    }
+
+    fn fill_text(&mut self, text: &str, x: f64, y: f64, style: &TextStyle) -> Result<(), JsValue> {
+        let weight = if style.bold { "bold " } else { "" };
+        self.ctx.set_font(&format!(
+            "{}{}px {}",
+            weight, style.font_size, style.font_family
+        ));
+        self.ctx.set_fill_style_str(style.color);
+        self.ctx.set_text_align(match style.align {
+            TextAlign::Left => "left",
+            TextAlign::Center => "center",
+            TextAlign::Right => "right",
+        });
+        self.ctx.set_text_baseline(match style.baseline {
+            TextBaseline::Top => "top",
+            TextBaseline::Middle => "middle",
+            TextBaseline::Bottom => "bottom",
+        });
+        self.ctx.fill_text(text, x, y)
+    }
+
+    fn push_clip(&mut self, rect: Rect) -> Result<(), JsValue> {
+        self.ctx.save();
+        self.ctx.begin_path();
+        self.ctx.rect(rect.x, rect.y, rect.width, rect.height);
+        self.ctx.clip();
+        Ok(())
+    }
+
+    fn pop_clip(&mut self) -> Result<(), JsValue> {
+        self.ctx.restore();
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+// Point reduction for long series, keeping the visual shape of the line
+
+use shared::DownsampleMethod;
+
+/// Indices (ascending) of at most `max_points` points to keep, though never
+/// fewer than 3; `xy` gives each point's position. All indices are returned
+/// if no reduction is needed.
+pub fn downsample<T>(
+    method: DownsampleMethod,
+    points: &[T],
+    max_points: usize,
+    xy: impl Fn(&T) -> (f64, f64),
+) -> Vec<usize> {
+    match method {
+        // Too few points for a low and a high per bucket; LTTB keeps the
+        // single most visible one instead
+        DownsampleMethod::MinMax if max_points < 4 => lttb(points, max_points, xy),
+        DownsampleMethod::MinMax => min_max(points, max_points, xy),
+        DownsampleMethod::Lttb => lttb(points, max_points, xy),
+    }
+}
+
+/// Largest-Triangle-Three-Buckets (Steinarsson, 2013). Always keeps the first
+/// and last points; `max_points` below 3 is treated as 3.
+pub fn lttb<T>(points: &[T], max_points: usize, xy: impl Fn(&T) -> (f64, f64)) -> Vec<usize> {
+    let n = points.len();
+    let max_points = max_points.max(3);
+    if n <= max_points {
+        return (0..n).collect();
+    }
+
+    // Interior points split into max_points - 2 buckets, one kept per bucket
+    let every = (n - 2) as f64 / (max_points - 2) as f64;
+    let bucket_start = |i: usize| ((i as f64 * every) as usize + 1).min(n - 1);
+
+    let mut kept = Vec::with_capacity(max_points);
+    kept.push(0);
+    let mut a = 0;
+    for i in 0..max_points - 2 {
+        // Average of the next bucket (or the last point) is the third vertex
+        let (next_start, next_end) = if i + 1 < max_points - 2 {
+            (bucket_start(i + 1), bucket_start(i + 2))
+        } else {
+            (n - 1, n)
+        };
+        let (sum_x, sum_y) = points[next_start..next_end]
+            .iter()
+            .map(&xy)
+            .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+        let count = (next_end - next_start) as f64;
+        let (avg_x, avg_y) = (sum_x / count, sum_y / count);
+
+        let (ax, ay) = xy(&points[a]);
+        let best = (bucket_start(i)..bucket_start(i + 1))
+            .map(|j| {
+                let (x, y) = xy(&points[j]);
+                let area = ((ax - avg_x) * (y - ay) - (ax - x) * (avg_y - ay)).abs();
+                (j, area)
+            })
+            .fold((bucket_start(i), -1.0), |best, candidate| {
+                if candidate.1 > best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            })
+            .0;
+        kept.push(best);
+        a = best;
+    }
+    kept.push(n - 1);
+    kept
+}
+
+/// First and last points plus the lowest and highest point of each of
+/// `(max_points - 2) / 2` equal-sized buckets, in their original order;
+/// `max_points` below 4 is treated as 4.
+pub fn min_max<T>(points: &[T], max_points: usize, xy: impl Fn(&T) -> (f64, f64)) -> Vec<usize> {
+    let n = points.len();
+    if n <= max_points.max(4) {
+        return (0..n).collect();
+    }
+
+    let buckets = ((max_points.max(4) - 2) / 2).max(1);
+    let interior = n - 2;
+    let mut kept = Vec::with_capacity(buckets * 2 + 2);
+    kept.push(0);
+    for b in 0..buckets {
+        let start = 1 + b * interior / buckets;
+        let end = 1 + (b + 1) * interior / buckets;
+        if start == end {
+            continue;
+        }
+        let y = |j: &usize| xy(&points[*j]).1;
+        let lowest = (start..end).min_by(|i, j| y(i).total_cmp(&y(j))).unwrap();
+        let highest = (start..end).max_by(|i, j| y(i).total_cmp(&y(j))).unwrap();
+        kept.push(lowest.min(highest));
+        if lowest != highest {
+            kept.push(lowest.max(highest));
+        }
+    }
+    kept.push(n - 1);
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xy(p: &(f64, f64)) -> (f64, f64) {
+        *p
+    }
+
+    #[test]
+    fn test_lttb_keeps_peaks_and_endpoints() {
+        let mut points: Vec<(f64, f64)> = (0..1_000)
+            .map(|i| (i as f64, (i % 7) as f64 * 0.01))
+            .collect();
+        points[400].1 = 50.0;
+        points[700].1 = -50.0;
+
+        let kept = lttb(&points, 100, xy);
+        assert_eq!(kept.len(), 100);
+        assert_eq!(kept[0], 0);
+        assert_eq!(kept[99], 999);
+        assert!(kept.windows(2).all(|w| w[0] < w[1]));
+        assert!(kept.contains(&400));
+        assert!(kept.contains(&700));
+
+        assert_eq!(lttb(&points[..50], 100, xy), (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_min_max_keeps_every_extreme() {
+        let points: Vec<(f64, f64)> = (0..1_000)
+            .map(|i| (i as f64, ((i * 37) % 101) as f64))
+            .collect();
+
+        let kept = downsample(DownsampleMethod::MinMax, &points, 50, xy);
+        assert!(kept.len() <= 50);
+        assert!(kept.windows(2).all(|w| w[0] < w[1]));
+        let max = points.iter().map(|p| p.1).fold(f64::MIN, f64::max);
+        let min = points.iter().map(|p| p.1).fold(f64::MAX, f64::min);
+        assert!(kept.iter().any(|i| points[*i].1 == max));
+        assert!(kept.iter().any(|i| points[*i].1 == min));
+    }
+
+    #[test]
+    fn test_min_max_respects_small_limits() {
+        let points: Vec<(f64, f64)> = (0..100).map(|i| (i as f64, (i % 9) as f64)).collect();
+
+        let kept = downsample(DownsampleMethod::MinMax, &points, 3, xy);
+        assert_eq!(kept.len(), 3);
+        assert_eq!((kept[0], kept[2]), (0, 99));
+        assert_eq!(downsample(DownsampleMethod::MinMax, &points, 4, xy).len(), 4);
+        assert_eq!(downsample(DownsampleMethod::MinMax, &points[..3], 3, xy), vec![0, 1, 2]);
+    }
+}
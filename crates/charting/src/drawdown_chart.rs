@@ -0,0 +1,185 @@
+// Drawdown (underwater) chart: percent below the running peak over time
+
+use chrono::{DateTime, Utc};
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+
+use crate::interaction::Overlay;
+use crate::line_chart::{Annotation, ChartTheme, LineChart, Series, DEFAULT_PALETTE};
+use crate::renderer::Renderer;
+use crate::scale::YAxisMode;
+
+/// Percent below the running peak at each point, 0 at new highs. Values
+/// that are not finite or not positive stay as gaps and don't move the peak.
+pub fn drawdown_series(points: &[(DateTime<Utc>, f64)]) -> Vec<(DateTime<Utc>, f64)> {
+    let mut peak = f64::NEG_INFINITY;
+    points
+        .iter()
+        .map(|(t, v)| {
+            if !v.is_finite() || *v <= 0.0 {
+                return (*t, f64::NAN);
+            }
+            peak = peak.max(*v);
+            (*t, (v / peak - 1.0) * 100.0)
+        })
+        .collect()
+}
+
+/// Deepest point of a drawdown series from `drawdown_series`, with its date
+pub fn max_drawdown(drawdowns: &[(DateTime<Utc>, f64)]) -> Option<(DateTime<Utc>, f64)> {
+    drawdowns
+        .iter()
+        .filter(|(_, d)| d.is_finite())
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .copied()
+}
+
+/// Filled drawdown areas for several series on one percent axis, with each
+/// series' maximum drawdown marked
+#[derive(Debug, Clone)]
+pub struct DrawdownChart {
+    width: f64,
+    height: f64,
+    title: Option<String>,
+    theme: ChartTheme,
+    x_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    overlay: Overlay,
+    series: Vec<Series>,
+}
+
+impl DrawdownChart {
+    /// Chart of `width` x `height` CSS pixels
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            title: None,
+            theme: ChartTheme::default(),
+            x_range: None,
+            overlay: Overlay::default(),
+            series: Vec::new(),
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_theme(mut self, theme: ChartTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn with_x_range(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        self.x_range = Some((start, end));
+        self
+    }
+
+    pub fn with_overlay(mut self, overlay: Overlay) -> Self {
+        self.overlay = overlay;
+        self
+    }
+
+    /// Add a series of values (prices or normalized values, not returns)
+    pub fn with_series(mut self, series: Series) -> Self {
+        self.add_series(series);
+        self
+    }
+
+    pub fn add_series(&mut self, series: Series) {
+        self.series.push(series);
+    }
+
+    /// Line chart of the drawdowns, which handles layout, interaction and
+    /// drawing
+    pub fn line_chart(&self) -> LineChart {
+        let mut chart = LineChart::new(self.width, self.height)
+            .with_theme(self.theme.clone())
+            .with_y_axis(YAxisMode::Percent)
+            .with_area_fill(0.0)
+            .with_overlay(self.overlay);
+        if let Some(title) = &self.title {
+            chart = chart.with_title(title.clone());
+        }
+        if let Some((start, end)) = self.x_range {
+            chart = chart.with_x_range(start, end);
+        }
+
+        for (index, series) in self.series.iter().enumerate() {
+            let color = series
+                .color
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PALETTE[index % DEFAULT_PALETTE.len()].to_string());
+            let drawdowns = drawdown_series(&series.points);
+            if let Some((timestamp, value)) = max_drawdown(&drawdowns) {
+                chart = chart.with_annotation(Annotation {
+                    timestamp,
+                    value,
+                    label: format!("{} {:.1}%", series.name, value),
+                    color: color.clone(),
+                });
+            }
+            chart = chart.with_series(Series {
+                name: series.name.clone(),
+                color: Some(color),
+                points: drawdowns,
+            });
+        }
+        chart
+    }
+
+    pub fn render(&self, canvas: &HtmlCanvasElement) -> Result<(), JsValue> {
+        self.line_chart().render(canvas)
+    }
+
+    pub fn to_svg(&self) -> String {
+        self.line_chart().to_svg()
+    }
+
+    pub fn draw<R: Renderer>(&self, renderer: &mut R) -> Result<(), R::Error> {
+        self.line_chart().draw(renderer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn points(values: &[f64]) -> Vec<(DateTime<Utc>, f64)> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (start + Duration::days(i as i64), *v))
+            .collect()
+    }
+
+    #[test]
+    fn test_drawdown_series() {
+        let drawdowns = drawdown_series(&points(&[100.0, 120.0, 90.0, f64::NAN, 60.0, 130.0]));
+        let values: Vec<f64> = drawdowns.iter().map(|(_, d)| *d).collect();
+        assert_eq!(values[..3], [0.0, 0.0, -25.0]);
+        assert!(values[3].is_nan());
+        assert_eq!(values[4..], [-50.0, 0.0]);
+
+        let (when, deepest) = max_drawdown(&drawdowns).unwrap();
+        assert_eq!(deepest, -50.0);
+        assert_eq!(when, drawdowns[4].0);
+    }
+
+    #[test]
+    fn test_svg_snapshot() {
+        let chart = DrawdownChart::new(640.0, 320.0)
+            .with_series(Series::new(
+                "QQQ",
+                points(&[100.0, 104.0, 97.0, 92.0, 99.0, 106.0]),
+            ))
+            .with_series(Series::new(
+                "BTC",
+                points(&[100.0, 80.0, 120.0, 70.0, 85.0, 95.0]),
+            ));
+        crate::svg::assert_snapshot("drawdown_chart", &chart.to_svg());
+    }
+}
@@ -0,0 +1,428 @@
+// Labelled matrix heatmap, e.g. for asset return correlations
+
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+
+use crate::canvas::CanvasRenderer;
+use crate::line_chart::{ChartTheme, Margins};
+use crate::renderer::{Rect, Renderer, TextAlign, TextBaseline, TextStyle};
+use crate::svg::SvgRenderer;
+
+/// Number of strips the color legend is drawn with
+const LEGEND_STEPS: usize = 40;
+
+/// Width of the color legend bar
+const LEGEND_WIDTH: f64 = 14.0;
+
+/// Colors for values below, at and above the midpoint of the range
+#[derive(Debug, Clone, PartialEq)]
+pub struct DivergingColors {
+    pub negative: String,
+    pub neutral: String,
+    pub positive: String,
+}
+
+impl Default for DivergingColors {
+    fn default() -> Self {
+        Self {
+            negative: "#ef4444".to_string(),
+            neutral: "#f8fafc".to_string(),
+            positive: "#3b82f6".to_string(),
+        }
+    }
+}
+
+impl DivergingColors {
+    /// Hex color for `t` in `[-1, 1]`, blended linearly through the neutral
+    /// color; values outside the range are clamped
+    pub fn color(&self, t: f64) -> String {
+        let t = t.clamp(-1.0, 1.0);
+        let (from, to) = if t < 0.0 {
+            (&self.neutral, &self.negative)
+        } else {
+            (&self.neutral, &self.positive)
+        };
+        match (parse_hex(from), parse_hex(to)) {
+            (Some(a), Some(b)) => {
+                let mix = |i: usize| (a[i] as f64 + (b[i] as f64 - a[i] as f64) * t.abs()).round();
+                format!(
+                    "#{:02x}{:02x}{:02x}",
+                    mix(0) as u8,
+                    mix(1) as u8,
+                    mix(2) as u8
+                )
+            }
+            // Named or rgba() colors can't be blended; pick the nearest end
+            _ if t.abs() < 0.5 => from.clone(),
+            _ => to.clone(),
+        }
+    }
+}
+
+fn parse_hex(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Positions for one render of a `HeatmapChart`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapLayout {
+    /// Area covered by the cells
+    pub grid: Rect,
+    pub cell_width: f64,
+    pub cell_height: f64,
+    /// Color legend bar to the right of the grid
+    pub legend: Rect,
+}
+
+/// Matrix of values with row and column labels, colored on a diverging
+/// scale around the midpoint of its range
+#[derive(Debug, Clone)]
+pub struct HeatmapChart {
+    width: f64,
+    height: f64,
+    title: Option<String>,
+    row_labels: Vec<String>,
+    column_labels: Vec<String>,
+    values: Vec<Vec<f64>>,
+    range: (f64, f64),
+    decimals: usize,
+    show_values: bool,
+    colors: DivergingColors,
+    margins: Margins,
+    theme: ChartTheme,
+}
+
+impl HeatmapChart {
+    /// Chart of `width` x `height` CSS pixels
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            title: None,
+            row_labels: Vec::new(),
+            column_labels: Vec::new(),
+            values: Vec::new(),
+            range: (-1.0, 1.0),
+            decimals: 2,
+            show_values: true,
+            colors: DivergingColors::default(),
+            margins: Margins {
+                left: 16.0,
+                right: 64.0,
+                ..Margins::default()
+            },
+            theme: ChartTheme::default(),
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Square matrix whose rows and columns share `labels`, such as a
+    /// correlation matrix
+    pub fn with_matrix(mut self, labels: Vec<String>, values: Vec<Vec<f64>>) -> Self {
+        self.column_labels = labels.clone();
+        self.row_labels = labels;
+        self.values = values;
+        self
+    }
+
+    /// Matrix with separate row and column labels; `values[row][column]`
+    pub fn with_grid(
+        mut self,
+        row_labels: Vec<String>,
+        column_labels: Vec<String>,
+        values: Vec<Vec<f64>>,
+    ) -> Self {
+        self.row_labels = row_labels;
+        self.column_labels = column_labels;
+        self.values = values;
+        self
+    }
+
+    /// Values mapped to the ends of the color scale; defaults to `-1..1`
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.range = (min, max);
+        self
+    }
+
+    /// Print each cell's value with `decimals` decimals, or `None` to leave
+    /// the cells blank
+    pub fn with_cell_values(mut self, decimals: Option<usize>) -> Self {
+        self.show_values = decimals.is_some();
+        self.decimals = decimals.unwrap_or(self.decimals);
+        self
+    }
+
+    pub fn with_colors(mut self, colors: DivergingColors) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    pub fn with_margins(mut self, margins: Margins) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: ChartTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    fn title_height(&self) -> f64 {
+        if self.title.is_some() {
+            self.theme.font_size * 1.5 + 8.0
+        } else {
+            0.0
+        }
+    }
+
+    fn value(&self, row: usize, column: usize) -> f64 {
+        self.values
+            .get(row)
+            .and_then(|r| r.get(column))
+            .copied()
+            .unwrap_or(f64::NAN)
+    }
+
+    /// Position of `value` on the color scale, from -1 at the low end of
+    /// the range to 1 at the high end
+    fn scale_position(&self, value: f64) -> f64 {
+        let (lo, hi) = self.range;
+        if hi <= lo {
+            return 0.0;
+        }
+        (value - lo) / (hi - lo) * 2.0 - 1.0
+    }
+
+    /// Cell text that stays readable on both pale and saturated cells
+    fn text_color(&self, t: f64) -> &str {
+        if t.abs() > 0.5 {
+            "#f8fafc"
+        } else {
+            "#0f172a"
+        }
+    }
+
+    /// Layout for the current matrix, or `None` if it is empty
+    pub fn layout(&self) -> Option<HeatmapLayout> {
+        if self.row_labels.is_empty() || self.column_labels.is_empty() {
+            return None;
+        }
+
+        let label_width = |labels: &[String]| {
+            labels
+                .iter()
+                .map(|l| self.theme.text_width(l))
+                .fold(0.0, f64::max)
+        };
+        let left = self.margins.left + label_width(&self.row_labels) + 8.0;
+        let top = self.margins.top + self.title_height() + self.theme.font_size + 8.0;
+        let width = (self.width - left - self.margins.right).max(0.0);
+        let height = (self.height - top - self.margins.bottom).max(0.0);
+
+        let rows = self.row_labels.len() as f64;
+        let columns = self.column_labels.len() as f64;
+        // Square cells unless that would squeeze them below the text height
+        let side = (width / columns).min(height / rows);
+        let (cell_width, cell_height) = if side >= self.theme.font_size * 1.5 {
+            (side, side)
+        } else {
+            (width / columns, height / rows)
+        };
+
+        let grid = Rect {
+            x: left,
+            y: top,
+            width: cell_width * columns,
+            height: cell_height * rows,
+        };
+        let legend = Rect {
+            x: grid.right() + 16.0,
+            y: grid.y,
+            width: LEGEND_WIDTH,
+            height: grid.height,
+        };
+        Some(HeatmapLayout {
+            grid,
+            cell_width,
+            cell_height,
+            legend,
+        })
+    }
+
+    pub fn render(&self, canvas: &HtmlCanvasElement) -> Result<(), JsValue> {
+        self.draw(&mut CanvasRenderer::new(canvas, self.width, self.height)?)
+    }
+
+    pub fn to_svg(&self) -> String {
+        let mut svg = SvgRenderer::new(self.width, self.height);
+        let Ok(()) = self.draw(&mut svg);
+        svg.finish()
+    }
+
+    pub fn draw<R: Renderer>(&self, renderer: &mut R) -> Result<(), R::Error> {
+        let theme = &self.theme;
+        renderer.fill_rect(
+            Rect {
+                x: 0.0,
+                y: 0.0,
+                width: self.width,
+                height: self.height,
+            },
+            &theme.background,
+        )?;
+
+        if let Some(title) = &self.title {
+            let style = TextStyle {
+                font_size: theme.font_size * 1.25,
+                bold: true,
+                ..theme.text_style(TextAlign::Left, TextBaseline::Top)
+            };
+            renderer.fill_text(title, self.margins.left, self.margins.top, &style)?;
+        }
+
+        let Some(layout) = self.layout() else {
+            let style = theme.text_style(TextAlign::Center, TextBaseline::Middle);
+            return renderer.fill_text("No data", self.width / 2.0, self.height / 2.0, &style);
+        };
+        let grid = layout.grid;
+
+        let column_style = theme.text_style(TextAlign::Center, TextBaseline::Bottom);
+        for (column, label) in self.column_labels.iter().enumerate() {
+            let x = grid.x + (column as f64 + 0.5) * layout.cell_width;
+            renderer.fill_text(label, x, grid.y - 6.0, &column_style)?;
+        }
+        let row_style = theme.text_style(TextAlign::Right, TextBaseline::Middle);
+        for (row, label) in self.row_labels.iter().enumerate() {
+            let y = grid.y + (row as f64 + 0.5) * layout.cell_height;
+            renderer.fill_text(label, grid.x - 8.0, y, &row_style)?;
+        }
+
+        for row in 0..self.row_labels.len() {
+            for column in 0..self.column_labels.len() {
+                let value = self.value(row, column);
+                let t = self.scale_position(value);
+                // Leave a one-pixel gap between cells
+                let cell = Rect {
+                    x: grid.x + column as f64 * layout.cell_width,
+                    y: grid.y + row as f64 * layout.cell_height,
+                    width: (layout.cell_width - 1.0).max(0.0),
+                    height: (layout.cell_height - 1.0).max(0.0),
+                };
+                if !value.is_finite() {
+                    renderer.fill_rect(cell, &theme.grid)?;
+                    continue;
+                }
+                renderer.fill_rect(cell, &self.colors.color(t))?;
+                if self.show_values && cell.height >= theme.font_size {
+                    let style = TextStyle {
+                        color: self.text_color(t),
+                        ..theme.text_style(TextAlign::Center, TextBaseline::Middle)
+                    };
+                    let text = format!("{:.*}", self.decimals, value);
+                    if theme.text_width(&text) <= cell.width {
+                        let (x, y) = (cell.x + cell.width / 2.0, cell.y + cell.height / 2.0);
+                        renderer.fill_text(&text, x, y, &style)?;
+                    }
+                }
+            }
+        }
+
+        self.draw_legend(renderer, &layout)
+    }
+
+    /// Vertical color bar from the top of the range down to the bottom,
+    /// labelled at both ends and the midpoint
+    fn draw_legend<R: Renderer>(
+        &self,
+        renderer: &mut R,
+        layout: &HeatmapLayout,
+    ) -> Result<(), R::Error> {
+        let legend = layout.legend;
+        let strip = legend.height / LEGEND_STEPS as f64;
+        for step in 0..LEGEND_STEPS {
+            let t = 1.0 - (step as f64 + 0.5) / LEGEND_STEPS as f64 * 2.0;
+            let rect = Rect {
+                y: legend.y + step as f64 * strip,
+                // Overlap strips slightly so no seams show between them
+                height: strip + 0.5,
+                ..legend
+            };
+            renderer.fill_rect(rect, &self.colors.color(t))?;
+        }
+
+        let (lo, hi) = self.range;
+        let style = self.theme.text_style(TextAlign::Left, TextBaseline::Middle);
+        let x = legend.right() + 6.0;
+        for (value, y) in [
+            (hi, legend.y),
+            ((lo + hi) / 2.0, legend.y + legend.height / 2.0),
+            (lo, legend.bottom()),
+        ] {
+            renderer.fill_text(&format!("{:.*}", self.decimals, value), x, y, &style)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels() -> Vec<String> {
+        ["SPY", "QQQ", "BTC"].map(String::from).to_vec()
+    }
+
+    #[test]
+    fn test_diverging_colors() {
+        let colors = DivergingColors::default();
+        assert_eq!(colors.color(-1.0), "#ef4444");
+        assert_eq!(colors.color(0.0), "#f8fafc");
+        assert_eq!(colors.color(1.0), "#3b82f6");
+        assert_eq!(colors.color(4.0), "#3b82f6");
+        // Halfway between #f8fafc and #3b82f6
+        assert_eq!(colors.color(0.5), "#9abef9");
+    }
+
+    #[test]
+    fn test_layout_uses_square_cells() {
+        let chart = HeatmapChart::new(500.0, 400.0).with_matrix(
+            labels(),
+            vec![
+                vec![1.0, 0.9, 0.3],
+                vec![0.9, 1.0, 0.35],
+                vec![0.3, 0.35, 1.0],
+            ],
+        );
+        let layout = chart.layout().unwrap();
+        assert_eq!(layout.cell_width, layout.cell_height);
+        assert!(layout.legend.x > layout.grid.right());
+        assert!(layout.legend.right() <= 500.0);
+        assert!(layout.grid.bottom() <= 400.0);
+
+        assert!(HeatmapChart::new(500.0, 400.0).layout().is_none());
+    }
+
+    #[test]
+    fn test_svg_snapshot() {
+        let chart = HeatmapChart::new(480.0, 320.0)
+            .with_title("Correlation")
+            .with_matrix(
+                labels(),
+                vec![
+                    vec![1.0, 0.92, 0.31],
+                    vec![0.92, 1.0, f64::NAN],
+                    vec![0.31, f64::NAN, 1.0],
+                ],
+            );
+        crate::svg::assert_snapshot("heatmap", &chart.to_svg());
+    }
+}
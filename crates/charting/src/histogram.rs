@@ -0,0 +1,502 @@
+// Histogram and density chart for distributions such as periodic returns
+
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+
+use crate::canvas::CanvasRenderer;
+use crate::line_chart::{
+    draw_legend, layout_legend, ChartTheme, LegendEntry, Margins, DEFAULT_PALETTE,
+};
+use crate::renderer::{Rect, Renderer, TextAlign, TextBaseline, TextStyle};
+use crate::scale::{LinearScale, YAxisMode};
+use crate::svg::SvgRenderer;
+use crate::ticks::{format_tick, nice_step, nice_ticks, value_axis, ValueTick};
+
+/// Opacity of histogram bars, low enough for overlaid series to show through
+const BAR_OPACITY: f64 = 0.35;
+
+/// Points sampled along the x axis for each density curve
+const CURVE_SAMPLES: usize = 120;
+
+/// Bin edges at whole multiples of a 1-2-5 step, giving at most about
+/// `max_bins` bins across `[min, max]`
+pub fn bin_edges(min: f64, max: f64, max_bins: usize) -> Vec<f64> {
+    if !min.is_finite() || !max.is_finite() {
+        return Vec::new();
+    }
+    let step = nice_step(min, max, max_bins.max(1));
+    if step == 0.0 {
+        // All values equal: one bin around them
+        let pad = if min == 0.0 { 0.5 } else { min.abs() * 0.05 };
+        return vec![min - pad, min + pad];
+    }
+    let first = (min / step).floor() as i64;
+    let last = ((max / step).ceil() as i64).max(first + 1);
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+/// Number of finite `values` in each bin between consecutive `edges`. Bins
+/// include their lower edge; the last also includes its upper edge.
+pub fn bin_counts(values: &[f64], edges: &[f64]) -> Vec<usize> {
+    let mut counts = vec![0; edges.len().saturating_sub(1)];
+    // Fewer than two edges make no bins
+    if counts.is_empty() {
+        return counts;
+    }
+    let (lo, hi) = (&edges[0], &edges[edges.len() - 1]);
+    for value in values.iter().filter(|v| v.is_finite()) {
+        if value < lo || value > hi {
+            continue;
+        }
+        let bin = edges
+            .partition_point(|edge| edge <= value)
+            .saturating_sub(1);
+        let last = counts.len() - 1;
+        counts[bin.min(last)] += 1;
+    }
+    counts
+}
+
+/// Gaussian kernel density estimate of `values` at each of `at`, with the
+/// bandwidth from Silverman's rule of thumb
+pub fn kernel_density(values: &[f64], at: &[f64]) -> Vec<f64> {
+    let values: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    let n = values.len() as f64;
+    if values.len() < 2 {
+        return vec![0.0; at.len()];
+    }
+    let mean = values.iter().sum::<f64>() / n;
+    let std_dev = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    if std_dev == 0.0 {
+        return vec![0.0; at.len()];
+    }
+
+    let bandwidth = 1.06 * std_dev * n.powf(-0.2);
+    let norm = 1.0 / (n * bandwidth * (2.0 * std::f64::consts::PI).sqrt());
+    at.iter()
+        .map(|x| {
+            values
+                .iter()
+                .map(|v| (-0.5 * ((x - v) / bandwidth).powi(2)).exp())
+                .sum::<f64>()
+                * norm
+        })
+        .collect()
+}
+
+/// Named sample of values, e.g. one asset's daily returns
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution {
+    pub name: String,
+    /// Defaults to the palette color for the distribution's position
+    pub color: Option<String>,
+    pub values: Vec<f64>,
+}
+
+impl Distribution {
+    pub fn new(name: impl Into<String>, values: Vec<f64>) -> Self {
+        Self {
+            name: name.into(),
+            color: None,
+            values,
+        }
+    }
+
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+}
+
+/// What the bar heights measure
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HistogramScale {
+    /// Number of values in each bin
+    Count,
+    /// Share of values per unit of x, so samples of different sizes are
+    /// comparable and density curves line up with the bars
+    #[default]
+    Density,
+}
+
+/// Positions and scales for one render of a `HistogramChart`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramLayout {
+    pub plot: Rect,
+    pub x_scale: LinearScale,
+    pub y_scale: LinearScale,
+    pub x_ticks: Vec<ValueTick>,
+    pub y_ticks: Vec<ValueTick>,
+    pub legend: Vec<LegendEntry>,
+    /// Bin edges shared by every distribution
+    pub edges: Vec<f64>,
+}
+
+/// Overlaid histograms of several distributions on shared bins, with
+/// optional kernel density curves
+#[derive(Debug, Clone)]
+pub struct HistogramChart {
+    width: f64,
+    height: f64,
+    title: Option<String>,
+    distributions: Vec<Distribution>,
+    max_bins: usize,
+    scale: HistogramScale,
+    density_curves: bool,
+    value_suffix: String,
+    margins: Margins,
+    theme: ChartTheme,
+    max_x_ticks: usize,
+    max_y_ticks: usize,
+}
+
+impl HistogramChart {
+    /// Chart of `width` x `height` CSS pixels
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            title: None,
+            distributions: Vec::new(),
+            max_bins: 30,
+            scale: HistogramScale::default(),
+            density_curves: true,
+            value_suffix: String::new(),
+            margins: Margins::default(),
+            theme: ChartTheme::default(),
+            max_x_ticks: 8,
+            max_y_ticks: 6,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Upper bound on the number of bins; the actual count depends on
+    /// rounding the bin width to a 1-2-5 step
+    pub fn with_max_bins(mut self, max_bins: usize) -> Self {
+        self.max_bins = max_bins.max(1);
+        self
+    }
+
+    pub fn with_scale(mut self, scale: HistogramScale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Draw a kernel density curve over each histogram; only applies to the
+    /// `Density` scale
+    pub fn with_density_curves(mut self, enabled: bool) -> Self {
+        self.density_curves = enabled;
+        self
+    }
+
+    /// Appended to x axis labels, e.g. "%" for percentage returns
+    pub fn with_value_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.value_suffix = suffix.into();
+        self
+    }
+
+    pub fn with_margins(mut self, margins: Margins) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: ChartTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn with_distribution(mut self, distribution: Distribution) -> Self {
+        self.distributions.push(distribution);
+        self
+    }
+
+    pub fn add_distribution(&mut self, distribution: Distribution) {
+        self.distributions.push(distribution);
+    }
+
+    fn color(&self, index: usize) -> String {
+        self.distributions[index]
+            .color
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PALETTE[index % DEFAULT_PALETTE.len()].to_string())
+    }
+
+    fn title_height(&self) -> f64 {
+        if self.title.is_some() {
+            self.theme.font_size * 1.5 + 8.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Bar heights of one distribution on `edges`, in the chart's scale
+    fn heights(&self, distribution: &Distribution, edges: &[f64]) -> Vec<f64> {
+        let counts = bin_counts(&distribution.values, edges);
+        let total: usize = counts.iter().sum();
+        counts
+            .iter()
+            .zip(edges.windows(2))
+            .map(|(count, edge)| match self.scale {
+                HistogramScale::Count => *count as f64,
+                HistogramScale::Density if total > 0 => {
+                    *count as f64 / total as f64 / (edge[1] - edge[0])
+                }
+                HistogramScale::Density => 0.0,
+            })
+            .collect()
+    }
+
+    /// Layout for the current distributions, or `None` if they hold no
+    /// finite values
+    pub fn layout(&self) -> Option<HistogramLayout> {
+        let (min, max) = self
+            .distributions
+            .iter()
+            .flat_map(|d| d.values.iter())
+            .filter(|v| v.is_finite())
+            .fold(None, |bounds: Option<(f64, f64)>, v| {
+                Some(bounds.map_or((*v, *v), |(lo, hi)| (lo.min(*v), hi.max(*v))))
+            })?;
+        let edges = bin_edges(min, max, self.max_bins);
+        let (x_min, x_max) = (*edges.first()?, *edges.last()?);
+
+        let mut y_max = self
+            .distributions
+            .iter()
+            .flat_map(|d| self.heights(d, &edges))
+            .fold(0.0, f64::max);
+        if self.scale == HistogramScale::Density && self.density_curves {
+            let xs = curve_samples(x_min, x_max);
+            for distribution in &self.distributions {
+                y_max = kernel_density(&distribution.values, &xs)
+                    .into_iter()
+                    .fold(y_max, f64::max);
+            }
+        }
+
+        let items = self
+            .distributions
+            .iter()
+            .enumerate()
+            .map(|(index, d)| (d.name.clone(), self.color(index)));
+        let legend = layout_legend(
+            &self.theme,
+            self.margins.left,
+            self.width - self.margins.right,
+            self.margins.top + self.title_height(),
+            items,
+        );
+        let top = legend
+            .last()
+            .map_or(self.margins.top + self.title_height(), |last| {
+                last.y + self.theme.legend_row_height() + 8.0
+            });
+        let plot = Rect {
+            x: self.margins.left,
+            y: top,
+            width: (self.width - self.margins.left - self.margins.right).max(0.0),
+            height: (self.height - top - self.margins.bottom).max(0.0),
+        };
+
+        let (y_domain, y_ticks) = value_axis(YAxisMode::Linear, 0.0, y_max, self.max_y_ticks);
+        let x_tick_budget = ((plot.width / 60.0) as usize).clamp(1, self.max_x_ticks);
+        let x_step = nice_step(x_min, x_max, x_tick_budget);
+        let x_ticks = nice_ticks(x_min, x_max, x_tick_budget)
+            .into_iter()
+            .map(|value| ValueTick {
+                value,
+                label: format!("{}{}", format_tick(value, x_step), self.value_suffix),
+            })
+            .collect();
+
+        Some(HistogramLayout {
+            plot,
+            x_scale: LinearScale::new((x_min, x_max), (plot.x, plot.right())),
+            y_scale: LinearScale::new((0.0, y_domain.1), (plot.bottom(), plot.y)),
+            x_ticks,
+            y_ticks,
+            legend,
+            edges,
+        })
+    }
+
+    pub fn render(&self, canvas: &HtmlCanvasElement) -> Result<(), JsValue> {
+        self.draw(&mut CanvasRenderer::new(canvas, self.width, self.height)?)
+    }
+
+    pub fn to_svg(&self) -> String {
+        let mut svg = SvgRenderer::new(self.width, self.height);
+        let Ok(()) = self.draw(&mut svg);
+        svg.finish()
+    }
+
+    pub fn draw<R: Renderer>(&self, renderer: &mut R) -> Result<(), R::Error> {
+        let theme = &self.theme;
+        renderer.fill_rect(
+            Rect {
+                x: 0.0,
+                y: 0.0,
+                width: self.width,
+                height: self.height,
+            },
+            &theme.background,
+        )?;
+
+        if let Some(title) = &self.title {
+            let style = TextStyle {
+                font_size: theme.font_size * 1.25,
+                bold: true,
+                ..theme.text_style(TextAlign::Left, TextBaseline::Top)
+            };
+            renderer.fill_text(title, self.margins.left, self.margins.top, &style)?;
+        }
+
+        let Some(layout) = self.layout() else {
+            let style = theme.text_style(TextAlign::Center, TextBaseline::Middle);
+            return renderer.fill_text("No data", self.width / 2.0, self.height / 2.0, &style);
+        };
+        let plot = layout.plot;
+
+        draw_legend(renderer, theme, &layout.legend)?;
+
+        // Grid lines sit on half pixels so one-pixel strokes stay crisp
+        let y_label_style = theme.text_style(TextAlign::Right, TextBaseline::Middle);
+        for tick in &layout.y_ticks {
+            let y = layout.y_scale.map(tick.value).round() + 0.5;
+            renderer.stroke_polyline(&[(plot.x, y), (plot.right(), y)], &theme.grid, 1.0)?;
+            renderer.fill_text(&tick.label, plot.x - 8.0, y, &y_label_style)?;
+        }
+
+        let x_label_style = theme.text_style(TextAlign::Center, TextBaseline::Top);
+        for tick in &layout.x_ticks {
+            let x = layout.x_scale.map(tick.value).round() + 0.5;
+            renderer.stroke_polyline(&[(x, plot.y), (x, plot.bottom())], &theme.grid, 1.0)?;
+            renderer.fill_text(&tick.label, x, plot.bottom() + 8.0, &x_label_style)?;
+        }
+
+        let axes = [
+            (plot.x + 0.5, plot.y),
+            (plot.x + 0.5, plot.bottom() + 0.5),
+            (plot.right(), plot.bottom() + 0.5),
+        ];
+        renderer.stroke_polyline(&axes, &theme.axis, 1.0)?;
+
+        renderer.push_clip(plot)?;
+        for (index, distribution) in self.distributions.iter().enumerate() {
+            let color = self.color(index);
+            let heights = self.heights(distribution, &layout.edges);
+            for (height, edge) in heights.iter().zip(layout.edges.windows(2)) {
+                if *height <= 0.0 {
+                    continue;
+                }
+                let (x0, x1) = (layout.x_scale.map(edge[0]), layout.x_scale.map(edge[1]));
+                let top = layout.y_scale.map(*height);
+                let bar = [
+                    (x0 + 0.5, plot.bottom()),
+                    (x0 + 0.5, top),
+                    (x1 - 0.5, top),
+                    (x1 - 0.5, plot.bottom()),
+                ];
+                renderer.fill_polygon(&bar, &color, BAR_OPACITY)?;
+            }
+        }
+
+        if self.scale == HistogramScale::Density && self.density_curves {
+            let (x_min, x_max) = layout.x_scale.domain;
+            let xs = curve_samples(x_min, x_max);
+            for (index, distribution) in self.distributions.iter().enumerate() {
+                let points: Vec<(f64, f64)> = xs
+                    .iter()
+                    .zip(kernel_density(&distribution.values, &xs))
+                    .map(|(x, density)| (layout.x_scale.map(*x), layout.y_scale.map(density)))
+                    .collect();
+                renderer.stroke_polyline(&points, &self.color(index), theme.line_width)?;
+            }
+        }
+        renderer.pop_clip()
+    }
+}
+
+fn curve_samples(min: f64, max: f64) -> Vec<f64> {
+    (0..=CURVE_SAMPLES)
+        .map(|i| min + (max - min) * i as f64 / CURVE_SAMPLES as f64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bins() {
+        let edges = bin_edges(-2.3, 3.1, 12);
+        assert_eq!(
+            edges,
+            vec![-2.5, -2.0, -1.5, -1.0, -0.5, 0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5]
+        );
+
+        let counts = bin_counts(&[-2.3, -2.0, 0.0, 0.2, 3.5, f64::NAN, 9.0], &edges);
+        assert_eq!(counts.len(), 12);
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts[1], 1);
+        assert_eq!(counts[5], 2);
+        // The top edge belongs to the last bin; values beyond it are dropped
+        assert_eq!(counts[11], 1);
+        assert_eq!(counts.iter().sum::<usize>(), 5);
+
+        assert_eq!(bin_edges(1.0, 1.0, 10), vec![0.95, 1.05]);
+
+        assert!(bin_counts(&[1.0], &[1.0]).is_empty());
+        assert!(bin_counts(&[1.0], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_kernel_density_integrates_to_one() {
+        let values: Vec<f64> = (0..200)
+            .map(|i| ((i * 37) % 101) as f64 / 10.0 - 5.0)
+            .collect();
+        let xs = curve_samples(-15.0, 15.0);
+        let step = xs[1] - xs[0];
+        let area: f64 = kernel_density(&values, &xs).iter().sum::<f64>() * step;
+        assert!((area - 1.0).abs() < 1e-3, "area {}", area);
+
+        assert_eq!(kernel_density(&[1.0], &xs), vec![0.0; xs.len()]);
+    }
+
+    #[test]
+    fn test_density_bars_have_unit_area() {
+        let chart = HistogramChart::new(600.0, 400.0).with_distribution(Distribution::new(
+            "SPY",
+            vec![-1.0, -0.4, 0.1, 0.3, 0.35, 1.2],
+        ));
+        let layout = chart.layout().unwrap();
+        let heights = chart.heights(&chart.distributions[0], &layout.edges);
+        let area: f64 = heights
+            .iter()
+            .zip(layout.edges.windows(2))
+            .map(|(h, e)| h * (e[1] - e[0]))
+            .sum();
+        assert!((area - 1.0).abs() < 1e-9);
+        assert_eq!(layout.y_scale.domain.0, 0.0);
+    }
+
+    #[test]
+    fn test_svg_snapshot() {
+        let returns = |shift: f64, spread: f64| -> Vec<f64> {
+            (0..60)
+                .map(|i| shift + spread * (((i * 29) % 61) as f64 / 30.0 - 1.0))
+                .collect()
+        };
+        let chart = HistogramChart::new(640.0, 360.0)
+            .with_title("Daily returns")
+            .with_value_suffix("%")
+            .with_max_bins(16)
+            .with_distribution(Distribution::new("SPY", returns(0.05, 1.2)))
+            .with_distribution(Distribution::new("BTC", returns(0.1, 3.5)));
+        crate::svg::assert_snapshot("histogram", &chart.to_svg());
+    }
+}
@@ -0,0 +1,124 @@
+// Pointer interaction helpers: hover read-outs, drag-to-zoom and panning
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::line_chart::ChartLayout;
+
+/// Narrowest drag, in pixels, treated as a zoom rather than a click
+pub const MIN_ZOOM_PIXELS: f64 = 5.0;
+
+/// Pointer state drawn on top of a chart, in CSS pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Overlay {
+    /// Horizontal pointer position for the crosshair and tooltip
+    pub pointer_x: Option<f64>,
+    /// Horizontal extent of an in-progress drag selection
+    pub selection: Option<(f64, f64)>,
+}
+
+/// One series' value under the crosshair
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoverValue {
+    pub label: String,
+    pub color: String,
+    pub value: f64,
+    pub y: f64,
+}
+
+/// Event marker or span under the pointer
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoverEvent {
+    pub label: String,
+    pub color: String,
+}
+
+/// Values and events under the crosshair. `timestamp` is snapped to the nearest data
+/// point; series without a point at that time report their latest earlier
+/// value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hover {
+    pub timestamp: DateTime<Utc>,
+    pub x: f64,
+    pub values: Vec<HoverValue>,
+    pub events: Vec<HoverEvent>,
+}
+
+/// Time range covered by a drag from `x0` to `x1`, clamped to the plot area,
+/// or `None` if the drag is too short to be a zoom
+pub fn selection_range(
+    layout: &ChartLayout,
+    x0: f64,
+    x1: f64,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let plot = layout.plot;
+    let (left, right) = (
+        x0.min(x1).clamp(plot.x, plot.right()),
+        x0.max(x1).clamp(plot.x, plot.right()),
+    );
+    if right - left < MIN_ZOOM_PIXELS {
+        return None;
+    }
+    let time_at = |x: f64| DateTime::<Utc>::from_timestamp_millis(layout.x_scale.invert(x) as i64);
+    Some((time_at(left)?, time_at(right)?))
+}
+
+/// `range` moved by `fraction` of its own length (positive is later in
+/// time) without leaving `bounds`
+pub fn pan_range(
+    range: (DateTime<Utc>, DateTime<Utc>),
+    bounds: (DateTime<Utc>, DateTime<Utc>),
+    fraction: f64,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let length = range.1 - range.0;
+    if length >= bounds.1 - bounds.0 {
+        return bounds;
+    }
+
+    let shift = Duration::milliseconds((length.num_milliseconds() as f64 * fraction) as i64);
+    let start = (range.0 + shift).clamp(bounds.0, bounds.1 - length);
+    (start, start + length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line_chart::{LineChart, Series};
+    use chrono::TimeZone;
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_pan_range_stays_in_bounds() {
+        let bounds = (day(1), day(31));
+        assert_eq!(
+            pan_range((day(10), day(20)), bounds, 0.5),
+            (day(15), day(25))
+        );
+        assert_eq!(
+            pan_range((day(10), day(20)), bounds, -2.0),
+            (day(1), day(11))
+        );
+        assert_eq!(
+            pan_range((day(10), day(20)), bounds, 5.0),
+            (day(21), day(31))
+        );
+        assert_eq!(pan_range(bounds, bounds, 0.5), bounds);
+    }
+
+    #[test]
+    fn test_selection_range() {
+        let chart = LineChart::new(800.0, 400.0)
+            .with_series(Series::new("SPY", vec![(day(1), 1.0), (day(31), 2.0)]));
+        let layout = chart.layout().unwrap();
+        let plot = layout.plot;
+
+        assert_eq!(selection_range(&layout, 100.0, 102.0), None);
+        let x_mid = plot.x + plot.width / 2.0;
+        assert_eq!(
+            selection_range(&layout, x_mid, 0.0),
+            Some((day(1), day(16)))
+        );
+    }
+}
@@ -1,7 +1,30 @@
 // Custom chart rendering library for WebAssembly
 
+pub mod candlestick_chart;
+pub mod canvas;
+pub mod downsample;
+pub mod drawdown_chart;
+pub mod heatmap;
+pub mod histogram;
+pub mod interaction;
 pub mod line_chart;
+pub mod renderer;
+pub mod scale;
+pub mod svg;
+pub mod ticks;
 
+pub use candlestick_chart::{Candle, CandleStyle, CandlestickChart, CandlestickLayout};
+pub use canvas::CanvasRenderer;
+pub use downsample::downsample;
+pub use drawdown_chart::{drawdown_series, max_drawdown, DrawdownChart};
+pub use heatmap::{DivergingColors, HeatmapChart, HeatmapLayout};
+pub use histogram::{
+    bin_counts, bin_edges, kernel_density, Distribution, HistogramChart, HistogramLayout,
+    HistogramScale,
+};
+pub use interaction::{pan_range, selection_range, Hover, HoverEvent, HoverValue, Overlay};
 pub use line_chart::*;
-
-// TODO: Implement chart rendering using Canvas API
+pub use renderer::{Rect, Renderer, TextAlign, TextBaseline, TextStyle};
+pub use scale::{LinearScale, LogScale, Scale, YAxisMode};
+pub use svg::SvgRenderer;
+pub use ticks::{TimeGranularity, TimeTick, ValueTick};
@@ -1,17 +1,985 @@
 // Line chart implementation for time series data
 
+use chrono::{DateTime, Utc};
+use shared::DownsampleMethod;
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+
+use crate::canvas::CanvasRenderer;
+use crate::downsample::downsample;
+use crate::interaction::{Hover, HoverEvent, HoverValue, Overlay};
+use crate::renderer::{Rect, Renderer, TextAlign, TextBaseline, TextStyle};
+use crate::scale::{LinearScale, Scale, YAxisMode};
+use crate::svg::SvgRenderer;
+use crate::ticks::{time_ticks, value_axis, TimeTick, ValueTick};
+
+/// Colors assigned by position to series that don't specify one
+pub const DEFAULT_PALETTE: [&str; 8] = [
+    "#3b82f6", "#f59e0b", "#10b981", "#ef4444", "#a855f7", "#06b6d4", "#ec4899", "#84cc16",
+];
+
+/// Rough glyph width as a fraction of the font size, used to lay out text
+/// without measuring it on a canvas
+const CHAR_WIDTH_RATIO: f64 = 0.6;
+
+/// Minimum horizontal room per date label
+const MIN_X_TICK_SPACING: f64 = 80.0;
+
+/// Opacity of shaded event spans
+const EVENT_SPAN_OPACITY: f64 = 0.12;
+
+/// Distance, in pixels, within which the pointer picks up a single-day event
+const EVENT_HOVER_PIXELS: f64 = 4.0;
+
+/// Named time series drawn as one line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub name: String,
+    /// CSS color; falls back to `DEFAULT_PALETTE`
+    pub color: Option<String>,
+    pub points: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl Series {
+    pub fn new(name: impl Into<String>, points: Vec<(DateTime<Utc>, f64)>) -> Self {
+        Self {
+            name: name.into(),
+            color: None,
+            points,
+        }
+    }
+
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+}
+
+/// Labelled marker at one data point, e.g. the deepest drawdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+    pub label: String,
+    pub color: String,
+}
+
+/// Dated event drawn as a vertical marker, or as a shaded span when `end`
+/// is set, with its label shown on hover
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventMarker {
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    pub label: String,
+    pub color: String,
+}
+
+/// Space reserved around the plot area for the title, legend and axis labels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Default for Margins {
+    fn default() -> Self {
+        Self {
+            top: 16.0,
+            right: 24.0,
+            bottom: 36.0,
+            left: 64.0,
+        }
+    }
+}
+
+/// Colors and fonts; the default matches the app's dark theme
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartTheme {
+    pub background: String,
+    pub text: String,
+    pub grid: String,
+    pub axis: String,
+    pub crosshair: String,
+    pub selection: String,
+    pub tooltip_background: String,
+    /// Candles and volume bars that closed at or above their open
+    pub up: String,
+    pub down: String,
+    pub font_family: String,
+    pub font_size: f64,
+    pub line_width: f64,
+    /// Opacity of areas filled between a series and its baseline
+    pub area_opacity: f64,
+}
+
+impl Default for ChartTheme {
+    fn default() -> Self {
+        Self {
+            background: "#1e293b".to_string(),
+            text: "#94a3b8".to_string(),
+            grid: "#334155".to_string(),
+            axis: "#475569".to_string(),
+            crosshair: "#64748b".to_string(),
+            selection: "rgba(59, 130, 246, 0.15)".to_string(),
+            tooltip_background: "#0f172a".to_string(),
+            up: "#10b981".to_string(),
+            down: "#ef4444".to_string(),
+            font_family: "sans-serif".to_string(),
+            font_size: 12.0,
+            line_width: 2.0,
+            area_opacity: 0.2,
+        }
+    }
+}
+
+impl ChartTheme {
+    pub(crate) fn text_style(&self, align: TextAlign, baseline: TextBaseline) -> TextStyle<'_> {
+        TextStyle {
+            color: &self.text,
+            font_family: &self.font_family,
+            font_size: self.font_size,
+            bold: false,
+            align,
+            baseline,
+        }
+    }
+
+    pub(crate) fn text_width(&self, text: &str) -> f64 {
+        text.chars().count() as f64 * self.font_size * CHAR_WIDTH_RATIO
+    }
+
+    pub(crate) fn legend_row_height(&self) -> f64 {
+        self.font_size + 8.0
+    }
+}
+
+/// Color swatch and label for one series; `x`/`y` is the swatch's top-left
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendEntry {
+    pub label: String,
+    pub color: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Legend entries for `(label, color)` items laid out left to right between
+/// `left` and `right` from `top`, wrapping onto new rows as needed
+pub(crate) fn layout_legend(
+    theme: &ChartTheme,
+    left: f64,
+    right: f64,
+    top: f64,
+    items: impl IntoIterator<Item = (String, String)>,
+) -> Vec<LegendEntry> {
+    let swatch = theme.font_size;
+    let mut x = left;
+    let mut y = top;
+    let mut entries = Vec::new();
+    for (label, color) in items {
+        let entry_width = swatch + 6.0 + theme.text_width(&label);
+        // Wrap onto a new row unless this is already the first entry of one
+        if x > left && x + entry_width > right {
+            x = left;
+            y += theme.legend_row_height();
+        }
+        entries.push(LegendEntry { label, color, x, y });
+        x += entry_width + 16.0;
+    }
+    entries
+}
+
+pub(crate) fn draw_legend<R: Renderer>(
+    renderer: &mut R,
+    theme: &ChartTheme,
+    legend: &[LegendEntry],
+) -> Result<(), R::Error> {
+    let style = theme.text_style(TextAlign::Left, TextBaseline::Top);
+    for entry in legend {
+        let swatch = Rect {
+            x: entry.x,
+            y: entry.y,
+            width: theme.font_size,
+            height: theme.font_size,
+        };
+        renderer.fill_rect(swatch, &entry.color)?;
+        renderer.fill_text(&entry.label, swatch.right() + 6.0, entry.y, &style)?;
+    }
+    Ok(())
+}
+
+/// Positions and scales for one render, computed without touching a canvas.
+/// The x scale maps milliseconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartLayout {
+    pub plot: Rect,
+    pub x_scale: LinearScale,
+    pub y_scale: Scale,
+    pub x_ticks: Vec<TimeTick>,
+    pub y_ticks: Vec<ValueTick>,
+    pub legend: Vec<LegendEntry>,
+}
+
+/// Multi-series time-series line chart
+#[derive(Debug, Clone)]
 pub struct LineChart {
-    // TODO: Chart configuration and rendering logic
+    width: f64,
+    height: f64,
+    title: Option<String>,
+    series: Vec<Series>,
+    margins: Margins,
+    theme: ChartTheme,
+    y_axis: YAxisMode,
+    x_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    overlay: Overlay,
+    downsample: Option<DownsampleMethod>,
+    area_baseline: Option<f64>,
+    annotations: Vec<Annotation>,
+    events: Vec<EventMarker>,
+    max_x_ticks: usize,
+    max_y_ticks: usize,
 }
 
 impl LineChart {
-    pub fn new() -> Self {
-        Self {}
+    /// Chart of `width` x `height` CSS pixels
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            title: None,
+            series: Vec::new(),
+            margins: Margins::default(),
+            theme: ChartTheme::default(),
+            y_axis: YAxisMode::default(),
+            x_range: None,
+            overlay: Overlay::default(),
+            downsample: Some(DownsampleMethod::default()),
+            area_baseline: None,
+            annotations: Vec::new(),
+            events: Vec::new(),
+            max_x_ticks: 8,
+            max_y_ticks: 6,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_margins(mut self, margins: Margins) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: ChartTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn with_y_axis(mut self, mode: YAxisMode) -> Self {
+        self.y_axis = mode;
+        self
+    }
+
+    /// Show only `start..=end` instead of the full extent of the data
+    pub fn with_x_range(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        self.x_range = Some((start, end));
+        self
+    }
+
+    pub fn with_overlay(mut self, overlay: Overlay) -> Self {
+        self.overlay = overlay;
+        self
+    }
+
+    /// How series with more points than the plot is wide are thinned out
+    /// before drawing; `None` draws every point
+    pub fn with_downsampling(mut self, downsample: Option<DownsampleMethod>) -> Self {
+        self.downsample = downsample;
+        self
+    }
+
+    /// Fill the area between each line and `baseline` (clamped to the
+    /// visible value range)
+    pub fn with_area_fill(mut self, baseline: f64) -> Self {
+        self.area_baseline = Some(baseline);
+        self
+    }
+
+    pub fn with_annotation(mut self, annotation: Annotation) -> Self {
+        self.annotations.push(annotation);
+        self
+    }
+
+    pub fn with_event(mut self, event: EventMarker) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    pub fn with_series(mut self, series: Series) -> Self {
+        self.add_series(series);
+        self
+    }
+
+    pub fn add_series(&mut self, series: Series) {
+        self.series.push(series);
+    }
+
+    pub fn series(&self) -> &[Series] {
+        &self.series
+    }
+
+    fn series_color(&self, index: usize) -> String {
+        self.series[index]
+            .color
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PALETTE[index % DEFAULT_PALETTE.len()].to_string())
+    }
+
+    fn title_height(&self) -> f64 {
+        if self.title.is_some() {
+            self.theme.font_size * 1.5 + 8.0
+        } else {
+            0.0
+        }
+    }
+
+    fn legend(&self) -> Vec<LegendEntry> {
+        let items = self
+            .series
+            .iter()
+            .enumerate()
+            .map(|(index, series)| (series.name.clone(), self.series_color(index)));
+        layout_legend(
+            &self.theme,
+            self.margins.left,
+            self.width - self.margins.right,
+            self.margins.top + self.title_height(),
+            items,
+        )
+    }
+
+    /// Whether `value` can be placed on the y axis
+    fn plottable(&self, value: f64) -> bool {
+        value.is_finite() && (self.y_axis != YAxisMode::Log || value > 0.0)
+    }
+
+    fn in_x_range(&self, t: DateTime<Utc>) -> bool {
+        self.x_range
+            .is_none_or(|(start, end)| t >= start && t <= end)
+    }
+
+    /// Plottable points of every series inside the visible range
+    fn visible_points(&self) -> impl Iterator<Item = &(DateTime<Utc>, f64)> + '_ {
+        self.series
+            .iter()
+            .flat_map(|s| s.points.iter())
+            .filter(|(t, v)| self.plottable(*v) && self.in_x_range(*t))
+    }
+
+    /// Earliest and latest timestamps with a plottable value, ignoring any
+    /// visible range
+    pub fn time_bounds(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let mut times = self
+            .series
+            .iter()
+            .flat_map(|s| s.points.iter())
+            .filter(|(_, v)| self.plottable(*v))
+            .map(|(t, _)| *t);
+        let first = times.next()?;
+        Some(times.fold((first, first), |(lo, hi), t| (lo.min(t), hi.max(t))))
+    }
+
+    /// Layout for the current series, or `None` if there are no plottable
+    /// points in the visible range
+    pub fn layout(&self) -> Option<ChartLayout> {
+        // The y axis fits the visible points; the x axis spans the visible
+        // range when one is set
+        let mut bounds: Option<(i64, i64, f64, f64)> = None;
+        for (t, v) in self.visible_points() {
+            let t = t.timestamp_millis();
+            bounds = Some(match bounds {
+                None => (t, t, *v, *v),
+                Some((t0, t1, v0, v1)) => (t0.min(t), t1.max(t), v0.min(*v), v1.max(*v)),
+            });
+        }
+        let (mut t_min, mut t_max, v_min, v_max) = bounds?;
+        if let Some((start, end)) = self.x_range {
+            t_min = start.timestamp_millis();
+            t_max = end.timestamp_millis();
+        }
+
+        let legend = self.legend();
+        let top = legend
+            .last()
+            .map_or(self.margins.top + self.title_height(), |last| {
+                last.y + self.theme.legend_row_height() + 8.0
+            });
+        let plot = Rect {
+            x: self.margins.left,
+            y: top,
+            width: (self.width - self.margins.left - self.margins.right).max(0.0),
+            height: (self.height - top - self.margins.bottom).max(0.0),
+        };
+
+        let (y_domain, y_ticks) = value_axis(self.y_axis, v_min, v_max, self.max_y_ticks);
+        let x_tick_budget = ((plot.width / MIN_X_TICK_SPACING) as usize).clamp(1, self.max_x_ticks);
+        let start = DateTime::<Utc>::from_timestamp_millis(t_min)?;
+        let end = DateTime::<Utc>::from_timestamp_millis(t_max)?;
+
+        Some(ChartLayout {
+            plot,
+            x_scale: LinearScale::new((t_min as f64, t_max as f64), (plot.x, plot.right())),
+            // Pixel rows grow downward, so larger values map to smaller y
+            y_scale: Scale::new(self.y_axis, y_domain, (plot.bottom(), plot.y)),
+            x_ticks: time_ticks(start, end, x_tick_budget),
+            y_ticks,
+            legend,
+        })
+    }
+
+    /// The part of a run of pixel positions that can appear within `plot`,
+    /// downsampled to about one point per horizontal pixel
+    fn reduce(&self, points: &[(f64, f64)], plot: Rect) -> Vec<(f64, f64)> {
+        // Keep one point beyond each edge so lines run through the border
+        let first = points
+            .partition_point(|(x, _)| *x < plot.x)
+            .saturating_sub(1);
+        let last = points
+            .partition_point(|(x, _)| *x <= plot.right())
+            .saturating_add(1)
+            .min(points.len());
+        let visible = &points[first..last.max(first)];
+
+        match self.downsample {
+            Some(method) if visible.len() > plot.width as usize => {
+                downsample(method, visible, plot.width.max(3.0) as usize, |p| *p)
+                    .into_iter()
+                    .map(|i| visible[i])
+                    .collect()
+            }
+            _ => visible.to_vec(),
+        }
+    }
+
+    /// Series values at horizontal position `x`, or `None` outside the plot
+    pub fn hover(&self, layout: &ChartLayout, x: f64) -> Option<Hover> {
+        let plot = layout.plot;
+        if x < plot.x || x > plot.right() {
+            return None;
+        }
+
+        let pointer = layout.x_scale.invert(x) as i64;
+        let timestamp = self
+            .visible_points()
+            .map(|(t, _)| *t)
+            .min_by_key(|t| (t.timestamp_millis() - pointer).abs())?;
+
+        let values = self
+            .series
+            .iter()
+            .enumerate()
+            .filter_map(|(index, series)| {
+                let (_, value) = series
+                    .points
+                    .iter()
+                    .filter(|(t, v)| *t <= timestamp && self.plottable(*v))
+                    .max_by_key(|(t, _)| *t)?;
+                Some(HoverValue {
+                    label: series.name.clone(),
+                    color: self.series_color(index),
+                    value: *value,
+                    y: layout.y_scale.map(*value),
+                })
+            })
+            .collect();
+
+        let events = self
+            .events
+            .iter()
+            .filter(|event| {
+                let start = event.start.timestamp_millis();
+                match event.end {
+                    Some(end) => (start..=end.timestamp_millis()).contains(&pointer),
+                    None => (layout.x_scale.map(start as f64) - x).abs() <= EVENT_HOVER_PIXELS,
+                }
+            })
+            .map(|event| HoverEvent {
+                label: event.label.clone(),
+                color: event.color.clone(),
+            })
+            .collect();
+
+        Some(Hover {
+            timestamp,
+            x: layout.x_scale.map(timestamp.timestamp_millis() as f64),
+            values,
+            events,
+        })
+    }
+
+    fn format_value(&self, value: f64) -> String {
+        match self.y_axis {
+            YAxisMode::Percent => format!("{:+.2}%", value),
+            YAxisMode::Linear | YAxisMode::Log => format!("{:.2}", value),
+        }
+    }
+
+    /// Shaded spans and vertical lines for events, each with a small flag at
+    /// the top of the plot; drawn under the series
+    fn draw_events<R: Renderer>(
+        &self,
+        renderer: &mut R,
+        layout: &ChartLayout,
+    ) -> Result<(), R::Error> {
+        let plot = layout.plot;
+        let x_at = |t: DateTime<Utc>| layout.x_scale.map(t.timestamp_millis() as f64);
+        for event in &self.events {
+            let x0 = x_at(event.start);
+            let x1 = event.end.map_or(x0, x_at);
+            if x1 < plot.x || x0 > plot.right() {
+                continue;
+            }
+            if x1 > x0 {
+                let span = [
+                    (x0, plot.y),
+                    (x1, plot.y),
+                    (x1, plot.bottom()),
+                    (x0, plot.bottom()),
+                ];
+                renderer.fill_polygon(&span, &event.color, EVENT_SPAN_OPACITY)?;
+            } else {
+                let x = x0.round() + 0.5;
+                renderer.stroke_polyline(&[(x, plot.y), (x, plot.bottom())], &event.color, 1.0)?;
+            }
+            let flag = [(x0, plot.y), (x0 + 6.0, plot.y), (x0, plot.y + 6.0)];
+            renderer.fill_polygon(&flag, &event.color, 1.0)?;
+        }
+        Ok(())
+    }
+
+    /// Markers with labels beside them, skipping any outside the plot
+    fn draw_annotations<R: Renderer>(
+        &self,
+        renderer: &mut R,
+        layout: &ChartLayout,
+    ) -> Result<(), R::Error> {
+        let plot = layout.plot;
+        for annotation in &self.annotations {
+            if !self.plottable(annotation.value) || !self.in_x_range(annotation.timestamp) {
+                continue;
+            }
+            let x = layout
+                .x_scale
+                .map(annotation.timestamp.timestamp_millis() as f64);
+            let y = layout.y_scale.map(annotation.value);
+            renderer.fill_circle(x, y, 4.0, &annotation.color)?;
+
+            // Label above the marker, or below it near the top edge; flip to
+            // the left of it near the right edge
+            let width = self.theme.text_width(&annotation.label);
+            let (label_x, align) = if x + 8.0 + width > plot.right() {
+                (x - 8.0, TextAlign::Right)
+            } else {
+                (x + 8.0, TextAlign::Left)
+            };
+            let (label_y, baseline) = if y - 8.0 - self.theme.font_size < plot.y {
+                (y + 8.0, TextBaseline::Top)
+            } else {
+                (y - 8.0, TextBaseline::Bottom)
+            };
+            let style = TextStyle {
+                color: &annotation.color,
+                ..self.theme.text_style(align, baseline)
+            };
+            renderer.fill_text(&annotation.label, label_x, label_y, &style)?;
+        }
+        Ok(())
+    }
+
+    /// Drag selection, crosshair, markers and tooltip
+    fn draw_overlay<R: Renderer>(
+        &self,
+        renderer: &mut R,
+        layout: &ChartLayout,
+    ) -> Result<(), R::Error> {
+        let theme = &self.theme;
+        let plot = layout.plot;
+
+        if let Some((x0, x1)) = self.overlay.selection {
+            let left = x0.min(x1).clamp(plot.x, plot.right());
+            let right = x0.max(x1).clamp(plot.x, plot.right());
+            let band = Rect {
+                x: left,
+                y: plot.y,
+                width: right - left,
+                height: plot.height,
+            };
+            renderer.fill_rect(band, &theme.selection)?;
+        }
+
+        let Some(hover) = self.overlay.pointer_x.and_then(|x| self.hover(layout, x)) else {
+            return Ok(());
+        };
+
+        let x = hover.x.round() + 0.5;
+        renderer.stroke_polyline(&[(x, plot.y), (x, plot.bottom())], &theme.crosshair, 1.0)?;
+        for value in &hover.values {
+            let marker = Rect {
+                x: hover.x - 3.0,
+                y: value.y - 3.0,
+                width: 6.0,
+                height: 6.0,
+            };
+            renderer.fill_rect(marker, &value.color)?;
+        }
+
+        let date = hover.timestamp.format("%b %-d, %Y").to_string();
+        // One row per series value, then one per event under the pointer
+        let lines: Vec<(String, &str)> = hover
+            .values
+            .iter()
+            .map(|v| {
+                let text = format!("{}  {}", v.label, self.format_value(v.value));
+                (text, v.color.as_str())
+            })
+            .chain(
+                hover
+                    .events
+                    .iter()
+                    .map(|e| (e.label.clone(), e.color.as_str())),
+            )
+            .collect();
+        let swatch = theme.font_size * 0.75;
+        let line_height = theme.font_size + 6.0;
+        let text_width = lines
+            .iter()
+            .map(|(l, _)| swatch + 6.0 + theme.text_width(l))
+            .fold(theme.text_width(&date), f64::max);
+        let width = text_width + 16.0;
+        let height = (lines.len() + 1) as f64 * line_height + 10.0;
+
+        // Keep the tooltip beside the crosshair, flipping left near the edge
+        let left = if hover.x + 12.0 + width > plot.right() {
+            hover.x - 12.0 - width
+        } else {
+            hover.x + 12.0
+        };
+        let tooltip = Rect {
+            x: left,
+            y: plot.y + 8.0,
+            width,
+            height,
+        };
+        renderer.fill_rect(tooltip, &theme.tooltip_background)?;
+        let border = [
+            (tooltip.x, tooltip.y),
+            (tooltip.right(), tooltip.y),
+            (tooltip.right(), tooltip.bottom()),
+            (tooltip.x, tooltip.bottom()),
+            (tooltip.x, tooltip.y),
+        ];
+        renderer.stroke_polyline(&border, &theme.grid, 1.0)?;
+
+        let style = theme.text_style(TextAlign::Left, TextBaseline::Top);
+        let mut y = tooltip.y + 8.0;
+        renderer.fill_text(&date, tooltip.x + 8.0, y, &style)?;
+        for (line, color) in &lines {
+            y += line_height;
+            let marker = Rect {
+                x: tooltip.x + 8.0,
+                y: y + (theme.font_size - swatch) / 2.0,
+                width: swatch,
+                height: swatch,
+            };
+            renderer.fill_rect(marker, color)?;
+            renderer.fill_text(line, marker.right() + 6.0, y, &style)?;
+        }
+        Ok(())
+    }
+
+    /// Draw the chart onto `canvas`
+    pub fn render(&self, canvas: &HtmlCanvasElement) -> Result<(), JsValue> {
+        self.draw(&mut CanvasRenderer::new(canvas, self.width, self.height)?)
+    }
+
+    /// The chart as a standalone SVG document
+    pub fn to_svg(&self) -> String {
+        let mut svg = SvgRenderer::new(self.width, self.height);
+        let Ok(()) = self.draw(&mut svg);
+        svg.finish()
+    }
+
+    /// Draw the chart with any backend
+    pub fn draw<R: Renderer>(&self, renderer: &mut R) -> Result<(), R::Error> {
+        let theme = &self.theme;
+        renderer.fill_rect(
+            Rect {
+                x: 0.0,
+                y: 0.0,
+                width: self.width,
+                height: self.height,
+            },
+            &theme.background,
+        )?;
+
+        if let Some(title) = &self.title {
+            let style = TextStyle {
+                font_size: theme.font_size * 1.25,
+                bold: true,
+                ..theme.text_style(TextAlign::Left, TextBaseline::Top)
+            };
+            renderer.fill_text(title, self.margins.left, self.margins.top, &style)?;
+        }
+
+        let Some(layout) = self.layout() else {
+            let style = theme.text_style(TextAlign::Center, TextBaseline::Middle);
+            return renderer.fill_text("No data", self.width / 2.0, self.height / 2.0, &style);
+        };
+        let plot = layout.plot;
+
+        draw_legend(renderer, theme, &layout.legend)?;
+
+        // Grid lines sit on half pixels so one-pixel strokes stay crisp
+        let y_label_style = theme.text_style(TextAlign::Right, TextBaseline::Middle);
+        for tick in &layout.y_ticks {
+            let y = layout.y_scale.map(tick.value).round() + 0.5;
+            // Percent charts emphasize the line where assets break even
+            let color = if self.y_axis == YAxisMode::Percent && tick.value == 0.0 {
+                &theme.axis
+            } else {
+                &theme.grid
+            };
+            renderer.stroke_polyline(&[(plot.x, y), (plot.right(), y)], color, 1.0)?;
+            renderer.fill_text(&tick.label, plot.x - 8.0, y, &y_label_style)?;
+        }
+
+        let x_label_style = theme.text_style(TextAlign::Center, TextBaseline::Top);
+        for tick in &layout.x_ticks {
+            let x = layout
+                .x_scale
+                .map(tick.timestamp.timestamp_millis() as f64)
+                .round()
+                + 0.5;
+            renderer.stroke_polyline(&[(x, plot.y), (x, plot.bottom())], &theme.grid, 1.0)?;
+            renderer.fill_text(&tick.label, x, plot.bottom() + 8.0, &x_label_style)?;
+        }
+
+        let axes = [
+            (plot.x + 0.5, plot.y),
+            (plot.x + 0.5, plot.bottom() + 0.5),
+            (plot.right(), plot.bottom() + 0.5),
+        ];
+        renderer.stroke_polyline(&axes, &theme.axis, 1.0)?;
+
+        renderer.push_clip(plot)?;
+        self.draw_events(renderer, &layout)?;
+        for (index, series) in self.series.iter().enumerate() {
+            let color = self.series_color(index);
+            // Gaps in the data break the line rather than bridging it
+            for run in series.points.split(|(_, v)| !self.plottable(*v)) {
+                let points: Vec<(f64, f64)> = run
+                    .iter()
+                    .map(|(t, v)| {
+                        (
+                            layout.x_scale.map(t.timestamp_millis() as f64),
+                            layout.y_scale.map(*v),
+                        )
+                    })
+                    .collect();
+                let points = self.reduce(&points, plot);
+                if let (Some(baseline), Some(first), Some(last)) =
+                    (self.area_baseline, points.first(), points.last())
+                {
+                    let (lo, hi) = layout.y_scale.domain();
+                    let base_y = layout.y_scale.map(baseline.clamp(lo, hi));
+                    let mut area = points.clone();
+                    area.push((last.0, base_y));
+                    area.push((first.0, base_y));
+                    renderer.fill_polygon(&area, &color, theme.area_opacity)?;
+                }
+                renderer.stroke_polyline(&points, &color, theme.line_width)?;
+            }
+        }
+        renderer.pop_clip()?;
+
+        self.draw_annotations(renderer, &layout)?;
+
+        self.draw_overlay(renderer, &layout)
     }
 }
 
 impl Default for LineChart {
     fn default() -> Self {
-        Self::new()
+        Self::new(800.0, 400.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn series(name: &str, values: &[f64]) -> Series {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        Series::new(
+            name,
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (start + chrono::Duration::days(i as i64), *v))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_layout_maps_data_inside_plot() {
+        let chart = LineChart::new(800.0, 400.0)
+            .with_series(series("SPY", &[100.0, 110.0, 105.0]))
+            .with_series(series("BTC", &[100.0, 140.0, f64::NAN]));
+        let layout = chart.layout().unwrap();
+
+        let plot = layout.plot;
+        assert!(plot.y > Margins::default().top);
+        assert_eq!(plot.bottom(), 400.0 - Margins::default().bottom);
+        assert_eq!(layout.y_scale.domain(), (100.0, 140.0));
+        assert_eq!(layout.y_scale.map(140.0), plot.y);
+        assert_eq!(layout.y_scale.map(100.0), plot.bottom());
+        assert_eq!(layout.x_ticks.len(), 3);
+        assert_eq!(layout.legend[1].color, DEFAULT_PALETTE[1]);
+    }
+
+    #[test]
+    fn test_log_axis_spaces_ratios_evenly() {
+        let chart = LineChart::new(800.0, 400.0)
+            .with_y_axis(YAxisMode::Log)
+            .with_series(series("BTC", &[10.0, 100.0, 1_000.0, 0.0]));
+        let layout = chart.layout().unwrap();
+
+        let y = |v: f64| layout.y_scale.map(v);
+        assert!((y(10.0) - y(100.0) - (y(100.0) - y(1_000.0))).abs() < 1e-9);
+        let labels: Vec<_> = layout.y_ticks.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(labels, vec!["10", "30", "100", "300", "1000"]);
+    }
+
+    #[test]
+    fn test_hover_snaps_to_nearest_point() {
+        let mut weekly = series("GOLD", &[50.0, 51.0]);
+        weekly.points[1].0 = weekly.points[0].0 + chrono::Duration::days(3);
+        let chart = LineChart::new(800.0, 400.0)
+            .with_series(series("BTC", &[100.0, 110.0, 120.0, 130.0, 140.0]))
+            .with_series(weekly);
+        let layout = chart.layout().unwrap();
+
+        // 30% of the way across four days is nearest to Jan 2
+        let x = layout.plot.x + layout.plot.width * 0.3;
+        let hover = chart.hover(&layout, x).unwrap();
+        assert_eq!(hover.timestamp.format("%F").to_string(), "2024-01-02");
+        assert_eq!(hover.values.len(), 2);
+        assert_eq!(hover.values[0].value, 110.0);
+        // GOLD has no point on Jan 2, so its Jan 1 value carries forward
+        assert_eq!(hover.values[1].value, 50.0);
+        assert!(chart.hover(&layout, 0.0).is_none());
+
+        let zoomed =
+            chart.with_x_range(hover.timestamp, hover.timestamp + chrono::Duration::days(2));
+        let layout = zoomed.layout().unwrap();
+        // Only BTC's 110..130 and GOLD's 51 are visible
+        assert_eq!(layout.y_scale.domain(), (40.0, 140.0));
+        assert_eq!(layout.x_ticks.len(), 3);
+    }
+
+    #[test]
+    fn test_hover_reports_events() {
+        let start = series("BTC", &[1.0]).points[0].0;
+        let day = chrono::Duration::days(1);
+        let event = |label: &str, start, end| EventMarker {
+            start,
+            end,
+            label: label.to_string(),
+            color: "#f59e0b".to_string(),
+        };
+        let chart = LineChart::new(800.0, 400.0)
+            .with_series(series("BTC", &[100.0, 110.0, 120.0, 130.0, 140.0]))
+            .with_event(event("Halving", start + day, None))
+            .with_event(event("Recession", start + day * 2, Some(start + day * 4)));
+        let layout = chart.layout().unwrap();
+        let x_at = |days: f64| layout.plot.x + layout.plot.width * days / 4.0;
+
+        let labels = |x: f64| -> Vec<String> {
+            let hover = chart.hover(&layout, x).unwrap();
+            hover.events.into_iter().map(|e| e.label).collect()
+        };
+        assert_eq!(labels(x_at(1.0) + 3.0), vec!["Halving"]);
+        assert!(labels(x_at(1.5)).is_empty());
+        assert_eq!(labels(x_at(3.5)), vec!["Recession"]);
+
+        let svg = chart
+            .with_overlay(Overlay {
+                pointer_x: Some(x_at(3.0)),
+                selection: None,
+            })
+            .to_svg();
+        assert!(svg.contains(">Recession</text>"));
+        assert!(svg.contains("fill-opacity=\"0.12\""));
+    }
+
+    #[test]
+    fn test_long_series_are_downsampled_to_plot_width() {
+        let values: Vec<f64> = (0..20_000).map(|i| (i as f64 / 50.0).sin() + 2.0).collect();
+        let chart = LineChart::new(400.0, 300.0).with_series(series("BTC", &values));
+        let plot = chart.layout().unwrap().plot;
+
+        let svg = chart.to_svg();
+        let line = svg
+            .lines()
+            .find(|l| l.contains(DEFAULT_PALETTE[0]) && l.starts_with("<polyline"))
+            .unwrap();
+        let drawn = line.matches(',').count();
+        assert!(drawn <= plot.width as usize, "{} points drawn", drawn);
+
+        let full = chart.with_downsampling(None).to_svg();
+        assert!(full.len() > svg.len() * 10);
+    }
+
+    #[test]
+    fn test_legend_wraps_onto_new_rows() {
+        let mut chart = LineChart::new(300.0, 300.0);
+        for name in ["Invesco QQQ Trust", "S&P 500 ETF", "Bitcoin", "Gold"] {
+            chart.add_series(series(name, &[1.0, 2.0]));
+        }
+        let layout = chart.layout().unwrap();
+
+        let rows: Vec<f64> = layout.legend.iter().map(|e| e.y).collect();
+        assert!(rows.windows(2).all(|w| w[0] <= w[1]));
+        assert!(rows[3] > rows[0]);
+        assert!(layout.plot.y > rows[3]);
+        assert!(layout.legend.iter().all(|e| e.x >= Margins::default().left));
+    }
+
+    #[test]
+    fn test_svg_snapshot() {
+        let chart = LineChart::new(640.0, 320.0)
+            .with_title("Growth of 10000")
+            .with_series(series(
+                "SPY",
+                &[10_000.0, 10_150.0, 9_980.0, 10_420.0, 10_610.0],
+            ))
+            .with_series(series(
+                "BTC",
+                &[10_000.0, 11_200.0, f64::NAN, 12_900.0, 12_100.0],
+            ));
+        crate::svg::assert_snapshot("line_chart", &chart.to_svg());
+    }
+
+    #[test]
+    fn test_layout_without_data() {
+        assert!(LineChart::default().layout().is_none());
+        assert!(LineChart::default()
+            .with_series(series("empty", &[f64::NAN]))
+            .layout()
+            .is_none());
     }
 }
@@ -0,0 +1,87 @@
+// Drawing primitives shared by the chart types, independent of the output
+// backend
+
+/// Axis-aligned rectangle in CSS pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+}
+
+/// Horizontal anchor of text relative to its position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+/// Vertical anchor of text relative to its position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextBaseline {
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Font and placement for a piece of text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle<'a> {
+    pub color: &'a str,
+    pub font_family: &'a str,
+    pub font_size: f64,
+    pub bold: bool,
+    pub align: TextAlign,
+    pub baseline: TextBaseline,
+}
+
+/// Output backend for charts. Coordinates are CSS pixels with the origin at
+/// the top-left corner; colors are CSS color strings.
+pub trait Renderer {
+    type Error;
+
+    fn fill_rect(&mut self, rect: Rect, color: &str) -> Result<(), Self::Error>;
+
+    /// Stroke a connected line through `points` with round joins
+    fn stroke_polyline(
+        &mut self,
+        points: &[(f64, f64)],
+        color: &str,
+        width: f64,
+    ) -> Result<(), Self::Error>;
+
+    /// Fill the closed polygon through `points` at `opacity` (0.0 to 1.0)
+    fn fill_polygon(
+        &mut self,
+        points: &[(f64, f64)],
+        color: &str,
+        opacity: f64,
+    ) -> Result<(), Self::Error>;
+
+    fn fill_circle(&mut self, x: f64, y: f64, radius: f64, color: &str) -> Result<(), Self::Error>;
+
+    fn fill_text(
+        &mut self,
+        text: &str,
+        x: f64,
+        y: f64,
+        style: &TextStyle,
+    ) -> Result<(), Self::Error>;
+
+    /// Restrict drawing to `rect` until the matching `pop_clip`
+    fn push_clip(&mut self, rect: Rect) -> Result<(), Self::Error>;
+
+    fn pop_clip(&mut self) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,111 @@
+// Mapping between data values and pixel coordinates
+
+/// Linear mapping from a data domain onto a pixel range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearScale {
+    pub domain: (f64, f64),
+    pub range: (f64, f64),
+}
+
+impl LinearScale {
+    pub fn new(domain: (f64, f64), range: (f64, f64)) -> Self {
+        Self { domain, range }
+    }
+
+    /// Pixel position of `value`; an empty domain maps to the middle of the range
+    pub fn map(&self, value: f64) -> f64 {
+        let (d0, d1) = self.domain;
+        let (r0, r1) = self.range;
+        if d1 == d0 {
+            return (r0 + r1) / 2.0;
+        }
+        r0 + (value - d0) / (d1 - d0) * (r1 - r0)
+    }
+
+    /// Data value at pixel position `pixel`
+    pub fn invert(&self, pixel: f64) -> f64 {
+        let (d0, d1) = self.domain;
+        let (r0, r1) = self.range;
+        if r1 == r0 {
+            return d0;
+        }
+        d0 + (pixel - r0) / (r1 - r0) * (d1 - d0)
+    }
+}
+
+/// Logarithmic mapping from a strictly positive domain onto a pixel range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogScale {
+    pub domain: (f64, f64),
+    pub range: (f64, f64),
+}
+
+impl LogScale {
+    pub fn new(domain: (f64, f64), range: (f64, f64)) -> Self {
+        Self { domain, range }
+    }
+
+    fn linear(&self) -> LinearScale {
+        LinearScale::new((self.domain.0.ln(), self.domain.1.ln()), self.range)
+    }
+
+    /// Pixel position of `value`; non-positive values map to negative infinity
+    pub fn map(&self, value: f64) -> f64 {
+        self.linear().map(value.ln())
+    }
+
+    pub fn invert(&self, pixel: f64) -> f64 {
+        self.linear().invert(pixel).exp()
+    }
+}
+
+/// How values are placed and labelled on the y axis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum YAxisMode {
+    #[default]
+    Linear,
+    /// Equal distances for equal ratios; values at or below zero are left out
+    Log,
+    /// Linear axis for percentage changes, labelled with `%` and drawn with a
+    /// zero baseline
+    Percent,
+}
+
+/// Value scale of either kind, as chosen by a `YAxisMode`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    Linear(LinearScale),
+    Log(LogScale),
+}
+
+impl Scale {
+    pub fn new(mode: YAxisMode, domain: (f64, f64), range: (f64, f64)) -> Self {
+        match mode {
+            YAxisMode::Log => Scale::Log(LogScale::new(domain, range)),
+            YAxisMode::Linear | YAxisMode::Percent => {
+                Scale::Linear(LinearScale::new(domain, range))
+            }
+        }
+    }
+
+    pub fn domain(&self) -> (f64, f64) {
+        match self {
+            Scale::Linear(s) => s.domain,
+            Scale::Log(s) => s.domain,
+        }
+    }
+
+    pub fn map(&self, value: f64) -> f64 {
+        match self {
+            Scale::Linear(s) => s.map(value),
+            Scale::Log(s) => s.map(value),
+        }
+    }
+
+    pub fn invert(&self, pixel: f64) -> f64 {
+        match self {
+            Scale::Linear(s) => s.invert(pixel),
+            Scale::Log(s) => s.invert(pixel),
+        }
+    }
+}
@@ -0,0 +1,255 @@
+// Renderer producing a standalone SVG document
+
+use std::convert::Infallible;
+use std::fmt::Write;
+
+use crate::renderer::{Rect, Renderer, TextAlign, TextBaseline, TextStyle};
+
+/// Builds an SVG document in memory; call `finish` for the markup
+#[derive(Debug, Clone)]
+pub struct SvgRenderer {
+    width: f64,
+    height: f64,
+    body: String,
+    clip_count: usize,
+}
+
+impl SvgRenderer {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+            clip_count: 0,
+        }
+    }
+
+    pub fn finish(self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}</svg>\n",
+            self.body,
+            w = num(self.width),
+            h = num(self.height),
+        )
+    }
+}
+
+/// Coordinates rounded to two decimals with trailing zeros dropped, so
+/// output is compact and stable across platforms
+fn num(value: f64) -> String {
+    let s = format!("{:.2}", value);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    match s {
+        "-0" => "0".to_string(),
+        s => s.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Renderer for SvgRenderer {
+    type Error = Infallible;
+
+    fn fill_rect(&mut self, rect: Rect, color: &str) -> Result<(), Infallible> {
+        let _ = writeln!(
+            self.body,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+            num(rect.x),
+            num(rect.y),
+            num(rect.width),
+            num(rect.height),
+            escape(color),
+        );
+        Ok(())
+    }
+
+    fn stroke_polyline(
+        &mut self,
+        points: &[(f64, f64)],
+        color: &str,
+        width: f64,
+    ) -> Result<(), Infallible> {
+        if points.is_empty() {
+            return Ok(());
+        }
+        let points: Vec<String> = points
+            .iter()
+            .map(|(x, y)| format!("{},{}", num(*x), num(*y)))
+            .collect();
+        let _ = writeln!(
+            self.body,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\"/>",
+            points.join(" "),
+            escape(color),
+            num(width),
+        );
+        Ok(())
+    }
+
+    fn fill_polygon(
+        &mut self,
+        points: &[(f64, f64)],
+        color: &str,
+        opacity: f64,
+    ) -> Result<(), Infallible> {
+        if points.is_empty() {
+            return Ok(());
+        }
+        let points: Vec<String> = points
+            .iter()
+            .map(|(x, y)| format!("{},{}", num(*x), num(*y)))
+            .collect();
+        let _ = writeln!(
+            self.body,
+            "<polygon points=\"{}\" fill=\"{}\" fill-opacity=\"{}\"/>",
+            points.join(" "),
+            escape(color),
+            num(opacity),
+        );
+        Ok(())
+    }
+
+    fn fill_circle(&mut self, x: f64, y: f64, radius: f64, color: &str) -> Result<(), Infallible> {
+        let _ = writeln!(
+            self.body,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+            num(x),
+            num(y),
+            num(radius),
+            escape(color),
+        );
+        Ok(())
+    }
+
+    fn fill_text(
+        &mut self,
+        text: &str,
+        x: f64,
+        y: f64,
+        style: &TextStyle,
+    ) -> Result<(), Infallible> {
+        let anchor = match style.align {
+            TextAlign::Left => "start",
+            TextAlign::Center => "middle",
+            TextAlign::Right => "end",
+        };
+        let baseline = match style.baseline {
+            TextBaseline::Top => "hanging",
+            TextBaseline::Middle => "central",
+            TextBaseline::Bottom => "text-after-edge",
+        };
+        let weight = if style.bold {
+            " font-weight=\"bold\""
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            self.body,
+            "<text x=\"{}\" y=\"{}\" fill=\"{}\" font-family=\"{}\" font-size=\"{}\"{} text-anchor=\"{}\" dominant-baseline=\"{}\">{}</text>",
+            num(x),
+            num(y),
+            escape(style.color),
+            escape(style.font_family),
+            num(style.font_size),
+            weight,
+            anchor,
+            baseline,
+            escape(text),
+        );
+        Ok(())
+    }
+
+    fn push_clip(&mut self, rect: Rect) -> Result<(), Infallible> {
+        self.clip_count += 1;
+        let _ = writeln!(
+            self.body,
+            "<clipPath id=\"clip{id}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>\n<g clip-path=\"url(#clip{id})\">",
+            num(rect.x),
+            num(rect.y),
+            num(rect.width),
+            num(rect.height),
+            id = self.clip_count,
+        );
+        Ok(())
+    }
+
+    fn pop_clip(&mut self) -> Result<(), Infallible> {
+        self.body.push_str("</g>\n");
+        Ok(())
+    }
+}
+
+/// Compare `output` with `<dir>/snapshots/<file>`, where `dir` is usually
+/// the calling crate's `CARGO_MANIFEST_DIR`. Set `UPDATE_SNAPSHOTS=1` to
+/// write the current output instead after an intentional rendering change.
+/// Other crates get this with the `snapshots` feature.
+#[cfg(any(test, feature = "snapshots"))]
+pub fn assert_snapshot_in(dir: &str, file: &str, output: &[u8]) {
+    let path = format!("{}/snapshots/{}", dir, file);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, output).unwrap();
+        return;
+    }
+    let expected =
+        std::fs::read(&path).unwrap_or_else(|e| panic!("missing snapshot {}: {}", path, e));
+    assert!(
+        output == expected,
+        "{} differs from its snapshot; rerun with UPDATE_SNAPSHOTS=1 to accept",
+        file
+    );
+}
+
+/// Compare `svg` with `snapshots/<name>.svg` in this crate
+#[cfg(test)]
+pub(crate) fn assert_snapshot(name: &str, svg: &str) {
+    assert_snapshot_in(env!("CARGO_MANIFEST_DIR"), &format!("{}.svg", name), svg.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg_output() {
+        let mut svg = SvgRenderer::new(100.0, 50.0);
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 50.0,
+        };
+        let style = TextStyle {
+            color: "#fff",
+            font_family: "sans-serif",
+            font_size: 12.0,
+            bold: false,
+            align: TextAlign::Center,
+            baseline: TextBaseline::Middle,
+        };
+        let Ok(()) = svg.push_clip(rect);
+        let Ok(()) = svg.stroke_polyline(&[(0.0, 0.0), (10.126, -0.001)], "red", 2.0);
+        let Ok(()) = svg.pop_clip();
+        let Ok(()) = svg.fill_text("S&P <500>", 50.0, 25.0, &style);
+
+        let out = svg.finish();
+        assert!(out
+            .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"50\""));
+        assert!(out.contains("points=\"0,0 10.13,0\""));
+        assert!(out.contains("<g clip-path=\"url(#clip1)\">"));
+        assert!(out.contains(">S&amp;P &lt;500&gt;</text>"));
+        assert!(out.trim_end().ends_with("</svg>"));
+    }
+}
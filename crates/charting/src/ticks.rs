@@ -0,0 +1,374 @@
+// Axis tick generation for numeric and date axes
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+
+use crate::scale::YAxisMode;
+
+/// Tick step of at most `max_ticks` intervals across `[min, max]`, rounded
+/// to 1, 2 or 5 times a power of ten
+pub fn nice_step(min: f64, max: f64, max_ticks: usize) -> f64 {
+    let span = max - min;
+    if !span.is_finite() || span <= 0.0 {
+        return 0.0;
+    }
+
+    let raw = span / max_ticks.max(1) as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let nice = match raw / magnitude {
+        r if r <= 1.0 => 1.0,
+        r if r <= 2.0 => 2.0,
+        r if r <= 5.0 => 5.0,
+        _ => 10.0,
+    };
+    nice * magnitude
+}
+
+/// `[min, max]` widened outward to whole tick steps, so the extreme values
+/// sit on or inside the outermost grid lines
+pub fn nice_domain(min: f64, max: f64, max_ticks: usize) -> (f64, f64) {
+    let mut step = nice_step(min, max, max_ticks);
+    if step == 0.0 {
+        // Flat series: pad around the single value instead
+        let pad = if min == 0.0 { 1.0 } else { min.abs() * 0.05 };
+        return (min - pad, max + pad);
+    }
+
+    // Widening can push the span into a coarser step; repeat until the
+    // bounds land on the step `nice_ticks` will use for them
+    let (mut lo, mut hi) = (min, max);
+    for _ in 0..10 {
+        lo = (lo / step).floor() * step;
+        hi = (hi / step).ceil() * step;
+        let next = nice_step(lo, hi, max_ticks);
+        if next == step {
+            break;
+        }
+        step = next;
+    }
+    (lo, hi)
+}
+
+/// Multiples of `nice_step` that fall inside `[min, max]`
+pub fn nice_ticks(min: f64, max: f64, max_ticks: usize) -> Vec<f64> {
+    let step = nice_step(min, max, max_ticks);
+    if step == 0.0 {
+        return vec![min];
+    }
+
+    // Small tolerance so bounds produced by `nice_domain` are included
+    let first = (min / step - 1e-9).ceil() as i64;
+    let last = (max / step + 1e-9).floor() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+/// Label for a numeric tick, with just enough decimals to tell adjacent
+/// ticks `step` apart
+pub fn format_tick(value: f64, step: f64) -> String {
+    let decimals = if step > 0.0 && step < 1.0 {
+        (-step.log10().floor()) as usize
+    } else {
+        0
+    };
+    // Avoid printing "-0" for values that are zero up to rounding error
+    let value = if value.abs() < step * 1e-9 {
+        0.0
+    } else {
+        value
+    };
+    format!("{:.*}", decimals, value)
+}
+
+/// Ticks for a logarithmic axis over `[min, max]` (both positive): 1-2-5 or
+/// 1-3 multiples of each decade when they fit within `max_ticks`, otherwise
+/// every n-th decade. Empty if fewer than two such values fall in range.
+pub fn log_ticks(min: f64, max: f64, max_ticks: usize) -> Vec<f64> {
+    if min <= 0.0 || max <= min || !max.is_finite() {
+        return Vec::new();
+    }
+
+    let lo = min.log10().floor() as i32;
+    let hi = max.log10().ceil() as i32;
+    let in_range = |v: &f64| *v >= min * (1.0 - 1e-9) && *v <= max * (1.0 + 1e-9);
+    let candidates: [&[f64]; 3] = [&[1.0, 2.0, 5.0], &[1.0, 3.0], &[1.0]];
+    for mantissas in candidates {
+        let ticks: Vec<f64> = (lo..=hi)
+            .flat_map(|e| mantissas.iter().map(move |m| m * 10f64.powi(e)))
+            .filter(in_range)
+            .collect();
+        if ticks.len() <= max_ticks.max(2) {
+            return if ticks.len() >= 2 { ticks } else { Vec::new() };
+        }
+    }
+
+    let stride = ((hi - lo) as f64 / max_ticks.max(1) as f64).ceil() as i32;
+    (lo..=hi)
+        .filter(|e| e.rem_euclid(stride) == 0)
+        .map(|e| 10f64.powi(e))
+        .filter(in_range)
+        .collect()
+}
+
+/// A labelled position on a value axis
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueTick {
+    pub value: f64,
+    pub label: String,
+}
+
+/// Domain and labelled ticks for values spanning `[min, max]`. Log axes
+/// fall back to linear ticks when the range is too narrow to hold two
+/// decade ticks.
+pub fn value_axis(
+    mode: YAxisMode,
+    min: f64,
+    max: f64,
+    max_ticks: usize,
+) -> ((f64, f64), Vec<ValueTick>) {
+    let labelled = |values: Vec<f64>, step: Option<f64>, suffix: &str| {
+        values
+            .into_iter()
+            .map(|value| ValueTick {
+                value,
+                label: format!("{}{}", format_tick(value, step.unwrap_or(value)), suffix),
+            })
+            .collect()
+    };
+
+    match mode {
+        YAxisMode::Linear | YAxisMode::Percent => {
+            let (lo, hi) = nice_domain(min, max, max_ticks);
+            let step = nice_step(lo, hi, max_ticks);
+            let suffix = if mode == YAxisMode::Percent { "%" } else { "" };
+            (
+                (lo, hi),
+                labelled(nice_ticks(lo, hi, max_ticks), Some(step), suffix),
+            )
+        }
+        YAxisMode::Log => {
+            // Pad by a small ratio so lines don't run along the plot edges
+            let pad = if max > min {
+                (max / min).powf(0.02)
+            } else {
+                2.0
+            };
+            let (lo, hi) = (min / pad, max * pad);
+            let ticks = log_ticks(lo, hi, max_ticks);
+            if ticks.is_empty() {
+                let step = nice_step(lo, hi, max_ticks);
+                (
+                    (lo, hi),
+                    labelled(nice_ticks(lo, hi, max_ticks), Some(step), ""),
+                )
+            } else {
+                ((lo, hi), labelled(ticks, None, ""))
+            }
+        }
+    }
+}
+
+/// Short label for large quantities such as volume: 950, 12.5k, 3M, 1.2B
+pub fn format_compact(value: f64) -> String {
+    let (scaled, suffix) = match value.abs() {
+        v if v >= 1e9 => (value / 1e9, "B"),
+        v if v >= 1e6 => (value / 1e6, "M"),
+        v if v >= 1e3 => (value / 1e3, "k"),
+        _ => (value, ""),
+    };
+    let text = format!("{:.1}", scaled);
+    format!("{}{}", text.trim_end_matches(".0"), suffix)
+}
+
+/// Calendar unit that date ticks are aligned to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeGranularity {
+    Day,
+    Month,
+    Year,
+}
+
+impl TimeGranularity {
+    /// Average length in days, used to estimate tick counts
+    fn approx_days(&self) -> f64 {
+        match self {
+            TimeGranularity::Day => 1.0,
+            TimeGranularity::Month => 30.44,
+            TimeGranularity::Year => 365.25,
+        }
+    }
+
+    fn label_format(&self) -> &'static str {
+        match self {
+            TimeGranularity::Day => "%b %-d",
+            TimeGranularity::Month => "%b %Y",
+            TimeGranularity::Year => "%Y",
+        }
+    }
+}
+
+/// Candidate steps in increasing length; weekly steps land on Mondays
+const TIME_STEPS: [(TimeGranularity, u32); 15] = [
+    (TimeGranularity::Day, 1),
+    (TimeGranularity::Day, 2),
+    (TimeGranularity::Day, 7),
+    (TimeGranularity::Day, 14),
+    (TimeGranularity::Month, 1),
+    (TimeGranularity::Month, 2),
+    (TimeGranularity::Month, 3),
+    (TimeGranularity::Month, 6),
+    (TimeGranularity::Year, 1),
+    (TimeGranularity::Year, 2),
+    (TimeGranularity::Year, 5),
+    (TimeGranularity::Year, 10),
+    (TimeGranularity::Year, 25),
+    (TimeGranularity::Year, 50),
+    (TimeGranularity::Year, 100),
+];
+
+/// A labelled position on a date axis
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeTick {
+    pub timestamp: DateTime<Utc>,
+    pub label: String,
+}
+
+/// Coarsest-needed calendar step that yields at most `max_ticks` ticks
+/// across `[start, end]`
+pub fn time_step(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    max_ticks: usize,
+) -> (TimeGranularity, u32) {
+    let span_days = (end - start).num_seconds() as f64 / 86_400.0;
+    let max_ticks = max_ticks.max(1) as f64;
+    TIME_STEPS
+        .iter()
+        .copied()
+        .find(|(granularity, step)| {
+            span_days / (granularity.approx_days() * *step as f64) <= max_ticks
+        })
+        .unwrap_or(TIME_STEPS[TIME_STEPS.len() - 1])
+}
+
+/// Date ticks on calendar boundaries (midnight, first of the month or
+/// January 1st) between `start` and `end`
+pub fn time_ticks(start: DateTime<Utc>, end: DateTime<Utc>, max_ticks: usize) -> Vec<TimeTick> {
+    if end <= start {
+        return Vec::new();
+    }
+
+    let (granularity, step) = time_step(start, end, max_ticks);
+    let first_day = start.date_naive();
+    let last_day = end.date_naive();
+
+    let dates: Vec<NaiveDate> = match granularity {
+        TimeGranularity::Day => first_day
+            .iter_days()
+            .take_while(|d| *d <= last_day)
+            // Day 1 of the common era was a Monday
+            .filter(|d| (d.num_days_from_ce() - 1).rem_euclid(step as i32) == 0)
+            .collect(),
+        TimeGranularity::Month => {
+            let first = first_day.year() * 12 + first_day.month0() as i32;
+            let last = last_day.year() * 12 + last_day.month0() as i32;
+            (first..=last)
+                .filter(|m| m.rem_euclid(step as i32) == 0)
+                .filter_map(|m| {
+                    NaiveDate::from_ymd_opt(m.div_euclid(12), m.rem_euclid(12) as u32 + 1, 1)
+                })
+                .collect()
+        }
+        TimeGranularity::Year => (first_day.year()..=last_day.year())
+            .filter(|y| y.rem_euclid(step as i32) == 0)
+            .filter_map(|y| NaiveDate::from_ymd_opt(y, 1, 1))
+            .collect(),
+    };
+
+    dates
+        .into_iter()
+        .map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        .filter(|t| *t >= start && *t <= end)
+        .map(|timestamp| TimeTick {
+            timestamp,
+            label: timestamp.format(granularity.label_format()).to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_nice_ticks() {
+        assert_eq!(
+            nice_ticks(0.0, 100.0, 5),
+            vec![0.0, 20.0, 40.0, 60.0, 80.0, 100.0]
+        );
+        assert_eq!(nice_domain(9_512.0, 13_870.0, 5), (9_000.0, 14_000.0));
+        // 500 steps widen to 9500..13000, which only fits 1000 steps
+        assert_eq!(nice_domain(9_980.0, 12_900.0, 6), (9_000.0, 13_000.0));
+
+        let ticks = nice_ticks(-0.3, 0.7, 5);
+        assert_eq!(ticks.len(), 5);
+        assert_eq!(format_tick(ticks[0], 0.2), "-0.2");
+        assert_eq!(format_tick(ticks[1], 0.2), "0.0");
+        assert_eq!(format_tick(12_000.0, 1_000.0), "12000");
+    }
+
+    #[test]
+    fn test_log_ticks() {
+        assert_eq!(
+            log_ticks(1.0, 100.0, 8),
+            vec![1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0]
+        );
+        assert_eq!(log_ticks(1.0, 1_000.0, 5), vec![1.0, 10.0, 100.0, 1_000.0]);
+        assert_eq!(log_ticks(1.0, 1e8, 4), vec![1.0, 100.0, 10_000.0, 1e6, 1e8]);
+        assert!(log_ticks(9_000.0, 13_000.0, 6).is_empty());
+        assert!(log_ticks(0.0, 10.0, 6).is_empty());
+
+        let (_, ticks) = value_axis(YAxisMode::Log, 9_800.0, 12_500.0, 6);
+        assert!(ticks.len() >= 2);
+        assert_eq!(ticks[0].label, "10000");
+
+        assert_eq!(format_compact(950.0), "950");
+        assert_eq!(format_compact(12_500.0), "12.5k");
+        assert_eq!(format_compact(3_000_000.0), "3M");
+
+        let (domain, ticks) = value_axis(YAxisMode::Percent, -12.0, 35.0, 6);
+        assert_eq!(domain, (-20.0, 40.0));
+        assert_eq!(ticks[2].label, "0%");
+    }
+
+    #[test]
+    fn test_time_ticks_pick_granularity_from_span() {
+        let ticks = time_ticks(date(2024, 1, 3), date(2024, 1, 9), 8);
+        assert_eq!(ticks.len(), 7);
+        assert_eq!(ticks[0].label, "Jan 3");
+
+        // Two weeks at one tick per week, on Mondays
+        let ticks = time_ticks(date(2024, 1, 3), date(2024, 1, 20), 3);
+        let labels: Vec<_> = ticks.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(labels, vec!["Jan 8", "Jan 15"]);
+
+        let ticks = time_ticks(date(2023, 11, 15), date(2024, 11, 15), 7);
+        let labels: Vec<_> = ticks.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec!["Jan 2024", "Mar 2024", "May 2024", "Jul 2024", "Sep 2024", "Nov 2024"]
+        );
+
+        let ticks = time_ticks(date(2014, 6, 1), date(2024, 6, 1), 6);
+        let labels: Vec<_> = ticks.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(labels, vec!["2016", "2018", "2020", "2022", "2024"]);
+    }
+
+    #[test]
+    fn test_time_ticks_empty_range() {
+        assert!(time_ticks(date(2024, 1, 1), date(2024, 1, 1), 6).is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+//! IndexedDB-backed cache of API responses, so pages load from the browser
+//! and keep working while the backend is unreachable. Values are stored as
+//! JSON. Any failure, e.g. IndexedDB being disabled, behaves like a miss.
+
+use std::cell::RefCell;
+
+use js_sys::Promise;
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbRequest, IdbTransactionMode};
+
+const DB_NAME: &str = "portfolio-tracker";
+const DB_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Store {
+    /// Full-resolution price history per asset, extended incrementally
+    PriceHistory,
+    /// Last successful response per request
+    Responses,
+}
+
+thread_local! {
+    /// Connection shared by all requests, opened on first use
+    static DB: RefCell<Option<IdbDatabase>> = const { RefCell::new(None) };
+}
+
+const STORES: [Store; 2] = [Store::PriceHistory, Store::Responses];
+
+impl Store {
+    fn name(self) -> &'static str {
+        match self {
+            Store::PriceHistory => "price_history",
+            Store::Responses => "responses",
+        }
+    }
+}
+
+/// Resolves with the result of `request` once it succeeds
+async fn completion(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let succeeded = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let result = succeeded.result().unwrap_or(JsValue::UNDEFINED);
+            let _ = resolve.call1(&JsValue::UNDEFINED, &result);
+        });
+        let failed = request.clone();
+        let on_error = Closure::once_into_js(move || {
+            let error = failed.error().ok().flatten().map_or(JsValue::UNDEFINED, JsValue::from);
+            let _ = reject.call1(&JsValue::UNDEFINED, &error);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await
+}
+
+/// The shared connection, opening it if needed
+async fn database() -> Result<IdbDatabase, JsValue> {
+    if let Some(db) = DB.with_borrow(Clone::clone) {
+        return Ok(db);
+    }
+    let db = open().await?;
+    DB.set(Some(db.clone()));
+    Ok(db)
+}
+
+/// Drops the shared connection after a failure, e.g. the browser closing
+/// it, so the next request reopens it
+fn forget_database<T>(result: Result<T, JsValue>) -> Result<T, JsValue> {
+    if result.is_err() {
+        DB.set(None);
+    }
+    result
+}
+
+async fn open() -> Result<IdbDatabase, JsValue> {
+    let factory = web_sys::window()
+        .ok_or("no window")?
+        .indexed_db()?
+        .ok_or("IndexedDB is unavailable")?;
+    let request = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+
+    let upgrading = request.clone();
+    let on_upgrade = Closure::once_into_js(move || {
+        let Ok(db) = upgrading.result().and_then(|db| db.dyn_into::<IdbDatabase>()) else {
+            return;
+        };
+        for store in STORES {
+            if !db.object_store_names().contains(store.name()) {
+                let _ = db.create_object_store(store.name());
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+    completion(&request).await?.dyn_into()
+}
+
+async fn get(store: Store, key: &str) -> Result<Option<String>, JsValue> {
+    let request = forget_database(
+        database()
+            .await?
+            .transaction_with_str(store.name())
+            .and_then(|tx| tx.object_store(store.name()))
+            .and_then(|store| store.get(&JsValue::from_str(key))),
+    )?;
+    Ok(completion(&request).await?.as_string())
+}
+
+async fn put(store: Store, key: &str, json: &str) -> Result<(), JsValue> {
+    let request = forget_database(
+        database()
+            .await?
+            .transaction_with_str_and_mode(store.name(), IdbTransactionMode::Readwrite)
+            .and_then(|tx| tx.object_store(store.name()))
+            .and_then(|store| {
+                store.put_with_key(&JsValue::from_str(json), &JsValue::from_str(key))
+            }),
+    )?;
+    completion(&request).await.map(|_| ())
+}
+
+async fn delete(store: Store, key: &str) -> Result<(), JsValue> {
+    let request = forget_database(
+        database()
+            .await?
+            .transaction_with_str_and_mode(store.name(), IdbTransactionMode::Readwrite)
+            .and_then(|tx| tx.object_store(store.name()))
+            .and_then(|store| store.delete(&JsValue::from_str(key))),
+    )?;
+    completion(&request).await.map(|_| ())
+}
+
+pub async fn load<T: DeserializeOwned>(store: Store, key: &str) -> Option<T> {
+    let json = get(store, key).await.ok()??;
+    serde_json::from_str(&json).ok()
+}
+
+pub async fn save<T: Serialize>(store: Store, key: &str, value: &T) {
+    let Ok(json) = serde_json::to_string(value) else {
+        return;
+    };
+    // A failed write only means the next load fetches again
+    let _ = put(store, key, &json).await;
+}
+
+pub async fn remove(store: Store, key: &str) {
+    let _ = delete(store, key).await;
+}
@@ -0,0 +1,31 @@
+use std::sync::OnceLock;
+
+/// `<meta name="api-base-url" content="https://...">` in `index.html`
+/// points the frontend at an API served from somewhere else
+const META_NAME: &str = "api-base-url";
+
+/// The backend serves the frontend, so by default the API is same-origin
+const DEFAULT_BASE_URL: &str = "/api";
+
+/// Base URL of the backend API, without a trailing slash. Resolved once,
+/// from the meta tag, then the `API_BASE_URL` variable at build time, then
+/// the same-origin default.
+pub fn base_url() -> &'static str {
+    static BASE_URL: OnceLock<String> = OnceLock::new();
+    BASE_URL.get_or_init(|| {
+        let url = meta_base_url()
+            .or_else(|| option_env!("API_BASE_URL").map(str::to_string))
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        url.trim_end_matches('/').to_string()
+    })
+}
+
+fn meta_base_url() -> Option<String> {
+    let document = web_sys::window()?.document()?;
+    let meta = document
+        .query_selector(&format!("meta[name=\"{}\"]", META_NAME))
+        .ok()??;
+    meta.get_attribute("content")
+        .map(|content| content.trim().to_string())
+        .filter(|content| !content.is_empty())
+}
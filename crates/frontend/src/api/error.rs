@@ -0,0 +1,47 @@
+use shared::ErrorResponse;
+use thiserror::Error;
+
+/// Why an API call failed
+#[derive(Debug, Clone, Error)]
+pub enum ApiError {
+    /// The request body could not be serialized
+    #[error("could not encode the request: {0}")]
+    Encode(String),
+    /// No response at all, e.g. the server is down or the network is
+    #[error("could not reach the server: {0}")]
+    Network(String),
+    /// The server answered with an error status. The body is the backend's
+    /// `ErrorResponse`, if it sent one.
+    #[error("{}", server_message(*.status, .body.as_ref()))]
+    Server {
+        status: u16,
+        body: Option<ErrorResponse>,
+    },
+    /// A success status with a body that isn't what was asked for
+    #[error("unexpected response from the server: {0}")]
+    Decode(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ApiError::Server { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(404)
+    }
+}
+
+fn server_message(status: u16, body: Option<&ErrorResponse>) -> String {
+    match body {
+        Some(ErrorResponse {
+            error,
+            details: Some(details),
+        }) => format!("{}: {}", error, details),
+        Some(ErrorResponse { error, .. }) => error.clone(),
+        None => format!("server returned HTTP {}", status),
+    }
+}
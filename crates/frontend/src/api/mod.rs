@@ -1,47 +1,451 @@
-use gloo_net::http::Request;
+mod cache;
+mod config;
+mod error;
+mod stream;
+
+pub use config::base_url;
+pub use error::ApiError;
+pub use stream::{subscribe_prices, PriceSubscription};
+
+use cache::Store;
+use chrono::{DateTime, Duration, NaiveTime, SecondsFormat, Utc};
+use gloo_net::http::{Request, Response};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use shared::{
-    GetAssetsResponse, ComparisonRequest, ComparisonResponse,
-    RefreshDataRequest, RefreshDataResponse,
+    AssetDetailResponse, AssetHistoryResponse, BacktestRequest, BacktestResponse,
+    ComparisonRequest, ComparisonResponse, CoverageResponse, CreatePortfolioRequest,
+    ErrorResponse, EventsResponse, GetAssetsResponse, ImportPortfolioRequest, ImportPreview,
+    Portfolio, PortfoliosResponse, PricePoint, RefreshDataRequest, RefreshDataResponse,
 };
 
-const API_BASE_URL: &str = "http://localhost:3000/api";
+/// Cached histories are refetched in full after this long, picking up
+/// corrections to old prices such as split adjustments
+const FULL_REFRESH_DAYS: i64 = 7;
 
-pub async fn fetch_assets() -> Result<GetAssetsResponse, String> {
-    let response = Request::get(&format!("{}/assets", API_BASE_URL))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch assets: {}", e))?;
+/// A response that may have come from the browser's cache
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    pub value: T,
+    /// Set when the backend couldn't be reached: when `value` was fetched
+    pub stale_since: Option<DateTime<Utc>>,
+}
 
-    response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse assets: {}", e))
+impl<T> Cached<T> {
+    fn fresh(value: T) -> Self {
+        Cached {
+            value,
+            stale_since: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedResponse<T> {
+    value: T,
+    fetched_at: DateTime<Utc>,
 }
 
-pub async fn fetch_comparison(request: ComparisonRequest) -> Result<ComparisonResponse, String> {
-    let response = Request::post(&format!("{}/comparison", API_BASE_URL))
-        .json(&request)
-        .map_err(|e| format!("Failed to serialize request: {}", e))?
+#[derive(Serialize, Deserialize)]
+struct CachedHistory {
+    prices: Vec<PricePoint>,
+    /// When the whole history was last fetched
+    full_fetch_at: DateTime<Utc>,
+    /// When new prices were last fetched
+    updated_at: DateTime<Utc>,
+}
+
+fn url(path: &str) -> String {
+    format!("{}{}", base_url(), path)
+}
+
+/// Send `request` and decode a successful response as `T`. The status is
+/// checked first, so error bodies are reported as such rather than failing
+/// to decode.
+async fn send<T: DeserializeOwned>(
+    request: Result<Request, gloo_net::Error>,
+) -> Result<T, ApiError> {
+    let request = request.map_err(|e| ApiError::Encode(e.to_string()))?;
+    let response = request
         .send()
         .await
-        .map_err(|e| format!("Failed to fetch comparison: {}", e))?;
+        .map_err(|e| ApiError::Network(e.to_string()))?;
+    decode(response).await
+}
+
+async fn decode<T: DeserializeOwned>(response: Response) -> Result<T, ApiError> {
+    if !response.ok() {
+        return Err(ApiError::Server {
+            status: response.status(),
+            body: response.json::<ErrorResponse>().await.ok(),
+        });
+    }
 
     response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse comparison: {}", e))
+        .map_err(|e| ApiError::Decode(e.to_string()))
 }
 
-pub async fn refresh_data(request: RefreshDataRequest) -> Result<RefreshDataResponse, String> {
-    let response = Request::post(&format!("{}/refresh", API_BASE_URL))
-        .json(&request)
-        .map_err(|e| format!("Failed to serialize request: {}", e))?
-        .send()
-        .await
-        .map_err(|e| format!("Failed to refresh data: {}", e))?;
+/// Like `send`, but remembers the response under `key` and falls back to
+/// it when the backend can't be reached
+async fn send_cached<T: Serialize + DeserializeOwned>(
+    key: &str,
+    request: Result<Request, gloo_net::Error>,
+) -> Result<Cached<T>, ApiError> {
+    match send(request).await {
+        Ok(value) => {
+            let cached = CachedResponse {
+                value,
+                fetched_at: Utc::now(),
+            };
+            cache::save(Store::Responses, key, &cached).await;
+            Ok(Cached::fresh(cached.value))
+        }
+        Err(e @ ApiError::Network(_)) => {
+            match cache::load::<CachedResponse<T>>(Store::Responses, key).await {
+                Some(cached) => Ok(Cached {
+                    value: cached.value,
+                    stale_since: Some(cached.fetched_at),
+                }),
+                None => Err(e),
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
 
+pub async fn fetch_assets() -> Result<Cached<GetAssetsResponse>, ApiError> {
+    send_cached("assets", Request::get(&url("/assets")).build()).await
+}
+
+pub async fn fetch_asset(id: &str) -> Result<Cached<AssetDetailResponse>, ApiError> {
+    let path = format!("/assets/{}", id);
+    send_cached(&path, Request::get(&url(&path)).build()).await
+}
+
+/// Full-resolution price history of an asset. The history is kept in the
+/// browser and only prices after the last cached one are fetched.
+pub async fn fetch_price_history(id: &str) -> Result<Cached<Vec<PricePoint>>, ApiError> {
+    let now = Utc::now();
+    let cached: Option<CachedHistory> = cache::load(Store::PriceHistory, id).await;
+    let base = cached
+        .as_ref()
+        .filter(|c| now - c.full_fetch_at < Duration::days(FULL_REFRESH_DAYS));
+    let since = base.and_then(|c| c.prices.last()).map(|p| p.timestamp);
+
+    let mut request = Request::get(&url(&format!("/assets/{}/history", id)));
+    if let Some(since) = since {
+        let start = since + Duration::seconds(1);
+        request = request.query([("start_date", start.to_rfc3339_opts(SecondsFormat::Secs, true))]);
+    }
+    let response: AssetHistoryResponse = match send(request.build()).await {
+        Ok(response) => response,
+        Err(e @ ApiError::Network(_)) => {
+            return cached
+                .map(|c| Cached {
+                    value: c.prices,
+                    stale_since: Some(c.updated_at),
+                })
+                .ok_or(e);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let history = match cached.filter(|_| since.is_some()) {
+        Some(mut history) => {
+            history.prices.extend(response.prices);
+            history.updated_at = now;
+            history
+        }
+        None => CachedHistory {
+            prices: response.prices,
+            full_fetch_at: now,
+            updated_at: now,
+        },
+    };
+    cache::save(Store::PriceHistory, id, &history).await;
+    Ok(Cached::fresh(history.prices))
+}
+
+/// Comparisons kept in the browser; the least recently used are evicted
+const MAX_CACHED_COMPARISONS: usize = 20;
+/// Cache keys of the stored comparisons, most recently used first
+const COMPARISON_KEYS: &str = "comparison-keys";
+
+#[derive(Serialize, Deserialize)]
+struct CachedComparison {
+    /// The request the cached series were normalized from
+    request: ComparisonRequest,
+    response: ComparisonResponse,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Cache key of a comparison. Ranges ending "now" move every request, so
+/// dates only count to the day.
+fn comparison_key(request: &ComparisonRequest) -> String {
+    let day = |t: DateTime<Utc>| t.date_naive().and_time(NaiveTime::MIN).and_utc();
+    let mut request = request.clone();
+    request.start_date = day(request.start_date);
+    request.end_date = day(request.end_date);
+    request.since = None;
+    format!("comparison:{}", serde_json::to_string(&request).unwrap_or_default())
+}
+
+/// Last point every series of `response` has, from which it can be
+/// extended. Downsampled series are always refetched, as their points
+/// shift when the series grows.
+fn extendable_since(
+    request: &ComparisonRequest,
+    response: &ComparisonResponse,
+) -> Option<DateTime<Utc>> {
+    if request.max_points.is_some() {
+        return None;
+    }
     response
-        .json()
+        .series
+        .iter()
+        .map(|s| s.points.last().map(|p| p.timestamp))
+        .min()
+        .flatten()
+}
+
+/// `cached` extended with `update`, which holds only points after `since`.
+/// Metrics and events cover the whole range and come from `update`.
+fn merge_comparison(
+    cached: ComparisonResponse,
+    update: ComparisonResponse,
+    since: DateTime<Utc>,
+) -> ComparisonResponse {
+    let mut cached_series = cached.series;
+    let series = update
+        .series
+        .into_iter()
+        .map(|mut series| {
+            if let Some(old) = cached_series.iter_mut().find(|s| s.asset_id == series.asset_id) {
+                let mut points = std::mem::take(&mut old.points);
+                points.retain(|p| p.timestamp <= since);
+                points.append(&mut series.points);
+                series.points = points;
+            }
+            series
+        })
+        .collect();
+    ComparisonResponse { series, ..update }
+}
+
+/// Move `key` to the front of `keys`, returning the keys beyond `max`
+fn touch_key(keys: &mut Vec<String>, key: &str, max: usize) -> Vec<String> {
+    keys.retain(|k| k != key);
+    keys.insert(0, key.to_string());
+    keys.split_off(max.min(keys.len()))
+}
+
+async fn save_comparison(key: &str, comparison: &CachedComparison) {
+    cache::save(Store::Responses, key, comparison).await;
+    let mut keys: Vec<String> = cache::load(Store::Responses, COMPARISON_KEYS)
         .await
-        .map_err(|e| format!("Failed to parse refresh response: {}", e))
+        .unwrap_or_default();
+    for evicted in touch_key(&mut keys, key, MAX_CACHED_COMPARISONS) {
+        cache::remove(Store::Responses, &evicted).await;
+    }
+    cache::save(Store::Responses, COMPARISON_KEYS, &keys).await;
+}
+
+/// Run a comparison. The result is kept in the browser, and a repeated
+/// comparison only fetches the points after the cached ones, keeping the
+/// cached start so the series stay normalized to the same base.
+pub async fn fetch_comparison(
+    request: ComparisonRequest,
+) -> Result<Cached<ComparisonResponse>, ApiError> {
+    let key = comparison_key(&request);
+    let cached: Option<CachedComparison> = cache::load(Store::Responses, &key).await;
+    let since = cached.as_ref().and_then(|c| extendable_since(&c.request, &c.response));
+    let request = match (&cached, since) {
+        (Some(cached), Some(since)) => ComparisonRequest {
+            start_date: cached.request.start_date,
+            since: Some(since),
+            ..request
+        },
+        _ => ComparisonRequest { since: None, ..request },
+    };
+
+    let sent = send(Request::post(&url("/comparison")).json(&request)).await;
+    let response: ComparisonResponse = match sent {
+        Ok(response) => response,
+        Err(e @ ApiError::Network(_)) => {
+            return cached
+                .map(|c| Cached {
+                    value: c.response,
+                    stale_since: Some(c.fetched_at),
+                })
+                .ok_or(e);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let response = match (cached, since) {
+        (Some(cached), Some(since)) => merge_comparison(cached.response, response, since),
+        _ => response,
+    };
+    let comparison = CachedComparison {
+        request: ComparisonRequest { since: None, ..request },
+        response,
+        fetched_at: Utc::now(),
+    };
+    save_comparison(&key, &comparison).await;
+    Ok(Cached::fresh(comparison.response))
+}
+
+pub async fn fetch_coverage(asset_ids: &[String]) -> Result<CoverageResponse, ApiError> {
+    send(
+        Request::get(&url("/coverage"))
+            .query([("assets", asset_ids.join(","))])
+            .build(),
+    )
+    .await
+}
+
+pub async fn fetch_events() -> Result<EventsResponse, ApiError> {
+    send(Request::get(&url("/events")).build()).await
+}
+
+pub async fn fetch_portfolios() -> Result<PortfoliosResponse, ApiError> {
+    send(Request::get(&url("/portfolios")).build()).await
+}
+
+pub async fn fetch_portfolio(id: &str) -> Result<Portfolio, ApiError> {
+    send(Request::get(&url(&format!("/portfolios/{}", id))).build()).await
+}
+
+pub async fn create_portfolio(request: &CreatePortfolioRequest) -> Result<Portfolio, ApiError> {
+    send(Request::post(&url("/portfolios")).json(request)).await
+}
+
+/// Import a portfolio from a broker CSV, or with `dry_run` only preview it
+pub async fn import_portfolio(request: &ImportPortfolioRequest) -> Result<ImportPreview, ApiError> {
+    send(Request::post(&url("/portfolios/import")).json(request)).await
+}
+
+pub async fn backtest_portfolio(request: &BacktestRequest) -> Result<BacktestResponse, ApiError> {
+    send(Request::post(&url("/portfolios/backtest")).json(request)).await
+}
+
+pub async fn refresh_data(request: RefreshDataRequest) -> Result<RefreshDataResponse, ApiError> {
+    send(Request::post(&url("/refresh")).json(&request)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use shared::{AssetSeries, ChartQuery, NormalizedPricePoint};
+
+    fn at(d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, d, h, 0, 0).unwrap()
+    }
+
+    fn series(asset_id: &str, points: &[(u32, f64)]) -> AssetSeries {
+        AssetSeries {
+            asset_id: asset_id.to_string(),
+            label: None,
+            points: points
+                .iter()
+                .map(|&(d, value)| NormalizedPricePoint {
+                    timestamp: at(d, 0),
+                    normalized_value: value,
+                    return_pct: value - 100.0,
+                })
+                .collect(),
+        }
+    }
+
+    fn response(series: Vec<AssetSeries>) -> ComparisonResponse {
+        ComparisonResponse {
+            series,
+            metrics: vec![],
+            events: vec![],
+        }
+    }
+
+    fn values(series: &AssetSeries) -> Vec<f64> {
+        series.points.iter().map(|p| p.normalized_value).collect()
+    }
+
+    #[test]
+    fn test_comparison_key() {
+        let query = ChartQuery {
+            assets: "SPY,BTC".to_string(),
+            ..Default::default()
+        };
+        let morning = query.comparison_request(at(5, 9));
+        let evening = query.comparison_request(at(5, 21));
+        assert_eq!(comparison_key(&morning), comparison_key(&evening));
+        assert_ne!(comparison_key(&morning), comparison_key(&query.comparison_request(at(6, 9))));
+
+        let extending = ComparisonRequest {
+            since: Some(at(4, 0)),
+            ..evening.clone()
+        };
+        assert_eq!(comparison_key(&extending), comparison_key(&evening));
+        let downsampled = ComparisonRequest {
+            max_points: Some(100),
+            ..evening.clone()
+        };
+        assert_ne!(comparison_key(&downsampled), comparison_key(&evening));
+    }
+
+    #[test]
+    fn test_extendable_since() {
+        let request = ChartQuery::default().comparison_request(at(5, 0));
+        let cached = response(vec![
+            series("SPY", &[(1, 100.0), (4, 101.0)]),
+            series("BTC", &[(1, 100.0), (2, 99.0), (3, 98.0)]),
+        ]);
+        assert_eq!(extendable_since(&request, &cached), Some(at(3, 0)));
+
+        let empty = response(vec![series("SPY", &[(1, 100.0)]), series("BTC", &[])]);
+        assert_eq!(extendable_since(&request, &empty), None);
+        let downsampled = ComparisonRequest {
+            max_points: Some(100),
+            ..request
+        };
+        assert_eq!(extendable_since(&downsampled, &cached), None);
+    }
+
+    #[test]
+    fn test_merge_comparison() {
+        let cached = response(vec![
+            series("SPY", &[(1, 100.0), (4, 101.0)]),
+            series("BTC", &[(1, 100.0), (2, 99.0), (3, 98.0)]),
+        ]);
+        // Points after `since` are replaced, so a corrected last price wins
+        let update = response(vec![
+            series("SPY", &[(4, 102.0), (5, 103.0)]),
+            series("BTC", &[(4, 97.0)]),
+        ]);
+        let merged = merge_comparison(cached, update, at(3, 0));
+        assert_eq!(merged.series.len(), 2);
+        assert_eq!(values(&merged.series[0]), vec![100.0, 102.0, 103.0]);
+        assert_eq!(values(&merged.series[1]), vec![100.0, 99.0, 98.0, 97.0]);
+
+        let unchanged = merge_comparison(
+            response(vec![series("SPY", &[(1, 100.0), (2, 101.0)])]),
+            response(vec![series("SPY", &[])]),
+            at(2, 0),
+        );
+        assert_eq!(values(&unchanged.series[0]), vec![100.0, 101.0]);
+    }
+
+    #[test]
+    fn test_touch_key() {
+        let mut keys = vec!["a".to_string(), "b".to_string()];
+        assert!(touch_key(&mut keys, "c", 3).is_empty());
+        assert_eq!(keys, ["c", "a", "b"]);
+
+        assert!(touch_key(&mut keys, "b", 3).is_empty());
+        assert_eq!(keys, ["b", "c", "a"]);
+
+        assert_eq!(touch_key(&mut keys, "d", 3), ["a"]);
+        assert_eq!(keys, ["d", "b", "c"]);
+    }
 }
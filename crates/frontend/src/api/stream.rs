@@ -0,0 +1,146 @@
+//! Live prices over Server-Sent Events
+
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use js_sys::encode_uri_component;
+use shared::{PriceUpdate, PRICE_EVENT, RESET_EVENT};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, EventSource, MessageEvent};
+
+use super::url;
+
+const MIN_RETRY_MS: i32 = 1_000;
+const MAX_RETRY_MS: i32 = 30_000;
+
+struct Connection {
+    source: EventSource,
+    // Kept alive for as long as the source can call them
+    _listeners: Vec<Closure<dyn FnMut(Event)>>,
+}
+
+struct Inner {
+    url: String,
+    on_prices: Box<dyn Fn(PriceUpdate)>,
+    on_reset: Box<dyn Fn()>,
+    connection: RefCell<Option<Connection>>,
+    last_event_id: RefCell<Option<String>>,
+    retry_ms: Cell<i32>,
+}
+
+/// Open stream of new prices; dropping it disconnects.
+///
+/// The browser reconnects by itself after network errors, resuming with the
+/// `Last-Event-ID` header. If it gives up, e.g. because the server answered
+/// with an error, the stream is reopened with backoff, resuming after the
+/// last event seen.
+pub struct PriceSubscription {
+    inner: Rc<Inner>,
+}
+
+impl Drop for PriceSubscription {
+    fn drop(&mut self) {
+        if let Some(connection) = self.inner.connection.take() {
+            connection.source.close();
+        }
+    }
+}
+
+/// Subscribe to prices stored for `asset_ids`. `on_reset` is called when
+/// updates were missed, and whatever was loaded should be reloaded.
+pub fn subscribe_prices(
+    asset_ids: &[String],
+    on_prices: impl Fn(PriceUpdate) + 'static,
+    on_reset: impl Fn() + 'static,
+) -> PriceSubscription {
+    let inner = Rc::new(Inner {
+        url: format!(
+            "{}?assets={}",
+            url("/prices/stream"),
+            encode_uri_component(&asset_ids.join(","))
+        ),
+        on_prices: Box::new(on_prices),
+        on_reset: Box::new(on_reset),
+        connection: RefCell::new(None),
+        last_event_id: RefCell::new(None),
+        retry_ms: Cell::new(MIN_RETRY_MS),
+    });
+    connect(&inner);
+    PriceSubscription { inner }
+}
+
+fn connect(inner: &Rc<Inner>) {
+    let url = match inner.last_event_id.borrow().as_deref() {
+        Some(id) => format!("{}&last_event_id={}", inner.url, encode_uri_component(id)),
+        None => inner.url.clone(),
+    };
+    let Ok(source) = EventSource::new(&url) else {
+        schedule_reconnect(inner);
+        return;
+    };
+
+    let listener = |inner: Weak<Inner>, handle: fn(&Inner, &MessageEvent)| {
+        Closure::<dyn FnMut(Event)>::new(move |event: Event| {
+            let (Some(inner), Ok(event)) = (inner.upgrade(), event.dyn_into::<MessageEvent>()) else {
+                return;
+            };
+            let id = event.last_event_id();
+            if !id.is_empty() {
+                inner.last_event_id.replace(Some(id));
+            }
+            inner.retry_ms.set(MIN_RETRY_MS);
+            handle(&inner, &event);
+        })
+    };
+    let on_prices = listener(Rc::downgrade(inner), |inner, event| {
+        let update = event
+            .data()
+            .as_string()
+            .and_then(|data| serde_json::from_str::<PriceUpdate>(&data).ok());
+        if let Some(update) = update {
+            (inner.on_prices)(update);
+        }
+    });
+    let on_reset = listener(Rc::downgrade(inner), |inner, _| (inner.on_reset)());
+
+    let weak = Rc::downgrade(inner);
+    let on_error = Closure::<dyn FnMut(Event)>::new(move |_: Event| {
+        let Some(inner) = weak.upgrade() else {
+            return;
+        };
+        let closed = inner
+            .connection
+            .borrow()
+            .as_ref()
+            .is_some_and(|c| c.source.ready_state() == EventSource::CLOSED);
+        if closed {
+            schedule_reconnect(&inner);
+        }
+    });
+
+    let _ = source.add_event_listener_with_callback(PRICE_EVENT, on_prices.as_ref().unchecked_ref());
+    let _ = source.add_event_listener_with_callback(RESET_EVENT, on_reset.as_ref().unchecked_ref());
+    let _ = source.add_event_listener_with_callback("error", on_error.as_ref().unchecked_ref());
+    inner.connection.replace(Some(Connection {
+        source,
+        _listeners: vec![on_prices, on_reset, on_error],
+    }));
+}
+
+fn schedule_reconnect(inner: &Rc<Inner>) {
+    let delay = inner.retry_ms.get();
+    inner.retry_ms.set((delay * 2).min(MAX_RETRY_MS));
+
+    let weak = Rc::downgrade(inner);
+    let reconnect = Closure::once_into_js(move || {
+        // Dropped in the meantime: stay disconnected
+        if let Some(inner) = weak.upgrade() {
+            connect(&inner);
+        }
+    });
+    if let Some(window) = web_sys::window() {
+        let _ = window
+            .set_timeout_with_callback_and_timeout_and_arguments_0(reconnect.unchecked_ref(), delay);
+    }
+}
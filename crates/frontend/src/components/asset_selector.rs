@@ -1,20 +1,24 @@
-use leptos::*;
-use shared::Asset;
+use leptos::prelude::*;
+use leptos_router::components::A;
+use shared::{Asset, Portfolio};
 
 #[component]
 pub fn AssetSelector(
     assets: ReadSignal<Vec<Asset>>,
-    selected_assets: ReadSignal<Vec<String>>,
-    set_selected_assets: WriteSignal<Vec<String>>,
+    #[prop(into)] selected_assets: Signal<Vec<String>>,
+    #[prop(into)] on_change: Callback<Vec<String>>,
+    /// Saved portfolios, listed after the assets and selected by
+    /// `Portfolio::comparison_id`
+    #[prop(optional, into)] portfolios: Signal<Vec<Portfolio>>,
 ) -> impl IntoView {
     let toggle_asset = move |asset_id: String| {
-        let mut current = selected_assets.get();
+        let mut current = selected_assets.get_untracked();
         if current.contains(&asset_id) {
             current.retain(|id| id != &asset_id);
         } else {
             current.push(asset_id);
         }
-        set_selected_assets.set(current);
+        on_change.run(current);
     };
 
     view! {
@@ -39,7 +43,44 @@ pub fn AssetSelector(
                                 on:click=move |_| toggle_asset(asset_id_clone.clone())
                             >
                                 <div class="asset-name">{asset.name.clone()}</div>
-                                <div class="asset-symbol">{asset.symbol.clone()}</div>
+                                <div class="asset-symbol">
+                                    {asset.symbol.clone()}
+                                    // Opening the details shouldn't also toggle the selection
+                                    <span class="asset-details" on:click=|ev| ev.stop_propagation()>
+                                        <A href=format!("/assets/{}", asset_id)>"Details"</A>
+                                    </span>
+                                </div>
+                            </div>
+                        }
+                    }).collect::<Vec<_>>()
+                }}
+                {move || {
+                    portfolios.get().into_iter().map(|portfolio| {
+                        let comparison_id = portfolio.comparison_id();
+                        let is_selected = selected_assets.get().contains(&comparison_id);
+                        let holdings = portfolio
+                            .assets
+                            .iter()
+                            .map(|a| a.asset_id.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" · ");
+
+                        view! {
+                            <div
+                                class=if is_selected {
+                                    "asset-item portfolio selected"
+                                } else {
+                                    "asset-item portfolio"
+                                }
+                                on:click=move |_| toggle_asset(comparison_id.clone())
+                            >
+                                <div class="asset-name">{portfolio.name}</div>
+                                <div class="asset-symbol">
+                                    {holdings}
+                                    <span class="asset-details" on:click=|ev| ev.stop_propagation()>
+                                        <A href=format!("/portfolios/{}", portfolio.id)>"Details"</A>
+                                    </span>
+                                </div>
                             </div>
                         }
                     }).collect::<Vec<_>>()
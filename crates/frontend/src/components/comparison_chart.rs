@@ -1,19 +1,238 @@
-use leptos::*;
-use chrono::{Utc, Duration};
-use shared::ComparisonRequest;
+use leptos::html::Canvas;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use chrono::{DateTime, Utc};
+use charting::{
+    pan_range, selection_range, DrawdownChart, EventMarker, LineChart, Overlay, Series,
+    YAxisMode,
+};
+use shared::{
+    ChartQuery, ChartScale, ChartView, ComparisonResponse, EventCategory, MarketEvent,
+    PerformanceMetrics, PriceUpdate, PORTFOLIO_ID_PREFIX,
+};
 use crate::api;
+use crate::components::{use_data_freshness, MetricsTable};
 
+const CHART_WIDTH: f64 = 1000.0;
+const CHART_HEIGHT: f64 = 450.0;
+
+type TimeRange = (DateTime<Utc>, DateTime<Utc>);
+
+/// Each compared series with the streamed assets its values derive from:
+/// the asset itself or a portfolio's holdings, plus the unit of account
+type FollowedAssets = Vec<(String, Vec<String>)>;
+
+fn category_label(category: EventCategory) -> &'static str {
+    match category {
+        EventCategory::Halving => "Halvings",
+        EventCategory::FedDecision => "Fed decisions",
+        EventCategory::Recession => "Recessions",
+        EventCategory::Note => "Notes",
+    }
+}
+
+fn event_marker(event: &MarketEvent) -> EventMarker {
+    let label = match &event.asset_id {
+        Some(asset_id) if event.category != EventCategory::Halving => {
+            format!("{} ({})", event.title, asset_id)
+        }
+        _ => event.title.clone(),
+    };
+    EventMarker {
+        start: event.start_date,
+        end: event.end_date,
+        label,
+        color: event.category.color().to_string(),
+    }
+}
+
+/// One line per asset, tracking the value of the initial amount over time,
+/// or its percentage change from the start in `Percent` mode. The drawdown
+/// view plots each asset's distance below its running peak instead. Events
+/// in `hidden` categories are left off.
+fn build_chart(
+    response: &ComparisonResponse,
+    view: ChartView,
+    y_axis: YAxisMode,
+    x_range: Option<TimeRange>,
+    hidden: &[EventCategory],
+) -> LineChart {
+    response
+        .events
+        .iter()
+        .filter(|event| !hidden.contains(&event.category))
+        .map(event_marker)
+        .fold(build_lines(response, view, y_axis, x_range), LineChart::with_event)
+}
+
+fn build_lines(
+    response: &ComparisonResponse,
+    view: ChartView,
+    y_axis: YAxisMode,
+    x_range: Option<TimeRange>,
+) -> LineChart {
+    if view == ChartView::Drawdown {
+        let chart = response.series.iter().fold(
+            DrawdownChart::new(CHART_WIDTH, CHART_HEIGHT),
+            |chart, series| {
+                chart.with_series(Series::new(
+                    series.display_name().to_string(),
+                    series.points.iter().map(|p| (p.timestamp, p.normalized_value)).collect(),
+                ))
+            },
+        );
+        return match x_range {
+            Some((start, end)) => chart.with_x_range(start, end),
+            None => chart,
+        }
+        .line_chart();
+    }
+
+    let chart = LineChart::new(CHART_WIDTH, CHART_HEIGHT).with_y_axis(y_axis);
+    let chart = match x_range {
+        Some((start, end)) => chart.with_x_range(start, end),
+        None => chart,
+    };
+    response.series.iter().fold(chart, |chart, series| {
+        chart.with_series(Series::new(
+            series.display_name().to_string(),
+            series
+                .points
+                .iter()
+                .map(|p| match y_axis {
+                    YAxisMode::Percent => (p.timestamp, p.return_pct),
+                    YAxisMode::Linear | YAxisMode::Log => (p.timestamp, p.normalized_value),
+                })
+                .collect(),
+        ))
+    })
+}
+
+/// Metrics listed under the same names as the chart legend, so portfolios
+/// show their name rather than their id
+fn table_metrics(response: &ComparisonResponse) -> Vec<PerformanceMetrics> {
+    response
+        .metrics
+        .iter()
+        .map(|metrics| {
+            let name = response
+                .series
+                .iter()
+                .find(|s| s.asset_id == metrics.asset_id)
+                .map_or(&metrics.asset_id[..], |s| s.display_name());
+            PerformanceMetrics {
+                asset_id: name.to_string(),
+                ..metrics.clone()
+            }
+        })
+        .collect()
+}
+
+/// Whether `update` has prices after the last shown point of a series
+/// derived from its asset
+fn extends_series(
+    response: &ComparisonResponse,
+    followed: &FollowedAssets,
+    update: &PriceUpdate,
+) -> bool {
+    let Some(latest) = update.prices.iter().map(|p| p.timestamp).max() else {
+        return false;
+    };
+    followed
+        .iter()
+        .filter(|(_, sources)| sources.contains(&update.asset_id))
+        .any(|(series_id, _)| {
+            response
+                .series
+                .iter()
+                .find(|s| &s.asset_id == series_id)
+                .and_then(|s| s.points.last())
+                .is_none_or(|last| last.timestamp < latest)
+        })
+}
+
+fn y_axis_mode(scale: ChartScale) -> YAxisMode {
+    match scale {
+        ChartScale::Linear => YAxisMode::Linear,
+        ChartScale::Log => YAxisMode::Log,
+        ChartScale::Percent => YAxisMode::Percent,
+    }
+}
+
+fn scale_value(scale: ChartScale) -> &'static str {
+    match scale {
+        ChartScale::Linear => "linear",
+        ChartScale::Log => "log",
+        ChartScale::Percent => "percent",
+    }
+}
+
+fn parse_scale(value: &str) -> ChartScale {
+    match value {
+        "log" => ChartScale::Log,
+        "percent" => ChartScale::Percent,
+        _ => ChartScale::Linear,
+    }
+}
+
+/// Comparison described by `query`, refetched whenever the assets, range,
+/// amount or unit change. View and scale changes go back through
+/// `on_change`; zoom and event toggles stay local.
 #[component]
 pub fn ComparisonChart(
-    selected_assets: ReadSignal<Vec<String>>,
+    #[prop(into)] query: Signal<ChartQuery>,
+    #[prop(into)] on_change: Callback<ChartQuery>,
 ) -> impl IntoView {
-    let (chart_data, set_chart_data) = create_signal(None);
-    let (loading, set_loading) = create_signal(false);
-    let (error, set_error) = create_signal(None::<String>);
+    let (chart_data, set_chart_data) = signal(None::<ComparisonResponse>);
+    let (loading, set_loading) = signal(false);
+    let (error, set_error) = signal(None::<String>);
+    let y_axis = Memo::new(move |_| query.with(|q| y_axis_mode(q.scale)));
+    let chart_view = Memo::new(move |_| query.with(|q| q.view));
+    let selected_assets = Memo::new(move |_| query.with(ChartQuery::asset_ids));
+    // Everything that changes the data rather than how it is drawn
+    let comparison = Memo::new(move |_| {
+        query.with(|q| (q.asset_ids(), q.start, q.end, q.amount, q.unit.clone()))
+    });
+    let canvas_ref = NodeRef::<Canvas>::new();
+    let freshness = use_data_freshness();
+
+    // Range the user asked to compare, and the range the current data was
+    // fetched for; they differ after zooming with rebasing enabled
+    let (default_range, set_default_range) = signal(None::<TimeRange>);
+    let (query_range, set_query_range) = signal(None::<TimeRange>);
+    // Visible window when zoomed without rebasing
+    let (x_range, set_x_range) = signal(None::<TimeRange>);
+    let (rebase, set_rebase) = signal(false);
+    let (pointer_x, set_pointer_x) = signal(None::<f64>);
+    let (drag_start, set_drag_start) = signal(None::<f64>);
+    let (hidden_categories, set_hidden_categories) = signal(Vec::<EventCategory>::new());
 
-    let fetch_comparison = move || {
-        let asset_ids = selected_assets.get();
-        if asset_ids.is_empty() {
+    // Redraw whenever the data, axis mode, zoom or pointer changes, or the
+    // canvas is (re)mounted
+    Effect::new(move |_| {
+        let Some(canvas) = canvas_ref.get() else {
+            return;
+        };
+        let overlay = Overlay {
+            pointer_x: pointer_x.get(),
+            selection: drag_start.get().zip(pointer_x.get()),
+        };
+        let result = chart_data.with(|data| {
+            data.as_ref().map(|data| {
+                hidden_categories.with(|hidden| {
+                    build_chart(data, chart_view.get(), y_axis.get(), x_range.get(), hidden)
+                })
+                .with_overlay(overlay)
+                    .render(&canvas)
+            })
+        });
+        if let Some(Err(e)) = result {
+            set_error.set(Some(format!("Failed to render chart: {:?}", e)));
+        }
+    });
+
+    let fetch_comparison = move |(start_date, end_date): TimeRange| {
+        if selected_assets.with_untracked(Vec::is_empty) {
             return;
         }
 
@@ -21,22 +240,20 @@ pub fn ComparisonChart(
             set_loading.set(true);
             set_error.set(None);
 
-            let end_date = Utc::now();
-            let start_date = end_date - Duration::days(365);
-
-            let request = ComparisonRequest {
-                asset_ids,
-                start_date,
-                end_date,
-                initial_amount: 10000.0,
-            };
+            // Drawing downsamples to the visible width, which keeps detail
+            // when zooming in without re-querying
+            let mut request = query.with_untracked(|q| q.comparison_request(end_date));
+            request.start_date = start_date;
+            request.end_date = end_date;
 
             match api::fetch_comparison(request).await {
                 Ok(response) => {
-                    set_chart_data.set(Some(response));
+                    freshness.report(&response);
+                    set_chart_data.set(Some(response.value));
+                    set_query_range.set(Some((start_date, end_date)));
                 }
                 Err(e) => {
-                    set_error.set(Some(e));
+                    set_error.set(Some(e.to_string()));
                 }
             }
 
@@ -44,6 +261,159 @@ pub fn ComparisonChart(
         });
     };
 
+    let compare = move || {
+        let request = query.with_untracked(|q| q.comparison_request(Utc::now()));
+        let range = (request.start_date, request.end_date);
+        set_default_range.set(Some(range));
+        set_x_range.set(None);
+        fetch_comparison(range);
+    };
+
+    Effect::new(move |_| {
+        comparison.track();
+        if selected_assets.with_untracked(Vec::is_empty) {
+            set_chart_data.set(None);
+            return;
+        }
+        compare();
+    });
+
+    // Open-ended ranges follow new prices as they are stored, unless zoomed
+    let follow_latest = move || {
+        let zoomed = x_range.get_untracked().is_some()
+            || query_range.get_untracked() != default_range.get_untracked();
+        if query.with_untracked(|q| q.end.is_none()) && !zoomed {
+            compare();
+        }
+    };
+
+    // Portfolios are streamed through their holdings
+    let (followed, set_followed) = signal(FollowedAssets::new());
+    let series_sources = Memo::new(move |_| {
+        query.with(|q| (q.asset_ids(), q.unit.clone().filter(|unit| !unit.is_empty())))
+    });
+    Effect::new(move |_| {
+        let (asset_ids, unit) = series_sources.get();
+        spawn_local(async move {
+            let mut resolved = FollowedAssets::with_capacity(asset_ids.len());
+            for asset_id in &asset_ids {
+                let mut sources = match asset_id.strip_prefix(PORTFOLIO_ID_PREFIX) {
+                    // A portfolio that fails to load just isn't followed
+                    Some(portfolio_id) => api::fetch_portfolio(portfolio_id)
+                        .await
+                        .map(|p| p.assets.into_iter().map(|a| a.asset_id).collect())
+                        .unwrap_or_default(),
+                    None => vec![asset_id.clone()],
+                };
+                sources.extend(unit.clone());
+                resolved.push((asset_id.clone(), sources));
+            }
+            if series_sources.with_untracked(|(current, _)| *current == asset_ids) {
+                set_followed.set(resolved);
+            }
+        });
+    });
+    let streamed_assets = Memo::new(move |_| {
+        let mut asset_ids: Vec<String> =
+            followed.with(|f| f.iter().flat_map(|(_, sources)| sources.clone()).collect());
+        asset_ids.sort();
+        asset_ids.dedup();
+        asset_ids
+    });
+
+    // Series are normalized, and may be priced in a unit or backtested, so
+    // streamed prices only say when to fetch the new points
+    let on_prices = move |update: PriceUpdate| {
+        let stale = chart_data.with_untracked(|data| {
+            followed.with_untracked(|followed| {
+                data.as_ref().is_some_and(|data| extends_series(data, followed, &update))
+            })
+        });
+        if stale {
+            follow_latest();
+        }
+    };
+
+    Effect::new(move |_| {
+        let asset_ids = streamed_assets.get();
+        (!asset_ids.is_empty())
+            .then(|| api::subscribe_prices(&asset_ids, on_prices, follow_latest))
+    });
+
+    let update_query = move |update: &dyn Fn(&mut ChartQuery)| {
+        let mut next = query.get_untracked();
+        update(&mut next);
+        on_change.run(next);
+    };
+
+    // Pointer position in chart coordinates, undoing any CSS scaling of the canvas
+    let chart_x = move |ev: &web_sys::MouseEvent| {
+        let width = canvas_ref
+            .get_untracked()
+            .map_or(0, |canvas| canvas.client_width());
+        if width == 0 {
+            return ev.offset_x() as f64;
+        }
+        ev.offset_x() as f64 * CHART_WIDTH / width as f64
+    };
+
+    let current_chart = move || {
+        chart_data.with_untracked(|data| {
+            data.as_ref()
+                .map(|data| {
+                    build_chart(
+                        data,
+                        chart_view.get_untracked(),
+                        y_axis.get_untracked(),
+                        x_range.get_untracked(),
+                        &hidden_categories.get_untracked(),
+                    )
+                })
+        })
+    };
+
+    let finish_drag = move |x: f64| {
+        let Some(start) = drag_start.get_untracked() else {
+            return;
+        };
+        set_drag_start.set(None);
+        let Some(chart) = current_chart() else {
+            return;
+        };
+        let Some(range) = chart.layout().and_then(|layout| selection_range(&layout, start, x)) else {
+            return;
+        };
+
+        set_x_range.set(Some(range));
+        if rebase.get_untracked() {
+            // Restart normalization at the new left edge; keep data up to
+            // the original end so the view can still be panned forward
+            let end = default_range.get_untracked().map_or(range.1, |(_, end)| end);
+            fetch_comparison((range.0, end));
+        }
+    };
+
+    let reset_zoom = move || {
+        set_x_range.set(None);
+        if let Some(default) = default_range.get_untracked() {
+            if query_range.get_untracked() != Some(default) {
+                fetch_comparison(default);
+            }
+        }
+    };
+
+    let pan = move |ev: web_sys::WheelEvent| {
+        let Some(range) = x_range.get_untracked() else {
+            return;
+        };
+        let Some(bounds) = current_chart().and_then(|chart| chart.time_bounds()) else {
+            return;
+        };
+        ev.prevent_default();
+        let delta = if ev.delta_x() != 0.0 { ev.delta_x() } else { ev.delta_y() };
+        set_x_range.set(Some(pan_range(range, bounds, delta / CHART_WIDTH)));
+    };
+
     view! {
         <div class="card">
             <h2>"Performance Comparison"</h2>
@@ -55,15 +425,15 @@ pub fn ComparisonChart(
                         <p style="color: #94a3b8; padding: 2rem; text-align: center;">
                             "Select at least one asset to view comparison"
                         </p>
-                    }.into_view()
+                    }.into_any()
                 } else {
                     view! {
                         <div>
                             <button
-                                on:click=move |_| fetch_comparison()
+                                on:click=move |_| compare()
                                 disabled=move || loading.get()
                             >
-                                {move || if loading.get() { "Loading..." } else { "Compare Assets" }}
+                                {move || if loading.get() { "Loading..." } else { "Refresh" }}
                             </button>
 
                             {move || {
@@ -72,26 +442,173 @@ pub fn ComparisonChart(
                                         <div class="error" style="margin-top: 1rem;">
                                             <p><strong>"Error: "</strong> {err}</p>
                                         </div>
-                                    }.into_view()
-                                } else if let Some(_data) = chart_data.get() {
+                                    }.into_any()
+                                } else if chart_data.with(Option::is_some) {
                                     view! {
                                         <div class="chart-container">
-                                            <p style="color: #94a3b8; text-align: center;">
-                                                "Chart visualization coming soon!"
-                                            </p>
-                                            <p style="color: #64748b; text-align: center; margin-top: 1rem; font-size: 0.9rem;">
-                                                "We'll implement the custom Rust charting library next"
-                                            </p>
+                                            <div class="chart-controls">
+                                                <span class="chart-hint">
+                                                    "Drag to zoom, scroll to pan, double-click to reset"
+                                                </span>
+                                                <label>
+                                                    <input
+                                                        type="checkbox"
+                                                        prop:checked=move || rebase.get()
+                                                        on:change=move |ev| set_rebase.set(event_target_checked(&ev))
+                                                    />
+                                                    " Rebase on zoom"
+                                                </label>
+                                                <label>
+                                                    "Chart "
+                                                    <select
+                                                        prop:value=move || match chart_view.get() {
+                                                            ChartView::Performance => "performance",
+                                                            ChartView::Drawdown => "drawdown",
+                                                        }
+                                                        on:change=move |ev| {
+                                                            let view = match event_target_value(&ev).as_str() {
+                                                                "drawdown" => ChartView::Drawdown,
+                                                                _ => ChartView::Performance,
+                                                            };
+                                                            update_query(&|q| q.view = view)
+                                                        }
+                                                    >
+                                                        <option value="performance">"Performance"</option>
+                                                        <option value="drawdown">"Drawdown"</option>
+                                                    </select>
+                                                </label>
+                                                <label>
+                                                    "Scale "
+                                                    <select
+                                                        disabled=move || chart_view.get() == ChartView::Drawdown
+                                                        prop:value=move || query.with(|q| scale_value(q.scale))
+                                                        on:change=move |ev| {
+                                                            let scale = parse_scale(&event_target_value(&ev));
+                                                            update_query(&|q| q.scale = scale)
+                                                        }
+                                                    >
+                                                        <option value="linear">"Value"</option>
+                                                        <option value="log">"Logarithmic"</option>
+                                                        <option value="percent">"% change"</option>
+                                                    </select>
+                                                </label>
+                                            </div>
+                                            <div class="chart-controls event-toggles">
+                                                <span>"Events"</span>
+                                                {EventCategory::ALL
+                                                    .into_iter()
+                                                    .map(|category| view! {
+                                                        <label>
+                                                            <span
+                                                                class="event-swatch"
+                                                                style:background-color=category.color()
+                                                            ></span>
+                                                            <input
+                                                                type="checkbox"
+                                                                prop:checked=move || {
+                                                                    hidden_categories.with(|hidden| !hidden.contains(&category))
+                                                                }
+                                                                on:change=move |ev| {
+                                                                    let shown = event_target_checked(&ev);
+                                                                    set_hidden_categories.update(|hidden| {
+                                                                        hidden.retain(|c| *c != category);
+                                                                        if !shown {
+                                                                            hidden.push(category);
+                                                                        }
+                                                                    });
+                                                                }
+                                                            />
+                                                            {category_label(category)}
+                                                        </label>
+                                                    })
+                                                    .collect_view()}
+                                            </div>
+                                            <canvas
+                                                node_ref=canvas_ref
+                                                on:mousemove=move |ev| set_pointer_x.set(Some(chart_x(&ev)))
+                                                on:mousedown=move |ev| set_drag_start.set(Some(chart_x(&ev)))
+                                                on:mouseup=move |ev| finish_drag(chart_x(&ev))
+                                                on:mouseleave=move |_| {
+                                                    set_pointer_x.set(None);
+                                                    set_drag_start.set(None);
+                                                }
+                                                on:wheel=pan
+                                                on:dblclick=move |_| reset_zoom()
+                                            ></canvas>
+                                            <MetricsTable metrics=Signal::derive(move || {
+                                                chart_data.with(|data| {
+                                                    data.as_ref().map(table_metrics).unwrap_or_default()
+                                                })
+                                            })/>
                                         </div>
-                                    }.into_view()
+                                    }.into_any()
                                 } else {
-                                    view! { <div></div> }.into_view()
+                                    view! { <div></div> }.into_any()
                                 }
                             }}
                         </div>
-                    }.into_view()
+                    }.into_any()
                 }
             }}
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use shared::{AssetSeries, NormalizedPricePoint, PricePoint};
+
+    fn at(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap()
+    }
+
+    fn series(asset_id: &str, last_day: u32) -> AssetSeries {
+        AssetSeries {
+            asset_id: asset_id.to_string(),
+            label: None,
+            points: (1..=last_day)
+                .map(|day| NormalizedPricePoint {
+                    timestamp: at(day),
+                    normalized_value: 100.0,
+                    return_pct: 0.0,
+                })
+                .collect(),
+        }
+    }
+
+    fn update(asset_id: &str, days: &[u32]) -> PriceUpdate {
+        PriceUpdate {
+            asset_id: asset_id.to_string(),
+            prices: days
+                .iter()
+                .map(|&day| PricePoint {
+                    asset_id: asset_id.to_string(),
+                    timestamp: at(day),
+                    price: 1.0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_extends_series() {
+        let response = ComparisonResponse {
+            series: vec![series("BTC", 5), series("portfolio:p1", 3)],
+            metrics: vec![],
+            events: vec![],
+        };
+        let followed = vec![
+            ("BTC".to_string(), vec!["BTC".to_string()]),
+            ("portfolio:p1".to_string(), vec!["SPY".to_string(), "BTC".to_string()]),
+        ];
+
+        assert!(!extends_series(&response, &followed, &update("SPY", &[2, 3])));
+        assert!(extends_series(&response, &followed, &update("SPY", &[4])));
+        // BTC is shown up to day 5, but the portfolio holding it only to day 3
+        assert!(extends_series(&response, &followed, &update("BTC", &[4])));
+        assert!(!extends_series(&response, &followed, &update("GLD", &[9])));
+        assert!(!extends_series(&response, &followed, &update("SPY", &[])));
+    }
+}
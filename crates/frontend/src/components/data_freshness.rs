@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use leptos::prelude::*;
+use leptos_router::hooks::use_location;
+use crate::api::Cached;
+
+/// Whether the data on screen is live, or what the browser cached before
+/// the backend became unreachable. Provided by `App`; pages report each
+/// load through it.
+#[derive(Debug, Clone, Copy)]
+pub struct DataFreshness {
+    stale_since: RwSignal<Option<DateTime<Utc>>>,
+}
+
+impl DataFreshness {
+    pub fn provide() {
+        provide_context(DataFreshness {
+            stale_since: RwSignal::new(None),
+        });
+    }
+
+    pub fn report<T>(&self, cached: &Cached<T>) {
+        self.stale_since.set(cached.stale_since);
+    }
+}
+
+pub fn use_data_freshness() -> DataFreshness {
+    expect_context()
+}
+
+/// Banner shown while pages display cached data because the backend
+/// can't be reached
+#[component]
+pub fn StaleDataIndicator() -> impl IntoView {
+    let freshness = use_data_freshness();
+    let location = use_location();
+
+    // Each page reports its own data
+    Effect::new(move |_| {
+        location.pathname.track();
+        freshness.stale_since.set(None);
+    });
+
+    move || {
+        freshness.stale_since.get().map(|since| view! {
+            <div class="stale-indicator" title="The server can't be reached; reload to try again">
+                {format!("Offline · showing data from {}", since.format("%Y-%m-%d %H:%M UTC"))}
+            </div>
+        })
+    }
+}
@@ -0,0 +1,324 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveTime, Utc};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use shared::{ChartQuery, CoverageResponse, DataCoverage, MarketEvent};
+use crate::api;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Preset {
+    YearToDate,
+    Years(u32),
+    Max,
+}
+
+const PRESETS: [(Preset, &str); 6] = [
+    (Preset::YearToDate, "YTD"),
+    (Preset::Years(1), "1Y"),
+    (Preset::Years(3), "3Y"),
+    (Preset::Years(5), "5Y"),
+    (Preset::Years(10), "10Y"),
+    (Preset::Max, "Max"),
+];
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    date.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// First midnight at or after `t`, so a range starting there has data
+fn ceil_to_day(t: DateTime<Utc>) -> DateTime<Utc> {
+    let day = midnight(t.date_naive());
+    if day == t {
+        day
+    } else {
+        day + Duration::days(1)
+    }
+}
+
+/// Start of the range a preset selects when the range ends at `end`.
+/// `Max` needs to know where the data starts.
+fn preset_start(
+    preset: Preset,
+    end: DateTime<Utc>,
+    data_start: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    match preset {
+        Preset::YearToDate => NaiveDate::from_ymd_opt(end.year(), 1, 1).map(midnight),
+        Preset::Years(years) => end
+            .date_naive()
+            .checked_sub_months(Months::new(12 * years))
+            .map(midnight),
+        Preset::Max => data_start.map(ceil_to_day),
+    }
+}
+
+/// Why the range of `query` cannot be shown as asked, if it can't.
+/// `common` is the asset whose data starts last.
+fn check_range(
+    query: &ChartQuery,
+    now: DateTime<Utc>,
+    common: Option<&DataCoverage>,
+) -> Result<(), String> {
+    let request = query.comparison_request(now);
+    if request.end_date > now {
+        return Err("End date is in the future".to_string());
+    }
+    if request.start_date >= request.end_date {
+        return Err("Start date must be before the end date".to_string());
+    }
+    if let Some(coverage) = common {
+        if request.start_date.date_naive() < coverage.first_date.date_naive() {
+            return Err(format!(
+                "{} data starts on {}",
+                coverage.asset_id,
+                coverage.first_date.format("%Y-%m-%d")
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn date_value(date: Option<DateTime<Utc>>) -> String {
+    date.map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Midnight UTC of an `<input type="date">` value; empty leaves the bound open
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().map(midnight)
+}
+
+/// Start/end dates and initial amount of a comparison, with presets and
+/// checks against the earliest data stored for the selected assets. Only
+/// valid changes are passed on to `on_change`.
+#[component]
+pub fn DateRangePicker(
+    #[prop(into)] query: Signal<ChartQuery>,
+    #[prop(into)] on_change: Callback<ChartQuery>,
+) -> impl IntoView {
+    let (coverage, set_coverage) = signal(None::<CoverageResponse>);
+    let (events, set_events) = signal(Vec::<MarketEvent>::new());
+    let (input_error, set_input_error) = signal(None::<String>);
+    let asset_ids = Memo::new(move |_| query.with(ChartQuery::asset_ids));
+
+    Effect::new(move |_| {
+        let asset_ids = asset_ids.get();
+        if asset_ids.is_empty() {
+            set_coverage.set(None);
+            return;
+        }
+        spawn_local(async move {
+            // Without coverage, ranges simply aren't checked against it
+            set_coverage.set(api::fetch_coverage(&asset_ids).await.ok());
+        });
+    });
+
+    Effect::new(move |_| {
+        spawn_local(async move {
+            if let Ok(response) = api::fetch_events().await {
+                set_events.set(response.events);
+            }
+        });
+    });
+
+    let common_start = Memo::new(move |_| {
+        coverage.with(|c| c.as_ref().and_then(|c| c.common_start().cloned()))
+    });
+    let range_end = move || query.with(|q| q.end).unwrap_or_else(Utc::now);
+
+    let apply = move |update: &dyn Fn(&mut ChartQuery)| {
+        let mut next = query.get_untracked();
+        update(&mut next);
+        match check_range(&next, Utc::now(), common_start.get_untracked().as_ref()) {
+            Ok(()) => {
+                set_input_error.set(None);
+                on_change.run(next);
+            }
+            Err(e) => set_input_error.set(Some(e)),
+        }
+    };
+
+    // Rejected input first, then problems with the range from the URL
+    let message = move || {
+        input_error.get().or_else(|| {
+            query.with(|q| check_range(q, Utc::now(), common_start.get().as_ref())).err()
+        })
+    };
+
+    // Events the chart would show: market-wide or tied to a selected asset
+    let relevant_events = move || {
+        let asset_ids = asset_ids.get();
+        events.with(|events| {
+            events
+                .iter()
+                .filter(|e| e.asset_id.as_ref().is_none_or(|id| asset_ids.contains(id)))
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    };
+
+    view! {
+        <div class="chart-controls range-presets">
+            {PRESETS
+                .into_iter()
+                .map(|(preset, label)| {
+                    let start = move || {
+                        preset_start(preset, range_end(), common_start.get().map(|c| c.first_date))
+                    };
+                    let active = move || {
+                        let current = query.with(|q| q.start);
+                        match current {
+                            Some(current) => start() == Some(current),
+                            None => preset == Preset::Years(1),
+                        }
+                    };
+                    let unavailable = move || match (start(), common_start.get()) {
+                        (None, _) => true,
+                        (Some(start), Some(common)) => {
+                            start.date_naive() < common.first_date.date_naive()
+                        }
+                        (Some(_), None) => false,
+                    };
+                    view! {
+                        <button
+                            class="preset"
+                            class:active=active
+                            disabled=unavailable
+                            on:click=move |_| {
+                                if let Some(start) = start() {
+                                    apply(&|q| q.start = Some(start))
+                                }
+                            }
+                        >
+                            {label}
+                        </button>
+                    }
+                })
+                .collect_view()}
+            <select
+                prop:value=""
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    let event = events.with_untracked(|events| {
+                        events.iter().find(|e| e.id.to_string() == value).cloned()
+                    });
+                    if let Some(event) = event {
+                        apply(&|q| q.start = Some(midnight(event.start_date.date_naive())))
+                    }
+                }
+            >
+                <option value="">"Since event…"</option>
+                {move || {
+                    relevant_events()
+                        .into_iter()
+                        .map(|event| view! {
+                            <option value=event.id.to_string()>
+                                {format!("{} ({})", event.title, event.start_date.format("%Y-%m-%d"))}
+                            </option>
+                        })
+                        .collect_view()
+                }}
+            </select>
+        </div>
+
+        <div class="chart-controls comparison-options">
+            <label>
+                "From "
+                <input
+                    type="date"
+                    prop:value=move || query.with(|q| date_value(q.start))
+                    on:change=move |ev| {
+                        let start = parse_date(&event_target_value(&ev));
+                        apply(&|q| q.start = start)
+                    }
+                />
+            </label>
+            <label>
+                "To "
+                <input
+                    type="date"
+                    prop:value=move || query.with(|q| date_value(q.end))
+                    on:change=move |ev| {
+                        let end = parse_date(&event_target_value(&ev));
+                        apply(&|q| q.end = end)
+                    }
+                />
+            </label>
+            <label>
+                "Initial amount "
+                <input
+                    type="number"
+                    min="1"
+                    step="any"
+                    prop:value=move || query.with(|q| q.amount.to_string())
+                    on:change=move |ev| {
+                        match event_target_value(&ev).parse::<f64>() {
+                            Ok(amount) if amount > 0.0 => apply(&|q| q.amount = amount),
+                            _ => set_input_error.set(Some("Initial amount must be a positive number".to_string())),
+                        }
+                    }
+                />
+            </label>
+        </div>
+
+        {move || message().map(|message| view! { <p class="range-error">{message}</p> })}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_ceil_to_day() {
+        assert_eq!(ceil_to_day(at(2024, 3, 1, 0)), at(2024, 3, 1, 0));
+        assert_eq!(ceil_to_day(at(2024, 2, 29, 15)), at(2024, 3, 1, 0));
+    }
+
+    #[test]
+    fn test_preset_start() {
+        let end = at(2024, 2, 29, 15);
+        assert_eq!(preset_start(Preset::YearToDate, end, None), Some(at(2024, 1, 1, 0)));
+        assert_eq!(preset_start(Preset::Years(1), end, None), Some(at(2023, 2, 28, 0)));
+        assert_eq!(preset_start(Preset::Years(10), end, None), Some(at(2014, 2, 28, 0)));
+        assert_eq!(preset_start(Preset::Max, end, None), None);
+        assert_eq!(
+            preset_start(Preset::Max, end, Some(at(2010, 7, 17, 12))),
+            Some(at(2010, 7, 18, 0))
+        );
+    }
+
+    #[test]
+    fn test_check_range() {
+        let now = at(2024, 6, 1, 12);
+        let query = |start, end| ChartQuery {
+            assets: "SPY".to_string(),
+            start,
+            end,
+            ..Default::default()
+        };
+        let coverage = DataCoverage {
+            asset_id: "BTC".to_string(),
+            first_date: at(2014, 9, 17, 0),
+            last_date: now,
+        };
+
+        assert_eq!(check_range(&query(None, None), now, Some(&coverage)), Ok(()));
+        assert_eq!(
+            check_range(&query(None, Some(at(2024, 6, 2, 0))), now, None),
+            Err("End date is in the future".to_string())
+        );
+        assert_eq!(
+            check_range(&query(Some(at(2024, 5, 1, 0)), Some(at(2024, 5, 1, 0))), now, None),
+            Err("Start date must be before the end date".to_string())
+        );
+        assert_eq!(
+            check_range(&query(Some(at(2014, 1, 1, 0)), None), now, Some(&coverage)),
+            Err("BTC data starts on 2014-09-17".to_string())
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use std::cmp::Ordering;
+
+use leptos::prelude::*;
+use shared::PerformanceMetrics;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Asset,
+    TotalReturn,
+    Cagr,
+    Volatility,
+    MaxDrawdown,
+    Sharpe,
+    Sortino,
+    Calmar,
+}
+
+const COLUMNS: [Column; 8] = [
+    Column::Asset,
+    Column::TotalReturn,
+    Column::Cagr,
+    Column::Volatility,
+    Column::MaxDrawdown,
+    Column::Sharpe,
+    Column::Sortino,
+    Column::Calmar,
+];
+
+impl Column {
+    fn label(self) -> &'static str {
+        match self {
+            Column::Asset => "Asset",
+            Column::TotalReturn => "Total return",
+            Column::Cagr => "CAGR",
+            Column::Volatility => "Volatility",
+            Column::MaxDrawdown => "Max drawdown",
+            Column::Sharpe => "Sharpe",
+            Column::Sortino => "Sortino",
+            Column::Calmar => "Calmar",
+        }
+    }
+
+    fn tooltip(self) -> &'static str {
+        match self {
+            Column::Asset => "Asset compared",
+            Column::TotalReturn => "Change in value from the first to the last price in the range",
+            Column::Cagr => "Compound annual growth rate: the constant yearly return giving the same total return",
+            Column::Volatility => "Annualized standard deviation of returns; higher means a bumpier ride",
+            Column::MaxDrawdown => "Largest fall from a previous peak to a later low",
+            Column::Sharpe => "Annualized return per unit of volatility, assuming a zero risk-free rate",
+            Column::Sortino => "Like Sharpe, but only counting volatility from losing days",
+            Column::Calmar => "CAGR divided by the size of the maximum drawdown",
+        }
+    }
+
+    fn value(self, metrics: &PerformanceMetrics) -> Option<f64> {
+        match self {
+            Column::Asset => None,
+            Column::TotalReturn => Some(metrics.total_return_pct),
+            Column::Cagr => Some(metrics.annualized_return_pct),
+            Column::Volatility => Some(metrics.volatility),
+            Column::MaxDrawdown => Some(metrics.max_drawdown_pct),
+            Column::Sharpe => metrics.sharpe_ratio,
+            Column::Sortino => metrics.sortino_ratio,
+            Column::Calmar => metrics.calmar_ratio,
+        }
+    }
+
+    /// Only volatility is better when lower; drawdowns are negative, so
+    /// the one closest to zero is the largest
+    fn higher_is_better(self) -> bool {
+        self != Column::Volatility
+    }
+
+    fn format(self, value: f64) -> String {
+        match self {
+            Column::Asset => String::new(),
+            Column::TotalReturn | Column::Cagr | Column::MaxDrawdown => format!("{:+.2}%", value),
+            Column::Volatility => format!("{:.2}%", value),
+            Column::Sharpe | Column::Sortino | Column::Calmar => format!("{:.2}", value),
+        }
+    }
+}
+
+fn compare(column: Column, a: &PerformanceMetrics, b: &PerformanceMetrics) -> Ordering {
+    match column {
+        Column::Asset => a.asset_id.cmp(&b.asset_id),
+        // Missing values sort below any number
+        _ => match (column.value(a), column.value(b)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        },
+    }
+}
+
+/// Best and worst value of a column, if at least two assets have one
+fn extremes(column: Column, rows: &[PerformanceMetrics]) -> Option<(f64, f64)> {
+    let values: Vec<f64> = rows.iter().filter_map(|m| column.value(m)).collect();
+    if values.len() < 2 {
+        return None;
+    }
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    if max == min {
+        return None;
+    }
+    Some(if column.higher_is_better() { (max, min) } else { (min, max) })
+}
+
+fn to_csv(rows: &[PerformanceMetrics]) -> String {
+    let header = COLUMNS.map(Column::label).join(",");
+    let lines = rows.iter().map(|metrics| {
+        COLUMNS
+            .iter()
+            .map(|column| match column {
+                Column::Asset => metrics.asset_id.clone(),
+                _ => column.value(metrics).map(|v| format!("{:.4}", v)).unwrap_or_default(),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+    std::iter::once(header).chain(lines).collect::<Vec<_>>().join("\n")
+}
+
+/// Per-asset performance metrics, sortable by any column, with the best and
+/// worst value of each column highlighted
+#[component]
+pub fn MetricsTable(#[prop(into)] metrics: Signal<Vec<PerformanceMetrics>>) -> impl IntoView {
+    let (sort, set_sort) = signal((Column::TotalReturn, true));
+    let (copied, set_copied) = signal(false);
+
+    let rows = Memo::new(move |_| {
+        let (column, descending) = sort.get();
+        let mut rows = metrics.get();
+        rows.sort_by(|a, b| {
+            let order = compare(column, a, b);
+            if descending { order.reverse() } else { order }
+        });
+        rows
+    });
+
+    Effect::new(move |_| {
+        rows.track();
+        set_copied.set(false);
+    });
+
+    let sort_by = move |column: Column| {
+        set_sort.update(|(current, descending)| {
+            if *current == column {
+                *descending = !*descending;
+            } else {
+                *current = column;
+                *descending = column != Column::Asset;
+            }
+        });
+    };
+
+    let copy_csv = move |_| {
+        let csv = rows.with_untracked(|rows| to_csv(rows));
+        if let Some(window) = web_sys::window() {
+            // Fire and forget; the promise only rejects without permission
+            let _ = window.navigator().clipboard().write_text(&csv);
+            set_copied.set(true);
+        }
+    };
+
+    view! {
+        <div class="metrics">
+            <div class="chart-controls">
+                <span class="chart-hint">"Click a column to sort"</span>
+                <button class="preset" on:click=copy_csv>
+                    {move || if copied.get() { "Copied" } else { "Copy CSV" }}
+                </button>
+            </div>
+            <table class="metrics-table">
+                <thead>
+                    <tr>
+                        {COLUMNS
+                            .into_iter()
+                            .map(|column| view! {
+                                <th title=column.tooltip() on:click=move |_| sort_by(column)>
+                                    {column.label()}
+                                    {move || {
+                                        let (current, descending) = sort.get();
+                                        match (current == column, descending) {
+                                            (false, _) => "",
+                                            (true, true) => " ▼",
+                                            (true, false) => " ▲",
+                                        }
+                                    }}
+                                </th>
+                            })
+                            .collect_view()}
+                    </tr>
+                </thead>
+                <tbody>
+                    {move || {
+                        rows.with(|rows| {
+                            let extremes = COLUMNS.map(|column| extremes(column, rows));
+                            rows.iter()
+                                .map(|metrics| {
+                                    let cells = COLUMNS
+                                        .iter()
+                                        .zip(&extremes)
+                                        .map(|(column, extremes)| match column.value(metrics) {
+                                            _ if *column == Column::Asset => view! {
+                                                <td class="asset">{metrics.asset_id.clone()}</td>
+                                            }.into_any(),
+                                            Some(value) => {
+                                                let class = match extremes {
+                                                    Some((best, _)) if value == *best => "best",
+                                                    Some((_, worst)) if value == *worst => "worst",
+                                                    _ => "",
+                                                };
+                                                view! { <td class=class>{column.format(value)}</td> }.into_any()
+                                            }
+                                            None => view! { <td>"–"</td> }.into_any(),
+                                        })
+                                        .collect_view();
+                                    view! { <tr>{cells}</tr> }
+                                })
+                                .collect_view()
+                        })
+                    }}
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -1,5 +1,11 @@
 mod asset_selector;
 mod comparison_chart;
+mod data_freshness;
+mod date_range_picker;
+mod metrics_table;
 
 pub use asset_selector::AssetSelector;
 pub use comparison_chart::ComparisonChart;
+pub use data_freshness::{use_data_freshness, DataFreshness, StaleDataIndicator};
+pub use date_range_picker::DateRangePicker;
+pub use metrics_table::MetricsTable;
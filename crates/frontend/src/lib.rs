@@ -1,14 +1,51 @@
 use leptos::mount::mount_to_body;
-use leptos::prelude::ElementChild;
-use leptos::*;
+use leptos::prelude::*;
+use leptos_router::components::{Route, Router, Routes, A};
+use leptos_router::path;
+
+pub mod api;
+pub mod components;
+pub mod pages;
+
+use components::{DataFreshness, StaleDataIndicator};
+use pages::{
+    AssetDetail, ComparePage, Home, NotFound, PortfolioBuilder, PortfolioDetail, PortfolioImport,
+    PortfolioList,
+};
 
 #[component]
 pub fn App() -> impl IntoView {
+    DataFreshness::provide();
+
     view! {
-        <main>
-            <h1>"Portfolio Tracker"</h1>
-            <p>"Compare stocks, Bitcoin, and gold performance"</p>
-        </main>
+        <Router>
+            <header>
+                <div class="container">
+                    <h1>"Portfolio Tracker"</h1>
+                    <p class="subtitle">
+                        "Compare stocks, Bitcoin, and gold performance over time"
+                    </p>
+                    <nav class="nav">
+                        <A href="/" exact=true>"Home"</A>
+                        <A href="/compare">"Compare"</A>
+                        <A href="/portfolios">"Portfolios"</A>
+                    </nav>
+                    <StaleDataIndicator/>
+                </div>
+            </header>
+
+            <main class="container">
+                <Routes fallback=NotFound>
+                    <Route path=path!("/") view=Home/>
+                    <Route path=path!("/compare") view=ComparePage/>
+                    <Route path=path!("/assets/:id") view=AssetDetail/>
+                    <Route path=path!("/portfolios") view=PortfolioList/>
+                    <Route path=path!("/portfolios/new") view=PortfolioBuilder/>
+                    <Route path=path!("/portfolios/import") view=PortfolioImport/>
+                    <Route path=path!("/portfolios/:id") view=PortfolioDetail/>
+                </Routes>
+            </main>
+        </Router>
     }
 }
 
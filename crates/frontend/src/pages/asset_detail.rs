@@ -0,0 +1,247 @@
+use charting::{LineChart, Series, YAxisMode};
+use chrono::{DateTime, Utc};
+use leptos::html::Canvas;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::components::A;
+use leptos_router::hooks::use_params_map;
+use shared::{AssetDetailResponse, ChartQuery, PricePoint};
+use crate::api;
+use crate::components::use_data_freshness;
+
+const CHART_WIDTH: f64 = 1000.0;
+const CHART_HEIGHT: f64 = 400.0;
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+fn format_ratio(value: Option<f64>) -> String {
+    value.map_or_else(|| "–".to_string(), |v| format!("{:.2}", v))
+}
+
+/// Add streamed prices to `history`, replacing any at the same time
+fn merge_prices(history: &mut Vec<PricePoint>, prices: Vec<PricePoint>) {
+    for price in prices {
+        match history.binary_search_by_key(&price.timestamp, |p| p.timestamp) {
+            Ok(i) => history[i] = price,
+            Err(i) => history.insert(i, price),
+        }
+    }
+}
+
+fn history_chart(name: &str, prices: &[PricePoint], y_axis: YAxisMode) -> LineChart {
+    LineChart::new(CHART_WIDTH, CHART_HEIGHT)
+        .with_y_axis(y_axis)
+        .with_series(Series::new(
+            name.to_string(),
+            prices.iter().map(|p| (p.timestamp, p.price)).collect(),
+        ))
+}
+
+#[component]
+pub fn AssetDetail() -> impl IntoView {
+    let params = use_params_map();
+    let asset_id = Memo::new(move |_| params.with(|p| p.get("id").unwrap_or_default()));
+    let (detail, set_detail) = signal(None::<AssetDetailResponse>);
+    let (history, set_history) = signal(Vec::<PricePoint>::new());
+    let (y_axis, set_y_axis) = signal(YAxisMode::Linear);
+    let (loading, set_loading) = signal(true);
+    let (error, set_error) = signal(None::<String>);
+    let canvas_ref = NodeRef::<Canvas>::new();
+    let freshness = use_data_freshness();
+
+    let load_history = move |id: String| {
+        spawn_local(async move {
+            // The chart downsamples to its width, so the full history is
+            // kept, which also lets the cache extend it with new prices only
+            match api::fetch_price_history(&id).await {
+                Ok(response) => set_history.set(response.value),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    Effect::new(move |_| {
+        let id = asset_id.get();
+        spawn_local(async move {
+            set_loading.set(true);
+            match api::fetch_asset(&id).await {
+                Ok(response) => {
+                    freshness.report(&response);
+                    set_detail.set(Some(response.value));
+                    set_error.set(None);
+                }
+                Err(e) => {
+                    set_detail.set(None);
+                    set_error.set(Some(if e.is_not_found() {
+                        format!("Unknown asset {}", id)
+                    } else {
+                        e.to_string()
+                    }));
+                }
+            }
+            set_loading.set(false);
+            load_history(id);
+        });
+    });
+
+    // New prices extend the chart as they are stored; after missing some,
+    // the history is reloaded, which only fetches what the cache lacks
+    Effect::new(move |_| {
+        let id = asset_id.get();
+        api::subscribe_prices(
+            std::slice::from_ref(&id),
+            move |update| set_history.update(|history| merge_prices(history, update.prices)),
+            move || load_history(asset_id.get_untracked()),
+        )
+    });
+
+    Effect::new(move |_| {
+        let Some(canvas) = canvas_ref.get() else {
+            return;
+        };
+        let name = detail.with(|d| d.as_ref().map(|d| d.asset.name.clone()).unwrap_or_default());
+        let result = history.with(|prices| {
+            (!prices.is_empty()).then(|| history_chart(&name, prices, y_axis.get()).render(&canvas))
+        });
+        if let Some(Err(e)) = result {
+            set_error.set(Some(format!("Failed to render chart: {:?}", e)));
+        }
+    });
+
+    view! {
+        <div>
+            {move || {
+                if loading.get() {
+                    view! {
+                        <div class="loading">
+                            <p>"Loading asset..."</p>
+                        </div>
+                    }.into_any()
+                } else if let Some(err) = error.get() {
+                    view! {
+                        <div class="error">
+                            <p><strong>"Error: "</strong> {err}</p>
+                        </div>
+                    }.into_any()
+                } else if let Some(detail) = detail.get() {
+                    let asset = detail.asset;
+                    let query = ChartQuery {
+                        assets: asset.id.clone(),
+                        ..Default::default()
+                    };
+                    view! {
+                        <div>
+                            <div class="card">
+                                <h2>{asset.name}</h2>
+                                <p class="subtitle">{format!("{} · {}", asset.symbol, asset.asset_type)}</p>
+                                {move || history.with(|h| h.last().cloned()).map(|latest| view! {
+                                    <p class="latest-price">
+                                        {format!("{:.2}", latest.price)}
+                                        <span class="subtitle">
+                                            {format!(" as of {}", format_date(latest.timestamp))}
+                                        </span>
+                                    </p>
+                                })}
+                                <p style="margin-top: 1rem;">
+                                    <A href=format!("/compare?{}", query.to_query_string())>
+                                        "Compare with other assets"
+                                    </A>
+                                </p>
+                            </div>
+
+                            <div class="card">
+                                <div class="chart-controls">
+                                    <span class="chart-hint">"Price history"</span>
+                                    <label>
+                                        "Scale "
+                                        <select
+                                            on:change=move |ev| {
+                                                set_y_axis.set(match event_target_value(&ev).as_str() {
+                                                    "log" => YAxisMode::Log,
+                                                    _ => YAxisMode::Linear,
+                                                })
+                                            }
+                                        >
+                                            <option value="linear">"Linear"</option>
+                                            <option value="log">"Logarithmic"</option>
+                                        </select>
+                                    </label>
+                                </div>
+                                <canvas node_ref=canvas_ref></canvas>
+                            </div>
+
+                            <div class="card">
+                                <h2>"Performance"</h2>
+                                <table class="metrics-table">
+                                    <thead>
+                                        <tr>
+                                            <th>"Window"</th>
+                                            <th>"Total return"</th>
+                                            <th>"CAGR"</th>
+                                            <th>"Volatility"</th>
+                                            <th>"Max drawdown"</th>
+                                            <th>"Sharpe"</th>
+                                            <th>"Sortino"</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        {detail
+                                            .windows
+                                            .into_iter()
+                                            .map(|window| {
+                                                let m = window.metrics;
+                                                view! {
+                                                    <tr>
+                                                        <td class="asset">{window.window}</td>
+                                                        <td>{format!("{:+.2}%", m.total_return_pct)}</td>
+                                                        <td>{format!("{:+.2}%", m.annualized_return_pct)}</td>
+                                                        <td>{format!("{:.2}%", m.volatility)}</td>
+                                                        <td>{format!("{:+.2}%", m.max_drawdown_pct)}</td>
+                                                        <td>{format_ratio(m.sharpe_ratio)}</td>
+                                                        <td>{format_ratio(m.sortino_ratio)}</td>
+                                                    </tr>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </tbody>
+                                </table>
+                            </div>
+
+                            <div class="card">
+                                <h2>"Data"</h2>
+                                <dl class="data-coverage">
+                                    <dt>"Earliest price"</dt>
+                                    <dd>{detail.coverage.as_ref().map_or("–".to_string(), |c| format_date(c.first_date))}</dd>
+                                    <dt>"Latest price"</dt>
+                                    <dd>{detail.coverage.as_ref().map_or("–".to_string(), |c| format_date(c.last_date))}</dd>
+                                    <dt>"Prices stored"</dt>
+                                    <dd>{detail.point_count}</dd>
+                                    <dt>"Gaps"</dt>
+                                    <dd>{detail.gap_count}</dd>
+                                    <dt>"Providers"</dt>
+                                    <dd>
+                                        {if detail.providers.is_empty() {
+                                            "None recorded".to_string()
+                                        } else {
+                                            detail.providers.join(", ")
+                                        }}
+                                    </dd>
+                                    <dt>"Last refresh"</dt>
+                                    <dd>
+                                        {detail.last_refresh.map_or("Never".to_string(), |t| {
+                                            t.format("%Y-%m-%d %H:%M UTC").to_string()
+                                        })}
+                                    </dd>
+                                </dl>
+                            </div>
+                        </div>
+                    }.into_any()
+                } else {
+                    view! { <div></div> }.into_any()
+                }
+            }}
+        </div>
+    }
+}
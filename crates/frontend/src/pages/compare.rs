@@ -0,0 +1,112 @@
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::hooks::{use_location, use_navigate};
+use leptos_router::NavigateOptions;
+use shared::{Asset, ChartQuery, Portfolio};
+use crate::api;
+use crate::components::{AssetSelector, ComparisonChart, DateRangePicker};
+
+/// The comparison page. All of its state lives in the query string, so the
+/// URL can be shared to show someone exactly the same chart.
+#[component]
+pub fn ComparePage() -> impl IntoView {
+    let (assets, set_assets) = signal(Vec::<Asset>::new());
+    let (portfolios, set_portfolios) = signal(Vec::<Portfolio>::new());
+    let location = use_location();
+    let navigate = use_navigate();
+
+    let parsed = Memo::new(move |_| {
+        location.search.with(|search| ChartQuery::from_query_string(search))
+    });
+    let query = Signal::derive(move || parsed.get().unwrap_or_default());
+
+    // Control changes replace the current history entry rather than adding
+    // one per click
+    let set_query = Callback::new(move |query: ChartQuery| {
+        let search = query.to_query_string();
+        let path = if search.is_empty() {
+            "/compare".to_string()
+        } else {
+            format!("/compare?{}", search)
+        };
+        navigate(
+            &path,
+            NavigateOptions {
+                replace: true,
+                ..Default::default()
+            },
+        );
+    });
+    let update_query = move |update: &dyn Fn(&mut ChartQuery)| {
+        let mut next = query.get_untracked();
+        update(&mut next);
+        set_query.run(next);
+    };
+
+    Effect::new(move |_| {
+        spawn_local(async move {
+            // The selector simply stays empty if this fails; the chart
+            // reports its own errors
+            if let Ok(response) = api::fetch_assets().await {
+                set_assets.set(response.value.assets);
+            }
+            if let Ok(response) = api::fetch_portfolios().await {
+                set_portfolios.set(response.portfolios);
+            }
+        });
+    });
+
+    view! {
+        <div>
+            {move || {
+                parsed.get().err().map(|err| view! {
+                    <div class="error">
+                        <p><strong>"Invalid link: "</strong> {err}</p>
+                    </div>
+                })
+            }}
+
+            <AssetSelector
+                assets=assets
+                portfolios=portfolios
+                selected_assets=Signal::derive(move || query.with(ChartQuery::asset_ids))
+                on_change=Callback::new(move |asset_ids: Vec<String>| {
+                    update_query(&|q| q.set_asset_ids(&asset_ids))
+                })
+            />
+
+            <div class="card">
+                <DateRangePicker query=query on_change=set_query/>
+                <div class="chart-controls comparison-options">
+                    <label>
+                        "Priced in "
+                        <select
+                            prop:value=move || {
+                                // Options arrive with the asset list
+                                assets.track();
+                                query.with(|q| q.unit.clone().unwrap_or_default())
+                            }
+                            on:change=move |ev| {
+                                let unit = Some(event_target_value(&ev)).filter(|unit| !unit.is_empty());
+                                update_query(&|q| q.unit = unit.clone())
+                            }
+                        >
+                            <option value="">"USD"</option>
+                            {move || {
+                                assets
+                                    .get()
+                                    .into_iter()
+                                    .map(|asset| view! {
+                                        <option value=asset.id.clone()>{asset.name}</option>
+                                    })
+                                    .collect_view()
+                            }}
+                        </select>
+                    </label>
+                </div>
+            </div>
+
+            <ComparisonChart query=query on_change=set_query/>
+        </div>
+    }
+}
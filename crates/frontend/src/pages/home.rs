@@ -1,26 +1,29 @@
-use leptos::*;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::components::A;
 use shared::Asset;
 use crate::api;
-use crate::components::{AssetSelector, ComparisonChart};
+use crate::components::use_data_freshness;
 
 #[component]
 pub fn Home() -> impl IntoView {
-    let (assets, set_assets) = create_signal(Vec::<Asset>::new());
-    let (selected_assets, set_selected_assets) = create_signal(Vec::<String>::new());
-    let (loading, set_loading) = create_signal(true);
-    let (error, set_error) = create_signal(None::<String>);
+    let (assets, set_assets) = signal(Vec::<Asset>::new());
+    let (loading, set_loading) = signal(true);
+    let (error, set_error) = signal(None::<String>);
+    let freshness = use_data_freshness();
 
     // Fetch available assets on mount
-    create_effect(move |_| {
+    Effect::new(move |_| {
         spawn_local(async move {
             set_loading.set(true);
             match api::fetch_assets().await {
                 Ok(response) => {
-                    set_assets.set(response.assets);
+                    freshness.report(&response);
+                    set_assets.set(response.value.assets);
                     set_error.set(None);
                 }
                 Err(e) => {
-                    set_error.set(Some(e));
+                    set_error.set(Some(e.to_string()));
                 }
             }
             set_loading.set(false);
@@ -29,46 +32,56 @@ pub fn Home() -> impl IntoView {
 
     view! {
         <div>
-            <header>
-                <div class="container">
-                    <h1>"Portfolio Tracker"</h1>
-                    <p class="subtitle">
-                        "Compare stocks, Bitcoin, and gold performance over time"
-                    </p>
-                </div>
-            </header>
+            <div class="card">
+                <h2>"Compare performance"</h2>
+                <p class="subtitle">
+                    "Chart any mix of assets over a date range, priced in dollars or in another asset. "
+                    "The comparison page keeps its settings in the URL, so a link shows others exactly what you see."
+                </p>
+                <p style="margin-top: 1rem;">
+                    <A href="/compare?assets=SPY,BTC,XAU">"Stocks vs Bitcoin vs gold"</A>
+                    " · "
+                    <A href="/compare?assets=QQQ,SPY&unit=XAU&scale=log">"Tech and the S&P 500 priced in gold"</A>
+                    " · "
+                    <A href="/portfolios">"Saved portfolios"</A>
+                </p>
+            </div>
 
-            <main class="container">
-                {move || {
-                    if loading.get() {
-                        view! {
-                            <div class="loading">
-                                <p>"Loading assets..."</p>
+            {move || {
+                if loading.get() {
+                    view! {
+                        <div class="loading">
+                            <p>"Loading assets..."</p>
+                        </div>
+                    }.into_any()
+                } else if let Some(err) = error.get() {
+                    view! {
+                        <div class="error">
+                            <p><strong>"Error: "</strong> {err}</p>
+                        </div>
+                    }.into_any()
+                } else {
+                    view! {
+                        <div class="card">
+                            <h2>"Assets"</h2>
+                            <div class="asset-list">
+                                {assets
+                                    .get()
+                                    .into_iter()
+                                    .map(|asset| view! {
+                                        <A href=format!("/assets/{}", asset.id)>
+                                            <div class="asset-item">
+                                                <div class="asset-name">{asset.name}</div>
+                                                <div class="asset-symbol">{asset.symbol}</div>
+                                            </div>
+                                        </A>
+                                    })
+                                    .collect_view()}
                             </div>
-                        }.into_view()
-                    } else if let Some(err) = error.get() {
-                        view! {
-                            <div class="error">
-                                <p><strong>"Error: "</strong> {err}</p>
-                            </div>
-                        }.into_view()
-                    } else {
-                        view! {
-                            <div>
-                                <AssetSelector
-                                    assets=assets
-                                    selected_assets=selected_assets
-                                    set_selected_assets=set_selected_assets
-                                />
-
-                                <ComparisonChart
-                                    selected_assets=selected_assets
-                                />
-                            </div>
-                        }.into_view()
-                    }
-                }}
-            </main>
+                        </div>
+                    }.into_any()
+                }
+            }}
         </div>
     }
 }
@@ -76,10 +89,10 @@ pub fn Home() -> impl IntoView {
 #[component]
 pub fn NotFound() -> impl IntoView {
     view! {
-        <div class="container">
+        <div class="card">
             <h1>"404 - Page Not Found"</h1>
             <p>"The page you're looking for doesn't exist."</p>
-            <a href="/">"Go back home"</a>
+            <A href="/">"Go back home"</A>
         </div>
     }
 }
@@ -1,3 +1,13 @@
+mod asset_detail;
+mod compare;
 mod home;
+mod portfolio_builder;
+mod portfolio_import;
+mod portfolios;
 
+pub use asset_detail::AssetDetail;
+pub use compare::ComparePage;
 pub use home::{Home, NotFound};
+pub use portfolio_builder::PortfolioBuilder;
+pub use portfolio_import::PortfolioImport;
+pub use portfolios::{PortfolioDetail, PortfolioList};
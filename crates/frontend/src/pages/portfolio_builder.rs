@@ -0,0 +1,452 @@
+use charting::{LineChart, Series};
+use chrono::{Duration, Utc};
+use leptos::html::Canvas;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::hooks::use_navigate;
+use leptos_router::NavigateOptions;
+use shared::{
+    Asset, BacktestRequest, BacktestResponse, CreatePortfolioRequest, PortfolioAsset,
+    RebalanceRule,
+};
+use crate::api;
+
+const CHART_WIDTH: f64 = 1000.0;
+const CHART_HEIGHT: f64 = 350.0;
+const PREVIEW_AMOUNT: f64 = 10000.0;
+const PREVIEW_YEARS: [u32; 4] = [1, 3, 5, 10];
+
+pub fn rebalance_label(rule: RebalanceRule) -> &'static str {
+    match rule {
+        RebalanceRule::Never => "Never (buy and hold)",
+        RebalanceRule::Monthly => "Monthly",
+        RebalanceRule::Quarterly => "Quarterly",
+        RebalanceRule::Yearly => "Yearly",
+    }
+}
+
+/// Set one weight, in percent, and scale the others so all still add up
+/// to 100%
+fn set_weight(weights: &mut [f64], index: usize, value: f64) {
+    if weights.len() == 1 {
+        weights[0] = 100.0;
+        return;
+    }
+    let value = value.clamp(0.0, 100.0);
+    let others: f64 = weights
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != index)
+        .map(|(_, w)| w)
+        .sum();
+    let remaining = 100.0 - value;
+    let even_share = remaining / (weights.len() - 1) as f64;
+    for (i, weight) in weights.iter_mut().enumerate() {
+        *weight = if i == index {
+            value
+        } else if others > 0.0 {
+            *weight * remaining / others
+        } else {
+            even_share
+        };
+    }
+}
+
+/// Scale weights to add up to 100%, splitting evenly if they are all zero
+fn normalize(weights: &mut [f64]) {
+    let total: f64 = weights.iter().sum();
+    let count = weights.len() as f64;
+    for weight in weights.iter_mut() {
+        *weight = if total > 0.0 {
+            *weight * 100.0 / total
+        } else {
+            100.0 / count
+        };
+    }
+}
+
+/// Apply `update` to the weights of `holdings`
+fn update_weights(holdings: &mut [(String, f64)], update: impl FnOnce(&mut [f64])) {
+    let mut weights: Vec<f64> = holdings.iter().map(|(_, w)| *w).collect();
+    update(&mut weights);
+    for ((_, weight), new) in holdings.iter_mut().zip(weights) {
+        *weight = new;
+    }
+}
+
+/// Holdings as fractions summing to exactly 1, as the API expects; all-zero
+/// weights are split evenly
+fn portfolio_assets(holdings: &[(String, f64)]) -> Vec<PortfolioAsset> {
+    let mut weights: Vec<f64> = holdings.iter().map(|(_, w)| *w).collect();
+    normalize(&mut weights);
+    holdings
+        .iter()
+        .zip(weights)
+        .map(|((asset_id, _), weight)| PortfolioAsset {
+            asset_id: asset_id.clone(),
+            weight: weight / 100.0,
+        })
+        .collect()
+}
+
+/// Editor for a new portfolio: pick assets, weight them, choose how often
+/// to rebalance and preview the backtest before saving
+#[component]
+pub fn PortfolioBuilder() -> impl IntoView {
+    let navigate = use_navigate();
+    let (assets, set_assets) = signal(Vec::<Asset>::new());
+    // Asset id and weight in percent; weights always sum to 100
+    let (holdings, set_holdings) = signal(Vec::<(String, f64)>::new());
+    let (name, set_name) = signal(String::new());
+    let (rebalance, set_rebalance) = signal(RebalanceRule::Quarterly);
+    let (preview_years, set_preview_years) = signal(5u32);
+    let (preview, set_preview) = signal(None::<BacktestResponse>);
+    let (error, set_error) = signal(None::<String>);
+    let (saving, set_saving) = signal(false);
+    let canvas_ref = NodeRef::<Canvas>::new();
+    // Only the latest preview request may update the chart
+    let preview_generation = StoredValue::new(0u64);
+
+    Effect::new(move |_| {
+        spawn_local(async move {
+            match api::fetch_assets().await {
+                Ok(response) => set_assets.set(response.value.assets),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    });
+
+    Effect::new(move |_| {
+        let holdings = holdings.get();
+        let rebalance = rebalance.get();
+        let years = preview_years.get();
+        preview_generation.update_value(|g| *g += 1);
+        let generation = preview_generation.get_value();
+        if holdings.is_empty() {
+            set_preview.set(None);
+            return;
+        }
+
+        let end_date = Utc::now();
+        let request = BacktestRequest {
+            assets: portfolio_assets(&holdings),
+            rebalance,
+            start_date: end_date - Duration::days(365 * years as i64),
+            end_date,
+            initial_amount: PREVIEW_AMOUNT,
+            max_points: Some(CHART_WIDTH as usize),
+        };
+        spawn_local(async move {
+            let result = api::backtest_portfolio(&request).await;
+            if preview_generation.get_value() != generation {
+                return;
+            }
+            match result {
+                Ok(response) => {
+                    set_preview.set(Some(response));
+                    set_error.set(None);
+                }
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+        });
+    });
+
+    Effect::new(move |_| {
+        let Some(canvas) = canvas_ref.get() else {
+            return;
+        };
+        let label = name.with(|n| match n.trim() {
+            "" => "Portfolio".to_string(),
+            name => name.to_string(),
+        });
+        let result = preview.with(|preview| {
+            preview.as_ref().map(|preview| {
+                LineChart::new(CHART_WIDTH, CHART_HEIGHT)
+                    .with_series(Series::new(
+                        label,
+                        preview
+                            .series
+                            .points
+                            .iter()
+                            .map(|p| (p.timestamp, p.normalized_value))
+                            .collect(),
+                    ))
+                    .render(&canvas)
+            })
+        });
+        if let Some(Err(e)) = result {
+            set_error.set(Some(format!("Failed to render chart: {:?}", e)));
+        }
+    });
+
+    let add_asset = move |asset_id: String| {
+        set_holdings.update(|holdings| {
+            if holdings.iter().any(|(id, _)| *id == asset_id) {
+                return;
+            }
+            holdings.push((asset_id, 0.0));
+            // A new holding gets an equal share; the others make room
+            let count = holdings.len();
+            update_weights(holdings, |w| set_weight(w, count - 1, 100.0 / count as f64));
+        });
+    };
+
+    let remove_asset = move |asset_id: String| {
+        set_holdings.update(|holdings| {
+            holdings.retain(|(id, _)| *id != asset_id);
+            update_weights(holdings, normalize);
+        });
+    };
+
+    let change_weight = move |index: usize, value: f64| {
+        set_holdings.update(|holdings| update_weights(holdings, |w| set_weight(w, index, value)));
+    };
+
+    let save = move |_| {
+        let request = CreatePortfolioRequest {
+            name: name.get_untracked().trim().to_string(),
+            assets: holdings.with_untracked(|h| portfolio_assets(h)),
+            rebalance: rebalance.get_untracked(),
+        };
+        if request.name.is_empty() {
+            set_error.set(Some("Give the portfolio a name".to_string()));
+            return;
+        }
+        let navigate = navigate.clone();
+        spawn_local(async move {
+            set_saving.set(true);
+            match api::create_portfolio(&request).await {
+                Ok(portfolio) => {
+                    navigate(&format!("/portfolios/{}", portfolio.id), NavigateOptions::default());
+                }
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+            set_saving.set(false);
+        });
+    };
+
+    view! {
+        <div>
+            <div class="card">
+                <h2>"New portfolio"</h2>
+                <div class="chart-controls comparison-options">
+                    <label>
+                        "Name "
+                        <input
+                            type="text"
+                            placeholder="e.g. 60/40 with Bitcoin"
+                            prop:value=move || name.get()
+                            on:input=move |ev| set_name.set(event_target_value(&ev))
+                        />
+                    </label>
+                    <label>
+                        "Rebalance "
+                        <select
+                            prop:value=move || rebalance.get().as_str()
+                            on:change=move |ev| {
+                                if let Ok(rule) = event_target_value(&ev).parse() {
+                                    set_rebalance.set(rule);
+                                }
+                            }
+                        >
+                            {RebalanceRule::ALL
+                                .into_iter()
+                                .map(|rule| view! {
+                                    <option value=rule.as_str()>{rebalance_label(rule)}</option>
+                                })
+                                .collect_view()}
+                        </select>
+                    </label>
+                </div>
+
+                <div class="asset-list">
+                    {move || {
+                        let held = holdings.with(|h| h.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>());
+                        assets
+                            .get()
+                            .into_iter()
+                            .map(|asset| {
+                                let is_held = held.contains(&asset.id);
+                                let asset_id = asset.id.clone();
+                                view! {
+                                    <div
+                                        class=if is_held { "asset-item selected" } else { "asset-item" }
+                                        on:click=move |_| {
+                                            if is_held {
+                                                remove_asset(asset_id.clone())
+                                            } else {
+                                                add_asset(asset_id.clone())
+                                            }
+                                        }
+                                    >
+                                        <div class="asset-name">{asset.name}</div>
+                                        <div class="asset-symbol">{asset.symbol}</div>
+                                    </div>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                </div>
+
+                <div class="weights">
+                    {move || {
+                        holdings
+                            .get()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, (asset_id, weight))| view! {
+                                <div class="weight-row">
+                                    <span class="asset-name">{asset_id}</span>
+                                    <input
+                                        type="range"
+                                        min="0"
+                                        max="100"
+                                        step="0.1"
+                                        prop:value=weight.to_string()
+                                        on:change=move |ev| {
+                                            if let Ok(value) = event_target_value(&ev).parse() {
+                                                change_weight(index, value)
+                                            }
+                                        }
+                                    />
+                                    <input
+                                        type="number"
+                                        min="0"
+                                        max="100"
+                                        step="0.1"
+                                        prop:value=format!("{:.1}", weight)
+                                        on:change=move |ev| {
+                                            if let Ok(value) = event_target_value(&ev).parse() {
+                                                change_weight(index, value)
+                                            }
+                                        }
+                                    />
+                                    "%"
+                                </div>
+                            })
+                            .collect_view()
+                    }}
+                </div>
+            </div>
+
+            {move || error.get().map(|err| view! {
+                <div class="error">
+                    <p><strong>"Error: "</strong> {err}</p>
+                </div>
+            })}
+
+            <div class="card">
+                <div class="chart-controls">
+                    <span class="chart-hint">
+                        {move || format!("Backtest of {:.0} over the last {} years", PREVIEW_AMOUNT, preview_years.get())}
+                    </span>
+                    <label>
+                        "Preview "
+                        <select
+                            prop:value=move || preview_years.get().to_string()
+                            on:change=move |ev| {
+                                if let Ok(years) = event_target_value(&ev).parse() {
+                                    set_preview_years.set(years);
+                                }
+                            }
+                        >
+                            {PREVIEW_YEARS
+                                .into_iter()
+                                .map(|years| view! {
+                                    <option value=years.to_string()>{format!("{}Y", years)}</option>
+                                })
+                                .collect_view()}
+                        </select>
+                    </label>
+                </div>
+                {move || {
+                    if holdings.with(Vec::is_empty) {
+                        view! {
+                            <p class="subtitle">"Pick assets to preview the portfolio"</p>
+                        }.into_any()
+                    } else {
+                        view! { <canvas node_ref=canvas_ref></canvas> }.into_any()
+                    }
+                }}
+                {move || preview.with(|p| p.as_ref().and_then(|p| p.metrics.clone())).map(|m| view! {
+                    <p class="subtitle">
+                        {format!(
+                            "Total return {:+.2}% · CAGR {:+.2}% · Max drawdown {:+.2}% · Sharpe {}",
+                            m.total_return_pct,
+                            m.annualized_return_pct,
+                            m.max_drawdown_pct,
+                            m.sharpe_ratio.map_or("–".to_string(), |s| format!("{:.2}", s)),
+                        )}
+                    </p>
+                })}
+            </div>
+
+            <button
+                on:click=save
+                disabled=move || saving.get() || holdings.with(Vec::is_empty)
+            >
+                {move || if saving.get() { "Saving..." } else { "Save portfolio" }}
+            </button>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_weights(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_set_weight_scales_the_others_to_100() {
+        let mut weights = vec![50.0, 30.0, 20.0];
+        set_weight(&mut weights, 0, 80.0);
+        assert_weights(&weights, &[80.0, 12.0, 8.0]);
+
+        // Out-of-range input is clamped
+        set_weight(&mut weights, 1, 150.0);
+        assert_weights(&weights, &[0.0, 100.0, 0.0]);
+        set_weight(&mut weights, 1, -10.0);
+        // The others were all zero, so they share the rest evenly
+        assert_weights(&weights, &[50.0, 0.0, 50.0]);
+    }
+
+    #[test]
+    fn test_set_weight_of_single_asset_stays_100() {
+        let mut weights = vec![100.0];
+        set_weight(&mut weights, 0, 40.0);
+        assert_weights(&weights, &[100.0]);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut weights = vec![1.0, 3.0];
+        normalize(&mut weights);
+        assert_weights(&weights, &[25.0, 75.0]);
+
+        let mut zeros = vec![0.0, 0.0, 0.0, 0.0];
+        normalize(&mut zeros);
+        assert_weights(&zeros, &[25.0; 4]);
+    }
+
+    #[test]
+    fn test_portfolio_assets_sum_to_one() {
+        let holdings = vec![
+            ("SPY".to_string(), 60.0),
+            ("BTC".to_string(), 30.0),
+            ("GLD".to_string(), 10.000001),
+        ];
+        let assets = portfolio_assets(&holdings);
+        assert_eq!(assets[0].asset_id, "SPY");
+        assert!((assets.iter().map(|a| a.weight).sum::<f64>() - 1.0).abs() < 1e-12);
+
+        let zeros = vec![("SPY".to_string(), 0.0), ("BTC".to_string(), 0.0)];
+        let weights: Vec<f64> = portfolio_assets(&zeros).iter().map(|a| a.weight).collect();
+        assert_weights(&weights, &[0.5, 0.5]);
+    }
+}
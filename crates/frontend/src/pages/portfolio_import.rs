@@ -0,0 +1,307 @@
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::hooks::use_navigate;
+use leptos_router::NavigateOptions;
+use shared::{CsvColumnMapping, ImportKind, ImportPortfolioRequest, ImportPreview, RebalanceRule};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::HtmlInputElement;
+use crate::api;
+use crate::pages::portfolio_builder::rebalance_label;
+
+type Column = fn(&mut CsvColumnMapping) -> &mut Option<String>;
+
+/// Columns to map for each kind of export
+fn mapped_columns(kind: ImportKind) -> [(&'static str, Column); 3] {
+    let symbol: (&str, Column) = ("Symbol", |m| &mut m.symbol);
+    let quantity: (&str, Column) = ("Quantity", |m| &mut m.quantity);
+    match kind {
+        ImportKind::Positions => [symbol, quantity, ("Market value", |m| &mut m.market_value)],
+        ImportKind::Transactions => [symbol, quantity, ("Buy/sell", |m| &mut m.action)],
+    }
+}
+
+/// Text of the file picked in `input`, with its name
+async fn read_file(input: &HtmlInputElement) -> Result<Option<(String, String)>, String> {
+    let Some(file) = input.files().and_then(|files| files.get(0)) else {
+        return Ok(None);
+    };
+    let text = JsFuture::from(file.text())
+        .await
+        .map_err(|e| format!("Failed to read {}: {:?}", file.name(), e))?;
+    Ok(Some((file.name(), text.as_string().unwrap_or_default())))
+}
+
+/// Upload a broker CSV of positions or transactions, map its columns and
+/// preview the resulting weights before saving them as a portfolio
+#[component]
+pub fn PortfolioImport() -> impl IntoView {
+    let navigate = use_navigate();
+    let (csv, set_csv) = signal(None::<String>);
+    let (kind, set_kind) = signal(ImportKind::Positions);
+    let (mapping, set_mapping) = signal(CsvColumnMapping::default());
+    let (preview, set_preview) = signal(None::<ImportPreview>);
+    let (name, set_name) = signal(String::new());
+    let (rebalance, set_rebalance) = signal(RebalanceRule::Never);
+    let (error, set_error) = signal(None::<String>);
+    let (importing, set_importing) = signal(false);
+    // Only the latest dry run may update the preview
+    let preview_generation = StoredValue::new(0u64);
+
+    let request = move |dry_run: bool| {
+        csv.get().map(|csv| ImportPortfolioRequest {
+            csv,
+            kind: kind.get(),
+            mapping: mapping.get(),
+            name: name.get_untracked().trim().to_string(),
+            rebalance: rebalance.get_untracked(),
+            dry_run,
+        })
+    };
+
+    Effect::new(move |_| {
+        let request = request(true);
+        preview_generation.update_value(|g| *g += 1);
+        let generation = preview_generation.get_value();
+        let Some(request) = request else {
+            set_preview.set(None);
+            return;
+        };
+        spawn_local(async move {
+            let result = api::import_portfolio(&request).await;
+            if preview_generation.get_value() != generation {
+                return;
+            }
+            match result {
+                Ok(response) => {
+                    set_preview.set(Some(response));
+                    set_error.set(None);
+                }
+                Err(e) => {
+                    set_preview.set(None);
+                    set_error.set(Some(e.to_string()));
+                }
+            }
+        });
+    });
+
+    let pick_file = move |ev| {
+        let input = event_target::<HtmlInputElement>(&ev);
+        spawn_local(async move {
+            match read_file(&input).await {
+                Ok(Some((file_name, text))) => {
+                    if name.with_untracked(|n| n.trim().is_empty()) {
+                        let stem = file_name.rsplit_once('.').map_or(file_name.as_str(), |(stem, _)| stem);
+                        set_name.set(stem.to_string());
+                    }
+                    // A new file gets its columns detected afresh
+                    set_mapping.set(CsvColumnMapping::default());
+                    set_csv.set(Some(text));
+                }
+                Ok(None) => set_csv.set(None),
+                Err(e) => set_error.set(Some(e)),
+            }
+        });
+    };
+
+    let save = move |_| {
+        let Some(request) = untrack(|| request(false)) else {
+            return;
+        };
+        if request.name.is_empty() {
+            set_error.set(Some("Give the portfolio a name".to_string()));
+            return;
+        }
+        let navigate = navigate.clone();
+        spawn_local(async move {
+            set_importing.set(true);
+            match api::import_portfolio(&request).await {
+                Ok(ImportPreview { portfolio: Some(portfolio), .. }) => {
+                    navigate(&format!("/portfolios/{}", portfolio.id), NavigateOptions::default());
+                }
+                Ok(_) => set_error.set(Some("The server did not save the portfolio".to_string())),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+            set_importing.set(false);
+        });
+    };
+
+    let can_import = move || {
+        preview.with(|p| p.as_ref().is_some_and(|p| p.mapping_error.is_none() && !p.holdings.is_empty()))
+    };
+
+    view! {
+        <div>
+            <div class="card">
+                <h2>"Import portfolio"</h2>
+                <p class="subtitle">
+                    "Upload a CSV exported from your broker. Holdings are weighted by market value, \
+                     or by quantity at the latest stored price."
+                </p>
+                <div class="chart-controls comparison-options">
+                    <label>
+                        "File "
+                        <input type="file" accept=".csv,text/csv" on:change=pick_file/>
+                    </label>
+                    <label>
+                        "Contains "
+                        <select
+                            on:change=move |ev| {
+                                set_kind.set(match event_target_value(&ev).as_str() {
+                                    "transactions" => ImportKind::Transactions,
+                                    _ => ImportKind::Positions,
+                                });
+                                set_mapping.set(CsvColumnMapping::default());
+                            }
+                        >
+                            <option value="positions">"Positions"</option>
+                            <option value="transactions">"Transactions"</option>
+                        </select>
+                    </label>
+                </div>
+
+                {move || preview.get().map(|preview| {
+                    let columns = preview.columns;
+                    let mut detected = preview.mapping;
+                    view! {
+                        <div class="chart-controls comparison-options">
+                            {mapped_columns(kind.get())
+                                .into_iter()
+                                .map(|(label, column)| {
+                                    let selected = column(&mut detected).clone().unwrap_or_default();
+                                    view! {
+                                        <label>
+                                            {format!("{} ", label)}
+                                            <select
+                                                prop:value=selected
+                                                on:change=move |ev| {
+                                                    let value = event_target_value(&ev);
+                                                    set_mapping.update(|m| {
+                                                        *column(m) = (!value.is_empty()).then_some(value);
+                                                    });
+                                                }
+                                            >
+                                                <option value="">"Detect"</option>
+                                                {columns
+                                                    .iter()
+                                                    .filter(|c| !c.is_empty())
+                                                    .map(|c| view! { <option value=c.clone()>{c.clone()}</option> })
+                                                    .collect_view()}
+                                            </select>
+                                        </label>
+                                    }
+                                })
+                                .collect_view()}
+                        </div>
+                        {preview.mapping_error.map(|e| view! {
+                            <p class="subtitle">{format!("Map the columns to continue: {}", e)}</p>
+                        })}
+                    }
+                })}
+            </div>
+
+            {move || error.get().map(|err| view! {
+                <div class="error">
+                    <p><strong>"Error: "</strong> {err}</p>
+                </div>
+            })}
+
+            {move || preview.get().filter(|p| p.mapping_error.is_none()).map(|preview| view! {
+                <div class="card">
+                    <h2>"Preview"</h2>
+                    {if preview.holdings.is_empty() {
+                        view! { <p class="subtitle">"No holdings of tracked assets found"</p> }.into_any()
+                    } else {
+                        view! {
+                            <table class="metrics-table">
+                                <thead>
+                                    <tr>
+                                        <th>"Asset"</th>
+                                        <th>"Quantity"</th>
+                                        <th>"Market value"</th>
+                                        <th>"Weight"</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {preview
+                                        .holdings
+                                        .into_iter()
+                                        .map(|h| view! {
+                                            <tr>
+                                                <td class="asset">{h.asset_id}</td>
+                                                <td>{format!("{:.4}", h.quantity)}</td>
+                                                <td>{format!("{:.2}", h.market_value)}</td>
+                                                <td>{format!("{:.1}%", h.weight * 100.0)}</td>
+                                            </tr>
+                                        })
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                        }.into_any()
+                    }}
+                    {(!preview.unknown_symbols.is_empty()).then(|| view! {
+                        <p class="subtitle">
+                            {format!("Not tracked, left out: {}", preview.unknown_symbols.join(", "))}
+                        </p>
+                    })}
+                    {(!preview.rejected_rows.is_empty()).then(|| view! {
+                        <h3>"Rejected rows"</h3>
+                        <table class="metrics-table">
+                            <thead>
+                                <tr>
+                                    <th>"Line"</th>
+                                    <th>"Reason"</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {preview
+                                    .rejected_rows
+                                    .into_iter()
+                                    .map(|row| view! {
+                                        <tr>
+                                            <td class="asset">{row.line}</td>
+                                            <td class="asset">{row.reason}</td>
+                                        </tr>
+                                    })
+                                    .collect_view()}
+                            </tbody>
+                        </table>
+                    })}
+                </div>
+            })}
+
+            <div class="card">
+                <div class="chart-controls comparison-options">
+                    <label>
+                        "Name "
+                        <input
+                            type="text"
+                            prop:value=move || name.get()
+                            on:input=move |ev| set_name.set(event_target_value(&ev))
+                        />
+                    </label>
+                    <label>
+                        "Rebalance "
+                        <select
+                            prop:value=move || rebalance.get().as_str()
+                            on:change=move |ev| {
+                                if let Ok(rule) = event_target_value(&ev).parse() {
+                                    set_rebalance.set(rule);
+                                }
+                            }
+                        >
+                            {RebalanceRule::ALL
+                                .into_iter()
+                                .map(|rule| view! {
+                                    <option value=rule.as_str()>{rebalance_label(rule)}</option>
+                                })
+                                .collect_view()}
+                        </select>
+                    </label>
+                </div>
+                <button on:click=save disabled=move || importing.get() || !can_import()>
+                    {move || if importing.get() { "Importing..." } else { "Import portfolio" }}
+                </button>
+            </div>
+        </div>
+    }
+}
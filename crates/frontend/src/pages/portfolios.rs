@@ -0,0 +1,178 @@
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::components::A;
+use leptos_router::hooks::use_params_map;
+use shared::{ChartQuery, Portfolio};
+use crate::api;
+use crate::pages::portfolio_builder::rebalance_label;
+
+/// Link to the comparison page showing `portfolio` next to its holdings
+fn compare_href(portfolio: &Portfolio) -> String {
+    let mut query = ChartQuery::default();
+    let asset_ids: Vec<String> = std::iter::once(portfolio.comparison_id())
+        .chain(portfolio.assets.iter().map(|a| a.asset_id.clone()))
+        .collect();
+    query.set_asset_ids(&asset_ids);
+    format!("/compare?{}", query.to_query_string())
+}
+
+fn weights_summary(portfolio: &Portfolio) -> String {
+    portfolio
+        .assets
+        .iter()
+        .map(|a| format!("{} {:.0}%", a.asset_id, a.weight * 100.0))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+#[component]
+pub fn PortfolioList() -> impl IntoView {
+    let (portfolios, set_portfolios) = signal(Vec::<Portfolio>::new());
+    let (loading, set_loading) = signal(true);
+    let (error, set_error) = signal(None::<String>);
+
+    Effect::new(move |_| {
+        spawn_local(async move {
+            set_loading.set(true);
+            match api::fetch_portfolios().await {
+                Ok(response) => {
+                    set_portfolios.set(response.portfolios);
+                    set_error.set(None);
+                }
+                Err(e) => {
+                    set_error.set(Some(e.to_string()));
+                }
+            }
+            set_loading.set(false);
+        });
+    });
+
+    view! {
+        <div class="card">
+            <div class="chart-controls">
+                <h2>"Portfolios"</h2>
+                <span>
+                    <A href="/portfolios/new">"New portfolio"</A>
+                    " · "
+                    <A href="/portfolios/import">"Import CSV"</A>
+                </span>
+            </div>
+            {move || {
+                if loading.get() {
+                    view! {
+                        <div class="loading">
+                            <p>"Loading portfolios..."</p>
+                        </div>
+                    }.into_any()
+                } else if let Some(err) = error.get() {
+                    view! {
+                        <div class="error">
+                            <p><strong>"Error: "</strong> {err}</p>
+                        </div>
+                    }.into_any()
+                } else if portfolios.with(Vec::is_empty) {
+                    view! {
+                        <p class="subtitle">"No saved portfolios yet"</p>
+                    }.into_any()
+                } else {
+                    view! {
+                        <div class="asset-list">
+                            {portfolios
+                                .get()
+                                .into_iter()
+                                .map(|portfolio| view! {
+                                    <A href=format!("/portfolios/{}", portfolio.id)>
+                                        <div class="asset-item">
+                                            <div class="asset-name">{portfolio.name.clone()}</div>
+                                            <div class="asset-symbol">{weights_summary(&portfolio)}</div>
+                                        </div>
+                                    </A>
+                                })
+                                .collect_view()}
+                        </div>
+                    }.into_any()
+                }
+            }}
+        </div>
+    }
+}
+
+#[component]
+pub fn PortfolioDetail() -> impl IntoView {
+    let params = use_params_map();
+    let portfolio_id = Memo::new(move |_| params.with(|p| p.get("id").unwrap_or_default()));
+    let (portfolio, set_portfolio) = signal(None::<Portfolio>);
+    let (loading, set_loading) = signal(true);
+    let (error, set_error) = signal(None::<String>);
+
+    Effect::new(move |_| {
+        let id = portfolio_id.get();
+        spawn_local(async move {
+            set_loading.set(true);
+            match api::fetch_portfolio(&id).await {
+                Ok(response) => {
+                    set_portfolio.set(Some(response));
+                    set_error.set(None);
+                }
+                Err(e) => {
+                    set_portfolio.set(None);
+                    set_error.set(Some(if e.is_not_found() {
+                        format!("Portfolio {} not found", id)
+                    } else {
+                        e.to_string()
+                    }));
+                }
+            }
+            set_loading.set(false);
+        });
+    });
+
+    view! {
+        <div>
+            {move || {
+                if loading.get() {
+                    view! {
+                        <div class="loading">
+                            <p>"Loading portfolio..."</p>
+                        </div>
+                    }.into_any()
+                } else if let Some(err) = error.get() {
+                    view! {
+                        <div class="error">
+                            <p><strong>"Error: "</strong> {err}</p>
+                        </div>
+                    }.into_any()
+                } else if let Some(portfolio) = portfolio.get() {
+                    let compare = compare_href(&portfolio);
+                    view! {
+                        <div class="card">
+                            <h2>{portfolio.name.clone()}</h2>
+                            <p class="subtitle">
+                                {format!("Rebalanced: {}", rebalance_label(portfolio.rebalance))}
+                            </p>
+                            <div class="asset-list">
+                                {portfolio
+                                    .assets
+                                    .into_iter()
+                                    .map(|asset| view! {
+                                        <A href=format!("/assets/{}", asset.asset_id)>
+                                            <div class="asset-item">
+                                                <div class="asset-name">{asset.asset_id}</div>
+                                                <div class="asset-symbol">
+                                                    {format!("{:.1}%", asset.weight * 100.0)}
+                                                </div>
+                                            </div>
+                                        </A>
+                                    })
+                                    .collect_view()}
+                            </div>
+                            <A href=compare>"Compare with its holdings"</A>
+                        </div>
+                    }.into_any()
+                } else {
+                    view! { <div></div> }.into_any()
+                }
+            }}
+        </div>
+    }
+}
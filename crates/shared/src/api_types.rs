@@ -1,5 +1,6 @@
 use super::models::*;
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, SecondsFormat, Utc};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 /// API request/response types
 
@@ -15,3 +16,656 @@ pub struct CompareAssetsResponse {
     pub assets: Vec<Asset>,
     pub price_data: Vec<PricePoint>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetAssetsResponse {
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfoliosResponse {
+    pub portfolios: Vec<Portfolio>,
+}
+
+/// `POST /api/portfolios`; weights must sum to 1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePortfolioRequest {
+    pub name: String,
+    pub assets: Vec<PortfolioAsset>,
+    #[serde(default)]
+    pub rebalance: RebalanceRule,
+}
+
+/// Whether an imported CSV lists current positions or past transactions
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportKind {
+    #[default]
+    Positions,
+    /// Buys and sells, netted into positions
+    Transactions,
+}
+
+/// Header of the CSV column holding each field. Columns left unset are
+/// detected from header names common in broker exports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CsvColumnMapping {
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// Units held, or traded by a transaction
+    #[serde(default)]
+    pub quantity: Option<String>,
+    /// Market value of a position; weights use it rather than quantity
+    /// times the latest stored price when present
+    #[serde(default)]
+    pub market_value: Option<String>,
+    /// Buy or sell, for transactions
+    #[serde(default)]
+    pub action: Option<String>,
+}
+
+/// `POST /api/portfolios/import`: a portfolio weighted by the holdings in
+/// a broker CSV export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPortfolioRequest {
+    pub csv: String,
+    #[serde(default)]
+    pub kind: ImportKind,
+    #[serde(default)]
+    pub mapping: CsvColumnMapping,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub rebalance: RebalanceRule,
+    /// Only report what would be imported
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportedHolding {
+    pub asset_id: String,
+    /// Units held; net of sales for transactions
+    pub quantity: f64,
+    pub market_value: f64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RejectedRow {
+    /// 1-based line in the CSV
+    pub line: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+    /// Header of the CSV, to map columns from
+    pub columns: Vec<String>,
+    /// Mapping used, including detected columns
+    pub mapping: CsvColumnMapping,
+    /// Why the mapping can't be used, e.g. no symbol column
+    pub mapping_error: Option<String>,
+    pub holdings: Vec<ImportedHolding>,
+    /// Symbols not matching any tracked asset
+    pub unknown_symbols: Vec<String>,
+    pub rejected_rows: Vec<RejectedRow>,
+    /// The saved portfolio; absent on a dry run
+    pub portfolio: Option<Portfolio>,
+}
+
+/// `POST /api/portfolios/backtest`: value of a weighted portfolio over time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestRequest {
+    pub assets: Vec<PortfolioAsset>,
+    #[serde(default)]
+    pub rebalance: RebalanceRule,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub initial_amount: f64,
+    /// Downsample the curve to at most this many points
+    #[serde(default)]
+    pub max_points: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestResponse {
+    pub series: AssetSeries,
+    /// `None` when the assets share fewer than two dates
+    pub metrics: Option<PerformanceMetrics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparisonRequest {
+    pub asset_ids: Vec<String>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub initial_amount: f64,
+    #[serde(default)]
+    pub return_basis: ReturnBasis,
+    /// Derive prices from this field of the daily bars instead of the
+    /// stored price points. Adjusted closes include dividends, so with
+    /// `PriceReturn` the split-neutralized close is used instead.
+    #[serde(default)]
+    pub price_field: Option<PriceField>,
+    /// Range-based estimators read the daily bars for each asset, adjusted
+    /// for corporate actions, and can't be combined with `unit_of_account`
+    #[serde(default)]
+    pub volatility_estimator: VolatilityEstimator,
+    /// Reduce each returned series to at most this many points; metrics are
+    /// still computed from the full series
+    #[serde(default)]
+    pub max_points: Option<usize>,
+    #[serde(default)]
+    pub downsample: DownsampleMethod,
+    /// Asset id to price every series in instead of USD, e.g. "XAU" for
+    /// performance measured in gold
+    #[serde(default)]
+    pub unit_of_account: Option<String>,
+    /// Only return series points after this time, for clients extending a
+    /// comparison they already have. Metrics and events still cover the
+    /// whole range. Can't be combined with `max_points`.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparisonResponse {
+    pub series: Vec<AssetSeries>,
+    pub metrics: Vec<PerformanceMetrics>,
+    /// Market-wide events and events for the compared assets, or the
+    /// holdings of compared portfolios, that overlap the requested range
+    #[serde(default)]
+    pub events: Vec<MarketEvent>,
+}
+
+/// Comparison state as a query string, shared by the `/compare` page URL
+/// and the server-rendered chart, e.g.
+/// `/api/chart.svg?assets=SPY,BTC&start=2024-01-01&unit=XAU&scale=log`.
+/// Fields left at their defaults are omitted when serializing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChartQuery {
+    /// Comma-separated asset ids
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub assets: String,
+    /// Date (`2024-01-01`) or RFC 3339 timestamp; defaults to one year
+    /// before `end`
+    #[serde(
+        default,
+        deserialize_with = "optional_date_or_timestamp",
+        serialize_with = "serialize_date_or_timestamp",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start: Option<DateTime<Utc>>,
+    /// Defaults to now
+    #[serde(
+        default,
+        deserialize_with = "optional_date_or_timestamp",
+        serialize_with = "serialize_date_or_timestamp",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub end: Option<DateTime<Utc>>,
+    /// Initial amount each series is normalized to
+    #[serde(
+        default = "default_initial_amount",
+        skip_serializing_if = "is_default_initial_amount"
+    )]
+    pub amount: f64,
+    /// Asset id to price everything in instead of USD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub view: ChartView,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub scale: ChartScale,
+    #[serde(
+        default = "default_chart_width",
+        skip_serializing_if = "is_default_chart_width"
+    )]
+    pub width: u32,
+    #[serde(
+        default = "default_chart_height",
+        skip_serializing_if = "is_default_chart_height"
+    )]
+    pub height: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Draw markers for events in the range
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub events: bool,
+}
+
+impl Default for ChartQuery {
+    fn default() -> Self {
+        Self {
+            assets: String::new(),
+            start: None,
+            end: None,
+            amount: default_initial_amount(),
+            unit: None,
+            view: ChartView::default(),
+            scale: ChartScale::default(),
+            width: default_chart_width(),
+            height: default_chart_height(),
+            title: None,
+            events: true,
+        }
+    }
+}
+
+impl ChartQuery {
+    pub fn asset_ids(&self) -> Vec<String> {
+        self.assets
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn set_asset_ids(&mut self, asset_ids: &[String]) {
+        self.assets = asset_ids.join(",");
+    }
+
+    /// Parse a query string, with or without the leading `?`
+    pub fn from_query_string(query: &str) -> Result<Self, String> {
+        serde_urlencoded::from_str(query.trim_start_matches('?')).map_err(|e| e.to_string())
+    }
+
+    /// Commas separating asset ids are left unescaped to keep links readable
+    pub fn to_query_string(&self) -> String {
+        serde_urlencoded::to_string(self)
+            .unwrap_or_default()
+            .replace("%2C", ",")
+    }
+
+    /// The comparison to run, resolving open dates against `now`
+    pub fn comparison_request(&self, now: DateTime<Utc>) -> ComparisonRequest {
+        let end_date = self.end.unwrap_or(now);
+        ComparisonRequest {
+            asset_ids: self.asset_ids(),
+            start_date: self.start.unwrap_or(end_date - Duration::days(365)),
+            end_date,
+            initial_amount: self.amount,
+            return_basis: Default::default(),
+            price_field: None,
+            volatility_estimator: Default::default(),
+            // Charts downsample to their own width
+            max_points: None,
+            downsample: Default::default(),
+            unit_of_account: self.unit.clone().filter(|unit| !unit.is_empty()),
+            since: None,
+        }
+    }
+}
+
+fn default_initial_amount() -> f64 {
+    10000.0
+}
+
+fn is_default_initial_amount(amount: &f64) -> bool {
+    *amount == default_initial_amount()
+}
+
+fn default_chart_width() -> u32 {
+    1000
+}
+
+fn is_default_chart_width(width: &u32) -> bool {
+    *width == default_chart_width()
+}
+
+fn default_chart_height() -> u32 {
+    450
+}
+
+fn is_default_chart_height(height: &u32) -> bool {
+    *height == default_chart_height()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+fn parse_date_or_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(NaiveTime::MIN).and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| format!("expected a date or RFC 3339 timestamp, got {:?}", value))
+}
+
+fn optional_date_or_timestamp<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) if value.is_empty() => Ok(None),
+        Some(value) => parse_date_or_timestamp(&value)
+            .map(Some)
+            .map_err(de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+/// Midnight as a plain date, anything else as an RFC 3339 timestamp
+fn serialize_date_or_timestamp<S: Serializer>(
+    value: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(t) if t.time() == NaiveTime::MIN => {
+            serializer.serialize_str(&t.format("%Y-%m-%d").to_string())
+        }
+        Some(t) => serializer.serialize_str(&t.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsertCorporateActionsRequest {
+    pub actions: Vec<CorporateAction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorporateActionsResponse {
+    pub actions: Vec<CorporateAction>,
+}
+
+/// Metrics over a trailing window ending at an asset's latest price
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowMetrics {
+    /// "1M", "YTD", "Max", ...
+    pub window: String,
+    pub metrics: PerformanceMetrics,
+}
+
+/// `GET /api/assets/:id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetDetailResponse {
+    pub asset: Asset,
+    /// `None` until prices have been loaded
+    pub coverage: Option<DataCoverage>,
+    pub point_count: usize,
+    /// Stretches between consecutive prices longer than the asset's
+    /// trading calendar explains
+    pub gap_count: usize,
+    /// Providers that have supplied prices, most recent first
+    pub providers: Vec<String>,
+    pub last_refresh: Option<DateTime<Utc>>,
+    /// Only windows the stored history fully covers
+    pub windows: Vec<WindowMetrics>,
+}
+
+/// `GET /api/assets/:id/history`; the full history by default
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetHistoryQuery {
+    #[serde(default)]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end_date: Option<DateTime<Utc>>,
+    /// Downsample to at most this many prices
+    #[serde(default)]
+    pub max_points: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetHistoryResponse {
+    pub asset_id: String,
+    pub prices: Vec<PricePoint>,
+}
+
+/// `GET /api/prices/stream?assets=SPY,BTC`: Server-Sent Events with new
+/// prices as they are stored
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceStreamQuery {
+    /// Comma-separated asset ids; all assets when absent
+    #[serde(default)]
+    pub assets: Option<String>,
+    /// Resume after this event, for clients that can't send the
+    /// `Last-Event-ID` header
+    #[serde(default)]
+    pub last_event_id: Option<String>,
+}
+
+/// Event carrying a [`PriceUpdate`]
+pub const PRICE_EVENT: &str = "prices";
+
+/// Event telling a client that updates were missed, e.g. after a long
+/// disconnect or a server restart, so it should reload what it shows
+pub const RESET_EVENT: &str = "reset";
+
+/// Prices just written for one asset, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceUpdate {
+    pub asset_id: String,
+    pub prices: Vec<PricePoint>,
+}
+
+/// `GET /api/coverage?assets=SPY,BTC`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageQuery {
+    /// Comma-separated asset ids
+    pub assets: String,
+}
+
+/// Span of stored prices for one asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataCoverage {
+    pub asset_id: String,
+    pub first_date: DateTime<Utc>,
+    pub last_date: DateTime<Utc>,
+}
+
+/// Assets without any stored prices are left out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageResponse {
+    pub coverage: Vec<DataCoverage>,
+}
+
+impl CoverageResponse {
+    /// Latest first date among the assets: the earliest start at which
+    /// every one of them has data, with the asset that limits it
+    pub fn common_start(&self) -> Option<&DataCoverage> {
+        self.coverage.iter().max_by_key(|c| c.first_date)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsQuery {
+    #[serde(default)]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub category: Option<EventCategory>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateEventRequest {
+    #[serde(default = "default_event_category")]
+    pub category: EventCategory,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub asset_id: Option<String>,
+    pub start_date: DateTime<Utc>,
+    #[serde(default)]
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+fn default_event_category() -> EventCategory {
+    EventCategory::Note
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsResponse {
+    pub events: Vec<MarketEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshDataRequest {
+    pub asset_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshDataResponse {
+    pub updated_assets: Vec<String>,
+    pub points_added: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskRequest {
+    /// Weighted holdings to report on; ignored when `portfolio_id` is set
+    #[serde(default)]
+    pub holdings: Vec<PortfolioAsset>,
+    /// Load holdings from a saved portfolio instead
+    #[serde(default)]
+    pub portfolio_id: Option<String>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(default = "default_confidence_levels")]
+    pub confidence_levels: Vec<f64>,
+    /// Each from 1 to 252 days; at most 100 measures are computed across
+    /// methods, confidence levels and horizons
+    #[serde(default = "default_horizons")]
+    pub horizons_days: Vec<usize>,
+    #[serde(default = "default_var_methods")]
+    pub methods: Vec<VarMethod>,
+}
+
+fn default_confidence_levels() -> Vec<f64> {
+    vec![0.95, 0.99]
+}
+
+fn default_horizons() -> Vec<usize> {
+    vec![1]
+}
+
+fn default_var_methods() -> Vec<VarMethod> {
+    vec![
+        VarMethod::Historical,
+        VarMethod::Parametric,
+        VarMethod::CornishFisher,
+    ]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskResponse {
+    pub assets: Vec<RiskReport>,
+    pub portfolio: RiskReport,
+}
+
+/// What the mean-variance optimizer should solve for
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OptimizationObjective {
+    MinVariance,
+    MaxSharpe,
+    TargetReturn { target_return_pct: f64 },
+}
+
+/// Allowed weight range for one asset (fractions, e.g. 0.0..=0.4)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WeightBound {
+    pub asset_id: String,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationRequest {
+    /// Assets to include; empty means every asset in the `assets` table
+    #[serde(default)]
+    pub asset_ids: Vec<String>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub objective: OptimizationObjective,
+    /// Per-asset overrides of the default long-only `0.0..=1.0` range
+    #[serde(default)]
+    pub bounds: Vec<WeightBound>,
+    /// Annual risk-free rate used for Sharpe ratios
+    #[serde(default)]
+    pub risk_free_rate_pct: f64,
+    /// Number of points to sample along the efficient frontier, at most 200
+    #[serde(default = "default_frontier_points")]
+    pub frontier_points: usize,
+    /// Save the optimal weights as a new portfolio with this name
+    #[serde(default)]
+    pub save_as: Option<String>,
+}
+
+fn default_frontier_points() -> usize {
+    20
+}
+
+/// A set of weights with its annualized risk/return profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizedPortfolio {
+    pub weights: Vec<PortfolioAsset>,
+    pub expected_return_pct: f64,
+    pub volatility_pct: f64,
+    pub sharpe_ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationResponse {
+    pub portfolio: OptimizedPortfolio,
+    pub frontier: Vec<OptimizedPortfolio>,
+    pub saved_portfolio: Option<Portfolio>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_chart_query_round_trips_through_query_string() {
+        let query = ChartQuery {
+            assets: "SPY,BTC".to_string(),
+            start: Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()),
+            end: Some(Utc.with_ymd_and_hms(2024, 6, 30, 16, 0, 0).unwrap()),
+            amount: 2500.0,
+            unit: Some("XAU".to_string()),
+            scale: ChartScale::Log,
+            ..Default::default()
+        };
+
+        let encoded = query.to_query_string();
+        assert_eq!(
+            encoded,
+            "assets=SPY,BTC&start=2024-01-01&end=2024-06-30T16%3A00%3A00Z&amount=2500.0&unit=XAU&scale=log"
+        );
+        assert_eq!(ChartQuery::from_query_string(&format!("?{}", encoded)), Ok(query));
+        assert_eq!(ChartQuery::default().to_query_string(), "");
+    }
+
+    #[test]
+    fn test_chart_query_resolves_open_dates() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 30, 0, 0, 0).unwrap();
+        let query = ChartQuery::from_query_string("assets=SPY&unit=").unwrap();
+
+        let request = query.comparison_request(now);
+        assert_eq!(request.asset_ids, vec!["SPY"]);
+        assert_eq!(request.end_date, now);
+        assert_eq!(request.start_date, now - Duration::days(365));
+        assert_eq!(request.initial_amount, 10000.0);
+        assert_eq!(request.unit_of_account, None);
+    }
+}
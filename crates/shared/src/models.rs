@@ -1,5 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// Types of assets we track
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -9,6 +11,45 @@ pub enum AssetType {
     Commodity, // Gold
 }
 
+impl AssetType {
+    /// Longest normal spacing between daily prices: every day for crypto,
+    /// weekdays only with the odd long weekend for everything else
+    pub fn max_price_spacing_days(&self) -> i64 {
+        match self {
+            AssetType::Crypto => 1,
+            _ => 4,
+        }
+    }
+
+    /// Representation used in the `assets.asset_type` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetType::Stock => "STOCK",
+            AssetType::Crypto => "CRYPTO",
+            AssetType::Commodity => "COMMODITY",
+        }
+    }
+}
+
+impl fmt::Display for AssetType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AssetType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "STOCK" => Ok(AssetType::Stock),
+            "CRYPTO" => Ok(AssetType::Crypto),
+            "COMMODITY" => Ok(AssetType::Commodity),
+            other => Err(format!("unknown asset type: {}", other)),
+        }
+    }
+}
+
 /// Individual asset (e.g., "BTC", "QQQ")
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Asset {
@@ -18,6 +59,28 @@ pub struct Asset {
     pub asset_type: AssetType,
 }
 
+impl Asset {
+    pub fn new(id: &str, symbol: &str, name: &str, asset_type: AssetType) -> Self {
+        Self {
+            id: id.to_string(),
+            symbol: symbol.to_string(),
+            name: name.to_string(),
+            asset_type,
+        }
+    }
+
+    /// Assets seeded by the initial migrations
+    pub fn all_default() -> Vec<Asset> {
+        vec![
+            Asset::new("QQQ", "QQQ", "Invesco QQQ Trust", AssetType::Stock),
+            Asset::new("SPY", "SPY", "S&P 500 ETF", AssetType::Stock),
+            Asset::new("IXIC", "^IXIC", "NASDAQ Composite", AssetType::Stock),
+            Asset::new("BTC", "BTC", "Bitcoin", AssetType::Crypto),
+            Asset::new("XAU", "XAU", "Gold", AssetType::Commodity),
+        ]
+    }
+}
+
 /// Price point at a specific time
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricePoint {
@@ -26,10 +89,408 @@ pub struct PricePoint {
     pub price: f64,
 }
 
+/// Sampling interval of a bar
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum BarInterval {
+    #[serde(rename = "1h")]
+    Hour,
+    #[serde(rename = "1d")]
+    Day,
+    #[serde(rename = "1w")]
+    Week,
+    #[serde(rename = "1mo")]
+    Month,
+}
+
+impl BarInterval {
+    /// Representation used in the `bars.interval` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BarInterval::Hour => "1h",
+            BarInterval::Day => "1d",
+            BarInterval::Week => "1w",
+            BarInterval::Month => "1mo",
+        }
+    }
+}
+
+impl FromStr for BarInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1h" => Ok(BarInterval::Hour),
+            "1d" => Ok(BarInterval::Day),
+            "1w" => Ok(BarInterval::Week),
+            "1mo" => Ok(BarInterval::Month),
+            other => Err(format!("unknown bar interval: {}", other)),
+        }
+    }
+}
+
+/// Which value of a bar to use when a single price per timestamp is needed
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceField {
+    Open,
+    High,
+    Low,
+    #[default]
+    Close,
+    AdjustedClose,
+}
+
+/// Open/high/low/close/volume bar for one interval
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Bar {
+    pub asset_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub interval: BarInterval,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Close adjusted for splits and dividends, when the provider supplies it
+    pub adjusted_close: Option<f64>,
+    pub volume: Option<f64>,
+}
+
+impl Bar {
+    /// Value of `field`; a missing adjusted close falls back to the close
+    pub fn price(&self, field: PriceField) -> f64 {
+        match field {
+            PriceField::Open => self.open,
+            PriceField::High => self.high,
+            PriceField::Low => self.low,
+            PriceField::Close => self.close,
+            PriceField::AdjustedClose => self.adjusted_close.unwrap_or(self.close),
+        }
+    }
+
+    pub fn to_price_point(&self, field: PriceField) -> PricePoint {
+        PricePoint {
+            asset_id: self.asset_id.clone(),
+            timestamp: self.timestamp,
+            price: self.price(field),
+        }
+    }
+}
+
+/// Corporate action affecting how raw closes translate into returns
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CorporateActionKind {
+    /// New shares per old share, e.g. 4.0 for a 4-for-1 split
+    Split { ratio: f64 },
+    /// Cash paid per share as of the ex-date
+    CashDividend { amount: f64 },
+}
+
+impl CorporateActionKind {
+    /// Representation used in the `corporate_actions.action_type` column
+    pub fn action_type(&self) -> &'static str {
+        match self {
+            CorporateActionKind::Split { .. } => "SPLIT",
+            CorporateActionKind::CashDividend { .. } => "DIVIDEND",
+        }
+    }
+
+    /// Split ratio or dividend amount
+    pub fn value(&self) -> f64 {
+        match self {
+            CorporateActionKind::Split { ratio } => *ratio,
+            CorporateActionKind::CashDividend { amount } => *amount,
+        }
+    }
+}
+
+/// Split or dividend for an asset, effective from `ex_date`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CorporateAction {
+    pub asset_id: String,
+    pub ex_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: CorporateActionKind,
+}
+
+/// Whether comparisons follow the raw price or reinvest dividends
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReturnBasis {
+    #[default]
+    PriceReturn,
+    TotalReturn,
+}
+
+/// How volatility is estimated from a series of bars
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VolatilityEstimator {
+    /// Standard deviation of close-to-close returns
+    #[default]
+    CloseToClose,
+    /// High/low range
+    Parkinson,
+    /// High/low range plus open/close
+    GarmanKlass,
+    /// Drift-independent high/low/open/close
+    RogersSatchell,
+    /// Rogers-Satchell plus overnight and open-to-close variance
+    YangZhang,
+}
+
+/// Algorithm used to thin out long series before sending or drawing them
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownsampleMethod {
+    /// Largest-Triangle-Three-Buckets, preserving the shape of the line
+    #[default]
+    Lttb,
+    /// Lowest and highest point per bucket, preserving every spike
+    MinMax,
+}
+
+/// Which comparison chart to draw
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartView {
+    /// Value of the initial amount over time
+    #[default]
+    Performance,
+    /// Distance below each asset's running peak
+    Drawdown,
+}
+
+/// Y axis of the performance chart
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartScale {
+    #[default]
+    Linear,
+    Log,
+    /// Percentage change from the start
+    Percent,
+}
+
+/// How Value-at-Risk and Expected Shortfall are estimated
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VarMethod {
+    /// Empirical quantile of past returns
+    Historical,
+    /// Normal distribution fitted to mean and standard deviation
+    Parametric,
+    /// Normal quantile corrected for skewness and excess kurtosis
+    CornishFisher,
+}
+
+/// Tail loss at one confidence level and horizon, as positive percentages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TailRiskMeasure {
+    pub method: VarMethod,
+    pub confidence: f64,
+    pub horizon_days: usize,
+    pub value_at_risk_pct: f64,
+    pub expected_shortfall_pct: f64,
+}
+
+/// Tail-risk summary for a single asset or a weighted portfolio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskReport {
+    /// Asset id, or the portfolio name for weighted holdings
+    pub label: String,
+    pub observations: usize,
+    pub skewness: f64,
+    pub excess_kurtosis: f64,
+    pub measures: Vec<TailRiskMeasure>,
+}
+
+/// Kind of event marked on time-series charts
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EventCategory {
+    Halving,
+    FedDecision,
+    Recession,
+    /// User-defined note
+    Note,
+}
+
+impl EventCategory {
+    pub const ALL: [EventCategory; 4] = [
+        EventCategory::Halving,
+        EventCategory::FedDecision,
+        EventCategory::Recession,
+        EventCategory::Note,
+    ];
+
+    /// Representation used in the `events.category` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventCategory::Halving => "HALVING",
+            EventCategory::FedDecision => "FED_DECISION",
+            EventCategory::Recession => "RECESSION",
+            EventCategory::Note => "NOTE",
+        }
+    }
+
+    /// Marker color shared by the web and server-rendered charts
+    pub fn color(&self) -> &'static str {
+        match self {
+            EventCategory::Halving => "#f59e0b",
+            EventCategory::FedDecision => "#a855f7",
+            EventCategory::Recession => "#94a3b8",
+            EventCategory::Note => "#14b8a6",
+        }
+    }
+}
+
+impl FromStr for EventCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "HALVING" => Ok(EventCategory::Halving),
+            "FED_DECISION" => Ok(EventCategory::FedDecision),
+            "RECESSION" => Ok(EventCategory::Recession),
+            "NOTE" => Ok(EventCategory::Note),
+            other => Err(format!("unknown event category: {}", other)),
+        }
+    }
+}
+
+/// Dated event such as a halving or rate decision, or a span such as a
+/// recession when `end_date` is set
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MarketEvent {
+    pub id: i64,
+    pub category: EventCategory,
+    pub title: String,
+    pub description: Option<String>,
+    /// Asset the event concerns, or `None` for market-wide events
+    pub asset_id: Option<String>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+/// Price point rescaled so the series starts at a common initial amount
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedPricePoint {
+    pub timestamp: DateTime<Utc>,
+    pub normalized_value: f64,
+    pub return_pct: f64,
+}
+
+/// Normalized time series for one asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetSeries {
+    pub asset_id: String,
+    /// Display name when it differs from the id, e.g. for portfolios
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub points: Vec<NormalizedPricePoint>,
+}
+
+impl AssetSeries {
+    pub fn display_name(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.asset_id)
+    }
+}
+
+/// Summary statistics for an asset over a date range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceMetrics {
+    pub asset_id: String,
+    pub total_return_pct: f64,
+    pub annualized_return_pct: f64,
+    /// Annualized standard deviation of periodic returns, in percent
+    pub volatility: f64,
+    /// Largest peak-to-trough decline, as a negative percentage
+    #[serde(default)]
+    pub max_drawdown_pct: f64,
+    /// Annualized mean return over volatility, with a zero risk-free rate;
+    /// `None` when returns don't vary
+    #[serde(default)]
+    pub sharpe_ratio: Option<f64>,
+    /// Like Sharpe, but only penalizing returns below zero
+    #[serde(default)]
+    pub sortino_ratio: Option<f64>,
+    /// Annualized return over the magnitude of the maximum drawdown
+    #[serde(default)]
+    pub calmar_ratio: Option<f64>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+}
+
+/// Prefix marking a saved portfolio among the asset ids of a comparison,
+/// e.g. `portfolio:3f2c...`
+pub const PORTFOLIO_ID_PREFIX: &str = "portfolio:";
+
 /// Portfolio composition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Portfolio {
     pub id: String,
     pub name: String,
-    pub assets: Vec<Asset>,
+    pub assets: Vec<PortfolioAsset>,
+    #[serde(default)]
+    pub rebalance: RebalanceRule,
+}
+
+impl Portfolio {
+    /// Id to compare this portfolio alongside single assets
+    pub fn comparison_id(&self) -> String {
+        format!("{}{}", PORTFOLIO_ID_PREFIX, self.id)
+    }
+}
+
+/// When a portfolio is traded back to its target weights
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RebalanceRule {
+    /// Buy and hold; weights drift with prices
+    #[default]
+    Never,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl RebalanceRule {
+    pub const ALL: [RebalanceRule; 4] = [
+        RebalanceRule::Never,
+        RebalanceRule::Monthly,
+        RebalanceRule::Quarterly,
+        RebalanceRule::Yearly,
+    ];
+
+    /// Representation used in the `portfolios.rebalance` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RebalanceRule::Never => "NEVER",
+            RebalanceRule::Monthly => "MONTHLY",
+            RebalanceRule::Quarterly => "QUARTERLY",
+            RebalanceRule::Yearly => "YEARLY",
+        }
+    }
+}
+
+impl FromStr for RebalanceRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "NEVER" => Ok(RebalanceRule::Never),
+            "MONTHLY" => Ok(RebalanceRule::Monthly),
+            "QUARTERLY" => Ok(RebalanceRule::Quarterly),
+            "YEARLY" => Ok(RebalanceRule::Yearly),
+            other => Err(format!("unknown rebalance rule: {}", other)),
+        }
+    }
+}
+
+/// Weight of a single asset within a portfolio (row of `portfolio_assets`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PortfolioAsset {
+    pub asset_id: String,
+    pub weight: f64,
 }